@@ -0,0 +1,39 @@
+use crate::int_enum;
+
+int_enum! {
+    /// 业务数据库名称，与配置文件中的数据源名称一致
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum DbName {
+        Phoenix = 1 => "sm_phoenix",
+        SakuraPay = 2 => "sakura_pay",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::EnumError;
+
+    #[test]
+    fn test_db_name_round_trip() {
+        for db in DbName::all() {
+            assert_eq!(db.as_str().parse::<DbName>(), Ok(*db));
+            assert_eq!(DbName::try_from(db.value()), Ok(*db));
+        }
+        assert_eq!(DbName::Phoenix.as_str(), "sm_phoenix");
+        assert_eq!(DbName::SakuraPay.to_string(), "sakura_pay");
+        assert_eq!(i32::from(DbName::SakuraPay), 2);
+    }
+
+    #[test]
+    fn test_db_name_unknown() {
+        assert_eq!(
+            DbName::try_from(99),
+            Err(EnumError::UnknownValue { type_name: "DbName", value: 99 })
+        );
+        assert_eq!(
+            "sm_unknown".parse::<DbName>(),
+            Err(EnumError::UnknownName { type_name: "DbName", name: "sm_unknown".to_string() })
+        );
+    }
+}
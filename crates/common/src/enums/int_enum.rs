@@ -0,0 +1,146 @@
+//! 整数枚举生成宏
+//!
+//! 为 C 风格的常量枚举生成 `TryFrom<整数>`、`as_str()`、`Display`、`FromStr` 和 `all()`，
+//! 避免每个枚举手写一遍相同的 match。整数类型默认为 `i32`，可以在枚举名后用 `: i8` 等指定，
+//! 数据库枚举宏 `rdatabase::db_int_enum!` 即以 `i8` 调用本宏。
+
+/// 枚举转换失败
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EnumError {
+    /// 整数值没有对应的成员，各种整数类型统一以 `i64` 记录
+    #[error("unknown {type_name} value: {value}")]
+    UnknownValue { type_name: &'static str, value: i64 },
+
+    #[error("unknown {type_name} name: {name}")]
+    UnknownName { type_name: &'static str, name: String },
+}
+
+/// 定义带整数值和名称的枚举
+///
+/// ```
+/// common::int_enum! {
+///     /// 订单状态
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     pub enum OrderState {
+///         Pending = 1 => "pending",
+///         Paid = 2 => "paid",
+///     }
+/// }
+///
+/// assert_eq!(OrderState::try_from(2), Ok(OrderState::Paid));
+/// assert_eq!(OrderState::Paid.as_str(), "paid");
+/// assert!(OrderState::try_from(3).is_err());
+///
+/// // 指定整数类型，支持不超过 32 位的整数
+/// common::int_enum! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     pub enum Flag: i8 {
+///         Off = 0 => "off",
+///         On = 1 => "on",
+///     }
+/// }
+///
+/// assert_eq!(Flag::try_from(1i8), Ok(Flag::On));
+/// assert_eq!(i8::from(Flag::Off), 0);
+/// ```
+#[macro_export]
+macro_rules! int_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident: $repr:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:literal => $str:literal
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr($repr)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant = $value,
+            )*
+        }
+
+        impl $name {
+            /// 枚举对应的整数值
+            pub fn value(&self) -> $repr {
+                match self {
+                    $(Self::$variant => $value,)*
+                }
+            }
+
+            /// 枚举对应的名称
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $str,)*
+                }
+            }
+
+            /// 所有枚举值，按定义顺序排列
+            pub fn all() -> &'static [$name] {
+                &[$(Self::$variant),*]
+            }
+        }
+
+        impl ::std::convert::TryFrom<$repr> for $name {
+            type Error = $crate::enums::EnumError;
+
+            fn try_from(value: $repr) -> ::std::result::Result<Self, Self::Error> {
+                match value {
+                    $($value => Ok(Self::$variant),)*
+                    _ => Err($crate::enums::EnumError::UnknownValue {
+                        type_name: stringify!($name),
+                        value: i64::from(value),
+                    }),
+                }
+            }
+        }
+
+        impl ::std::convert::From<$name> for $repr {
+            fn from(value: $name) -> $repr {
+                value.value()
+            }
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = $crate::enums::EnumError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                match s {
+                    $($str => Ok(Self::$variant),)*
+                    _ => Err($crate::enums::EnumError::UnknownName {
+                        type_name: stringify!($name),
+                        name: s.to_string(),
+                    }),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:literal => $str:literal
+            ),* $(,)?
+        }
+    ) => {
+        $crate::int_enum! {
+            $(#[$meta])*
+            $vis enum $name: i32 {
+                $(
+                    $(#[$variant_meta])*
+                    $variant = $value => $str
+                ),*
+            }
+        }
+    };
+}
@@ -1 +1,6 @@
 pub mod state_enum;
+pub mod int_enum;
+pub mod db_name;
+
+pub use int_enum::EnumError;
+pub use db_name::DbName;
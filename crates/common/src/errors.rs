@@ -0,0 +1,94 @@
+//! 错误码目录
+//!
+//! 各服务对外返回的错误码统一在这里定义，客户端按数字码处理错误而不是匹配提示文案。
+//! 错误码为五位数，前三位是对应的 HTTP 状态码，后两位是序号，已发布的错误码不能修改含义。
+
+use crate::int_enum;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+int_enum! {
+    /// 对外错误码，名称即默认提示信息
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum ErrorCode {
+        InvalidParameter = 40001 => "参数错误",
+        MissingParameter = 40002 => "缺少必要参数",
+        InvalidSignature = 40003 => "签名校验失败",
+        Unauthorized = 40101 => "未登录或登录已失效",
+        TokenExpired = 40102 => "令牌已过期",
+        Forbidden = 40301 => "没有操作权限",
+        NotFound = 40401 => "资源不存在",
+        OrderNotFound = 40402 => "订单不存在",
+        Conflict = 40901 => "资源状态冲突",
+        DuplicateRequest = 40902 => "重复请求",
+        ValidationFailed = 42201 => "参数校验失败",
+        TooManyRequests = 42901 => "请求过于频繁",
+        InternalError = 50001 => "服务内部错误",
+        DatabaseError = 50002 => "数据库错误",
+        ExternalServiceError = 50201 => "第三方服务错误",
+        ServiceUnavailable = 50301 => "服务暂不可用",
+        Timeout = 50401 => "请求超时",
+    }
+}
+
+impl ErrorCode {
+    /// 数字错误码
+    pub fn code(&self) -> i32 {
+        self.value()
+    }
+
+    /// 默认提示信息
+    pub fn message(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// 对应的 HTTP 状态码，取错误码的前三位
+    pub fn http_status(&self) -> u16 {
+        (self.code() / 100) as u16
+    }
+}
+
+/// 序列化为数字错误码
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = i32::deserialize(deserializer)?;
+        ErrorCode::try_from(code).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status_and_message() {
+        assert_eq!(ErrorCode::InvalidParameter.code(), 40001);
+        assert_eq!(ErrorCode::InvalidParameter.http_status(), 400);
+        assert_eq!(ErrorCode::InvalidParameter.message(), "参数错误");
+
+        assert_eq!(ErrorCode::TokenExpired.http_status(), 401);
+        assert_eq!(ErrorCode::OrderNotFound.http_status(), 404);
+        assert_eq!(ErrorCode::TooManyRequests.http_status(), 429);
+        assert_eq!(ErrorCode::ExternalServiceError.http_status(), 502);
+        assert_eq!(ErrorCode::ServiceUnavailable.to_string(), "服务暂不可用");
+    }
+
+    #[test]
+    fn test_codes_are_valid_http_statuses() {
+        for code in ErrorCode::all() {
+            assert!((400..600).contains(&code.http_status()), "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn test_serde_as_number() {
+        assert_eq!(serde_json::to_string(&ErrorCode::Forbidden).unwrap(), "40301");
+        assert_eq!(serde_json::from_str::<ErrorCode>("40901").unwrap(), ErrorCode::Conflict);
+        assert!(serde_json::from_str::<ErrorCode>("12345").is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! 出站 HTTP 客户端
+//!
+//! 在 `reqwest::Client` 之上统一出站调用的行为：超时、按 [`RetryPolicy`] 重试、
+//! 每次请求的 tracing span（记录状态码和耗时），以及调用链请求头的透传。
+//!
+//! ```ignore
+//! use common::http::Client;
+//! use middleware::trace_context;
+//!
+//! let client = Client::builder()
+//!     .timeout(Duration::from_secs(10))
+//!     .retry(RetryPolicy::new(3))
+//!     .propagate(trace_context::inject)
+//!     .build()?;
+//!
+//! let resp = client.post_json("https://api.example.com/orders", &body).await?;
+//! ```
+
+use crate::retry::{retry_async, RetryPolicy};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// 为出站请求写入调用链请求头，如 `middleware::trace_context::inject`
+pub type Propagator = Arc<dyn Fn(RequestBuilder) -> RequestBuilder + Send + Sync>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HttpError {
+    #[error("HTTP request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("HTTP {status}: {body}")]
+    Status { status: StatusCode, body: String },
+}
+
+impl HttpError {
+    /// 超时、连接失败、5xx 和 429 可以重试，其余错误重试也不会成功
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            HttpError::Request(e) => e.is_timeout() || e.is_connect(),
+            HttpError::Status { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            HttpError::Request(e) => e.status(),
+            HttpError::Status { status, .. } => Some(*status),
+        }
+    }
+}
+
+/// 带超时、重试和链路追踪的 HTTP 客户端，克隆开销很小，可以在多个调用方之间共享
+#[derive(Clone)]
+pub struct Client {
+    inner: reqwest::Client,
+    retry: RetryPolicy,
+    propagator: Option<Propagator>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("retry", &self.retry)
+            .field("propagator", &self.propagator.is_some())
+            .finish()
+    }
+}
+
+impl Client {
+    /// 使用默认配置：30 秒超时，默认重试策略
+    pub fn new() -> Result<Self, HttpError> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// 底层的 `reqwest::Client`，用于构造请求后交给 [`execute`](Self::execute) 发送
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.inner
+    }
+
+    pub async fn get(&self, url: &str) -> Result<Response, HttpError> {
+        self.execute(self.inner.get(url)).await
+    }
+
+    pub async fn post_json<T: Serialize + ?Sized>(&self, url: &str, body: &T) -> Result<Response, HttpError> {
+        self.execute(self.inner.post(url).json(body)).await
+    }
+
+    /// 发送请求，非 2xx 响应返回 [`HttpError::Status`]
+    ///
+    /// 可重试的错误按重试策略重新发送；流式请求体无法复制，只发送一次
+    pub async fn execute(&self, request: RequestBuilder) -> Result<Response, HttpError> {
+        let policy = match request.try_clone() {
+            Some(_) => self.retry.clone(),
+            None => RetryPolicy { max_attempts: 1, ..self.retry.clone() },
+        };
+
+        let mut attempt = 0;
+        retry_async(policy, HttpError::is_retryable, || {
+            attempt += 1;
+            // 可复制性已在上面检查，不可复制时只会执行一次
+            let request = request.try_clone().expect("request checked to be cloneable");
+            self.send_once(request, attempt)
+        })
+        .await
+    }
+
+    async fn send_once(&self, request: RequestBuilder, attempt: u32) -> Result<Response, HttpError> {
+        let request = match &self.propagator {
+            Some(propagate) => propagate(request),
+            None => request,
+        };
+        let request = request.build()?;
+
+        let span = tracing::info_span!(
+            "http.request",
+            method = %request.method(),
+            url = %request.url(),
+            attempt,
+        );
+
+        async move {
+            let started = Instant::now();
+            let result = self.inner.execute(request).await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+
+            let response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!(latency_ms, error = %e, "HTTP request failed");
+                    return Err(HttpError::Request(e));
+                }
+            };
+
+            let status = response.status();
+            if status.is_success() {
+                tracing::info!(status = status.as_u16(), latency_ms, "HTTP request completed");
+                return Ok(response);
+            }
+
+            tracing::warn!(status = status.as_u16(), latency_ms, "HTTP request returned error status");
+            let body = response.text().await.unwrap_or_default();
+            Err(HttpError::Status { status, body })
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+/// [`Client`] 构建器
+pub struct ClientBuilder {
+    timeout: Duration,
+    retry: RetryPolicy,
+    propagator: Option<Propagator>,
+    user_agent: Option<String>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            retry: RetryPolicy::default(),
+            propagator: None,
+            user_agent: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// 单次请求的超时时间，重试时每次单独计时
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// 每次发送前写入调用链请求头
+    pub fn propagate<F>(mut self, propagator: F) -> Self
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder + Send + Sync + 'static,
+    {
+        self.propagator = Some(Arc::new(propagator));
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Client, HttpError> {
+        let mut builder = reqwest::Client::builder().timeout(self.timeout);
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        Ok(Client {
+            inner: builder.build()?,
+            retry: self.retry,
+            propagator: self.propagator,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    fn client(max_attempts: u32) -> Client {
+        Client::builder()
+            .retry(RetryPolicy::new(max_attempts).initial_delay(Duration::from_millis(1)))
+            .propagate(|request| request.header("x-request-id", "req-1"))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_server_error_retried() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/orders").header("x-request-id", "req-1");
+                then.status(500).body("boom");
+            })
+            .await;
+
+        let err = client(3).get(&server.url("/orders")).await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(matches!(&err, HttpError::Status { body, .. } if body == "boom"));
+        mock.assert_hits_async(3).await;
+    }
+
+    #[tokio::test]
+    async fn test_client_error_not_retried() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(POST).path("/orders");
+                then.status(400);
+            })
+            .await;
+
+        let err = client(3)
+            .post_json(&server.url("/orders"), &serde_json::json!({ "amount": 100 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+        mock.assert_hits_async(1).await;
+    }
+}
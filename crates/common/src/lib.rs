@@ -1,6 +1,18 @@
 pub mod enums;
 pub mod utils;
+pub mod retry;
+pub mod http;
+pub mod errors;
+pub mod trace;
+pub mod sched;
+pub mod security;
+pub mod shutdown;
+pub mod validate;
 
 pub use enums::state_enum::State;
+pub use enums::DbName;
+pub use errors::ErrorCode;
+pub use validate::{FieldError, ValidationErrors, Validator};
 
+pub use utils::datetime;
 pub use utils::{datetime::*, datetime_format::*, type_convert::*};
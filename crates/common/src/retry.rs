@@ -0,0 +1,220 @@
+//! 异步重试工具
+//! 提供带指数退避和随机抖动的通用重试原语，供下载、支付渠道、数据库等调用点复用
+use rand::Rng;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// 重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 最大尝试次数（包含第一次调用）
+    pub max_attempts: u32,
+    /// 首次重试前的等待时间
+    pub initial_delay: Duration,
+    /// 单次等待时间上限
+    pub max_delay: Duration,
+    /// 每次重试等待时间的增长倍数
+    pub multiplier: f64,
+    /// 是否对等待时间添加随机抖动，避免多个调用方同时重试
+    pub jitter: bool,
+    /// 总耗时上限，超过后不再重试
+    pub max_elapsed: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            max_elapsed: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 创建指定最大尝试次数的策略，其余参数使用默认值
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// 设置首次重试前的等待时间
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// 设置单次等待时间上限
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// 设置等待时间增长倍数
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// 设置是否启用随机抖动
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 设置总耗时上限
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// 计算第 `attempt` 次失败后（从1开始）的等待时间
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exp = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let delay = self.initial_delay.mul_f64(exp).min(self.max_delay);
+
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+
+        // 等值抖动：保留一半等待时间，另一半随机
+        let half = delay / 2;
+        half + half.mul_f64(rand::rng().random_range(0.0..=1.0))
+    }
+}
+
+/// 按照重试策略执行异步操作
+///
+/// - `should_retry` 返回 false 的错误会立即返回，不再重试
+/// - 达到最大尝试次数或总耗时上限后，返回最后一次的错误
+///
+/// # 示例
+/// ```ignore
+/// let body = retry_async(RetryPolicy::new(5), |e: &reqwest::Error| e.is_timeout(), || {
+///     client.get(url).send()
+/// }).await?;
+/// ```
+pub async fn retry_async<F, Fut, T, E>(
+    policy: RetryPolicy,
+    should_retry: impl Fn(&E) -> bool,
+    mut op: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let started = Instant::now();
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let err = match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        if attempt >= policy.max_attempts || !should_retry(&err) {
+            return Err(err);
+        }
+
+        let delay = policy.delay_for(attempt);
+        if let Some(max_elapsed) = policy.max_elapsed
+            && started.elapsed() + delay > max_elapsed
+        {
+            return Err(err);
+        }
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).initial_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn succeeds_on_third_try() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<&str, String> = retry_async(fast_policy(5), |_| true, || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if n < 3 { Err(format!("failed {}", n)) } else { Ok("ok") }
+        })
+        .await;
+
+        assert_eq!(result, Ok("ok"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), String> = retry_async(fast_policy(4), |_| true, || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Err(format!("failed {}", n))
+        })
+        .await;
+
+        assert_eq!(result, Err("failed 4".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn non_retryable_error_returns_immediately() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), &str> = retry_async(fast_policy(5), |e: &&str| *e != "fatal", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("fatal")
+        })
+        .await;
+
+        assert_eq!(result, Err("fatal"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn stops_when_max_elapsed_exceeded() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(10)
+            .initial_delay(Duration::from_millis(50))
+            .jitter(false)
+            .max_elapsed(Duration::from_millis(20));
+
+        let result: Result<(), &str> = retry_async(policy, |_| true, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("timeout")
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn delay_grows_exponentially_and_is_capped() {
+        let policy = RetryPolicy::default()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(500))
+            .jitter(false);
+
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(500));
+
+        let jittered = policy.jitter(true).delay_for(2);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,62 @@
+//! 周期任务调度
+//!
+//! 多个实例使用固定间隔轮询（缓存刷新、证书续期、配置拉取等）时会在同一时刻集中触发。
+//! [`jittered_interval`] 每次在 `base ± jitter` 内随机取间隔，使各实例的执行时间错开。
+
+use futures_util::stream::{self, Stream};
+use rand::Rng;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 在 `base ± jitter` 内随机取一个间隔，`jitter` 超过 `base` 时按 `base` 计算
+pub fn jittered(base: Duration, jitter: Duration) -> Duration {
+    let jitter = jitter.min(base);
+    if jitter.is_zero() {
+        return base;
+    }
+    base - jitter + (jitter * 2).mul_f64(rand::rng().random_range(0.0..=1.0))
+}
+
+/// 按 `base ± jitter` 的随机间隔产生 tick 的流，返回每次 tick 的时间
+///
+/// 间隔从上一次 tick 开始计算；处理耗时超过间隔时下一次 tick 立即产生，不补偿错过的次数
+pub fn jittered_interval(base: Duration, jitter: Duration) -> impl Stream<Item = Instant> + Send {
+    stream::unfold(Instant::now(), move |last| async move {
+        tokio::time::sleep_until(last + jittered(base, jitter)).await;
+        let now = Instant::now();
+        Some((now, now))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn test_jittered_bounds() {
+        let base = Duration::from_secs(60);
+        for _ in 0..1000 {
+            let delay = jittered(base, Duration::from_secs(10));
+            assert!(delay >= Duration::from_secs(50) && delay <= Duration::from_secs(70), "{:?}", delay);
+        }
+
+        assert_eq!(jittered(base, Duration::ZERO), base);
+        // 抖动不超过间隔本身，不会出现负间隔
+        assert!(jittered(Duration::from_secs(1), Duration::from_secs(5)) <= Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ticks_within_jitter_bounds() {
+        let base = Duration::from_secs(30);
+        let jitter = Duration::from_secs(5);
+
+        let mut last = Instant::now();
+        let ticks: Vec<Instant> = jittered_interval(base, jitter).take(50).collect().await;
+        for tick in ticks {
+            let elapsed = tick - last;
+            assert!(elapsed >= base - jitter && elapsed <= base + jitter, "{:?}", elapsed);
+            last = tick;
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! 安全响应头
+//!
+//! 各 axum 服务通过 [`headers_layer`] 统一设置 HSTS、`X-Content-Type-Options` 等安全响应头。
+//! 配置放在 rconfig 的扩展段中，读取后传入：
+//!
+//! ```toml
+//! [security_headers]
+//! hsts_max_age = 63072000
+//! frame_options = "SAMEORIGIN"
+//!
+//! [security_headers.extra]
+//! "Permissions-Policy" = "camera=()"
+//! ```
+//!
+//! ```ignore
+//! let config: SecurityHeadersConfig = app_config.get_extension("security_headers").unwrap_or_default();
+//! let app = Router::new().layer(headers_layer(&config)?);
+//! ```
+//!
+//! 未配置的字段使用安全的默认值，字段设为 `null`（JSON）或留空字符串即可关闭对应的头。
+//! 处理器自己设置过的响应头不会被覆盖。
+
+use http::header::{
+    CONTENT_SECURITY_POLICY, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+};
+use http::{HeaderMap, HeaderName, HeaderValue, Request, Response};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use thiserror::Error;
+use tower::{Layer, Service};
+
+/// 安全响应头配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    /// HSTS 有效期（秒），为空时不发送 `Strict-Transport-Security`
+    pub hsts_max_age: Option<u64>,
+    /// HSTS 是否包含子域名
+    pub hsts_include_subdomains: bool,
+    /// 是否发送 `X-Content-Type-Options: nosniff`
+    pub content_type_options: bool,
+    /// `X-Frame-Options`
+    pub frame_options: Option<String>,
+    /// `Referrer-Policy`
+    pub referrer_policy: Option<String>,
+    /// `Content-Security-Policy`
+    pub content_security_policy: Option<String>,
+    /// 其他自定义响应头
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            hsts_max_age: Some(31_536_000),
+            hsts_include_subdomains: true,
+            content_type_options: true,
+            frame_options: Some("DENY".to_string()),
+            referrer_policy: Some("no-referrer".to_string()),
+            // 服务只返回 JSON，不需要加载任何资源
+            content_security_policy: Some("default-src 'none'; frame-ancestors 'none'".to_string()),
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+/// 响应头名称或取值无效
+#[derive(Debug, Error)]
+#[error("无效的安全响应头: {0}")]
+pub struct InvalidSecurityHeader(String);
+
+impl SecurityHeadersConfig {
+    /// 生成需要设置的响应头
+    pub fn to_headers(&self) -> Result<HeaderMap, InvalidSecurityHeader> {
+        let mut headers = HeaderMap::new();
+        let mut insert = |name: HeaderName, value: &str| -> Result<(), InvalidSecurityHeader> {
+            if value.is_empty() {
+                return Ok(());
+            }
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| InvalidSecurityHeader(format!("{}: {}", name, value)))?;
+            headers.insert(name, value);
+            Ok(())
+        };
+
+        if let Some(max_age) = self.hsts_max_age {
+            let value = if self.hsts_include_subdomains {
+                format!("max-age={}; includeSubDomains", max_age)
+            } else {
+                format!("max-age={}", max_age)
+            };
+            insert(STRICT_TRANSPORT_SECURITY, &value)?;
+        }
+        if self.content_type_options {
+            insert(X_CONTENT_TYPE_OPTIONS, "nosniff")?;
+        }
+        if let Some(value) = &self.frame_options {
+            insert(X_FRAME_OPTIONS, value)?;
+        }
+        if let Some(value) = &self.referrer_policy {
+            insert(REFERRER_POLICY, value)?;
+        }
+        if let Some(value) = &self.content_security_policy {
+            insert(CONTENT_SECURITY_POLICY, value)?;
+        }
+        for (name, value) in &self.extra {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| InvalidSecurityHeader(name.clone()))?;
+            insert(name, value)?;
+        }
+
+        Ok(headers)
+    }
+}
+
+/// 按配置创建设置安全响应头的 tower layer，配置中的头名称或取值无效时返回错误
+pub fn headers_layer(config: &SecurityHeadersConfig) -> Result<SecurityHeadersLayer, InvalidSecurityHeader> {
+    Ok(SecurityHeadersLayer {
+        headers: Arc::new(config.to_headers()?),
+    })
+}
+
+/// 设置安全响应头的 layer
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersLayer {
+    headers: Arc<HeaderMap>,
+}
+
+impl<S> Layer<S> for SecurityHeadersLayer {
+    type Service = SecurityHeaders<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SecurityHeaders {
+            inner,
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+/// 设置安全响应头的服务
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders<S> {
+    inner: S,
+    headers: Arc<HeaderMap>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for SecurityHeaders<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = SecurityHeadersFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        SecurityHeadersFuture {
+            inner: Box::pin(self.inner.call(req)),
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+pub struct SecurityHeadersFuture<F> {
+    inner: Pin<Box<F>>,
+    headers: Arc<HeaderMap>,
+}
+
+impl<F, ResBody, E> Future for SecurityHeadersFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<ResBody>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut response = ready!(self.inner.as_mut().poll(cx))?;
+        let headers = response.headers_mut();
+        for (name, value) in self.headers.iter() {
+            if !headers.contains_key(name) {
+                headers.insert(name, value.clone());
+            }
+        }
+        Poll::Ready(Ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::{service_fn, ServiceExt};
+
+    #[tokio::test]
+    async fn test_headers_present_on_response() {
+        let config: SecurityHeadersConfig = serde_json::from_value(serde_json::json!({
+            "frame_options": "SAMEORIGIN",
+            "content_security_policy": null,
+            "extra": { "Permissions-Policy": "camera=()" }
+        }))
+        .unwrap();
+
+        let service = headers_layer(&config).unwrap().layer(service_fn(|_req: Request<()>| async {
+            // 处理器自行设置的头保持不变
+            Ok::<_, Infallible>(Response::builder().header(REFERRER_POLICY, "origin").body(()).unwrap())
+        }));
+        let response = service.oneshot(Request::new(())).await.unwrap();
+
+        let headers = response.headers();
+        assert_eq!(headers[STRICT_TRANSPORT_SECURITY], "max-age=31536000; includeSubDomains");
+        assert_eq!(headers[X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[X_FRAME_OPTIONS], "SAMEORIGIN");
+        assert_eq!(headers[REFERRER_POLICY], "origin");
+        assert_eq!(headers["permissions-policy"], "camera=()");
+        assert!(!headers.contains_key(CONTENT_SECURITY_POLICY));
+    }
+
+    #[test]
+    fn test_invalid_header_rejected() {
+        let config = SecurityHeadersConfig {
+            extra: BTreeMap::from([("Bad Header".to_string(), "x".to_string())]),
+            ..Default::default()
+        };
+        assert!(headers_layer(&config).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! 优雅停机
+//!
+//! [`signal`] 在收到 SIGTERM / SIGINT（Windows 下为 Ctrl-C）时完成，可以直接交给 axum 的
+//! `with_graceful_shutdown`。需要同时停止后台任务时使用 [`ShutdownController`]，由它监听信号并通知所有订阅者：
+//!
+//! ```ignore
+//! let controller = ShutdownController::new();
+//! controller.trigger_on_signal();
+//!
+//! let shutdown = controller.subscribe();
+//! tokio::spawn(async move {
+//!     let mut ticks = std::pin::pin!(jittered_interval(base, jitter).take_until(shutdown.wait()));
+//!     while ticks.next().await.is_some() { ... }
+//! });
+//!
+//! axum::serve(listener, app).with_graceful_shutdown(controller.subscribe().wait()).await?;
+//! ```
+
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// 收到停机信号时完成
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("监听 Ctrl-C 失败: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("监听 SIGTERM 失败: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("收到 Ctrl-C，开始停机"),
+        _ = terminate => tracing::info!("收到 SIGTERM，开始停机"),
+    }
+}
+
+/// 向订阅的任务广播停机，克隆后共享同一个停机状态
+#[derive(Debug, Clone)]
+pub struct ShutdownController {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ShutdownController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownController {
+    pub fn new() -> Self {
+        Self { sender: Arc::new(watch::Sender::new(false)) }
+    }
+
+    /// 订阅停机通知，停机之后订阅的也会立即收到
+    pub fn subscribe(&self) -> Shutdown {
+        Shutdown { receiver: self.sender.subscribe() }
+    }
+
+    /// 通知所有订阅者停机，重复调用无效果
+    pub fn shutdown(&self) {
+        self.sender.send_if_modified(|stopped| !std::mem::replace(stopped, true));
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// 在后台监听 [`signal`]，收到信号时停机
+    pub fn trigger_on_signal(&self) {
+        let controller = self.clone();
+        tokio::spawn(async move {
+            signal().await;
+            controller.shutdown();
+        });
+    }
+}
+
+/// 停机通知的订阅
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    receiver: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    pub fn is_shutdown(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// 停机时完成；控制器全部释放后不可能再触发停机，同样视为停机
+    pub async fn wait(mut self) {
+        let _ = self.receiver.wait_for(|stopped| *stopped).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_shutdown_completes_subscribers() {
+        let controller = ShutdownController::new();
+        let tasks: Vec<_> = (0..3)
+            .map(|_| tokio::spawn(controller.subscribe().wait()))
+            .collect();
+
+        tokio::task::yield_now().await;
+        assert!(tasks.iter().all(|task| !task.is_finished()));
+        assert!(!controller.subscribe().is_shutdown());
+
+        controller.clone().shutdown();
+        for task in tasks {
+            tokio::time::timeout(Duration::from_secs(1), task).await.unwrap().unwrap();
+        }
+
+        // 停机之后订阅的立即完成
+        let late = controller.subscribe();
+        assert!(late.is_shutdown());
+        tokio::time::timeout(Duration::from_secs(1), late.wait()).await.unwrap();
+        assert!(controller.is_shutdown());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_controller_completes_subscribers() {
+        let controller = ShutdownController::new();
+        let shutdown = controller.subscribe();
+        drop(controller);
+        tokio::time::timeout(Duration::from_secs(1), shutdown.wait()).await.unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+//! 请求追踪ID
+//!
+//! 中间件在请求开始时通过 [`scope`] 设置追踪ID，中间件、处理器和错误响应都通过
+//! [`current_trace_id`] 读取同一个值，不需要在调用之间层层传递。
+
+use std::future::Future;
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// 生成新的追踪ID
+///
+/// 使用 UUIDv7 的 32 位十六进制形式，按生成时间排序，也可以直接作为 W3C `traceparent` 中的 trace-id
+pub fn new_trace_id() -> String {
+    uuid::Uuid::now_v7().simple().to_string()
+}
+
+/// 当前请求的追踪ID，不在 [`scope`] 内执行时返回 `None`
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.try_with(|trace_id| trace_id.clone()).ok()
+}
+
+/// 在指定追踪ID下执行异步任务，任务内的 [`current_trace_id`] 都返回该ID
+pub async fn scope<F: Future>(trace_id: String, fut: F) -> F::Output {
+    TRACE_ID.scope(trace_id, fut).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stable_within_scope() {
+        assert_eq!(current_trace_id(), None);
+
+        let trace_id = new_trace_id();
+        let seen = scope(trace_id.clone(), async {
+            let first = current_trace_id();
+            tokio::task::yield_now().await;
+            (first, current_trace_id())
+        })
+        .await;
+
+        assert_eq!(seen, (Some(trace_id.clone()), Some(trace_id)));
+        assert_eq!(current_trace_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_unique_across_scopes() {
+        let mut ids = Vec::new();
+        for _ in 0..100 {
+            let id = scope(new_trace_id(), async { current_trace_id().unwrap() }).await;
+            assert_eq!(id.len(), 32);
+            assert!(id.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase()));
+            ids.push(id);
+        }
+
+        // 按生成顺序排序且互不相同
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, ids);
+    }
+}
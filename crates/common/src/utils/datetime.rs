@@ -5,6 +5,8 @@ use chrono::{
     DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike,
     Utc, Weekday,
 };
+use serde::{Deserialize, Serialize};
+use std::ops::Sub;
 
 /// 常用日期时间格式常量
 pub mod formats {
@@ -343,6 +345,50 @@ impl TimeUtil {
     }
 }
 
+/// 时间区间，左闭右开 `[start, end)`
+///
+/// 用于活动有效期、排期等场景的区间判断，`T` 可以是 `DateTime<Tz>` 或 `NaiveDateTime`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Range<T = DateTime<Local>> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: PartialOrd> Range<T> {
+    /// 创建时间区间，`end` 早于 `start` 时返回 None
+    pub fn new(start: T, end: T) -> Option<Self> {
+        if end < start {
+            return None;
+        }
+        Some(Self { start, end })
+    }
+
+    /// 时间点是否在区间内（包含起点，不包含终点）
+    pub fn contains(&self, t: &T) -> bool {
+        &self.start <= t && t < &self.end
+    }
+
+    /// 两个区间是否重叠，首尾相接不算重叠
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// 是否完全包含另一个区间
+    pub fn contains_range(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+impl<T> Range<T>
+where
+    T: Clone + Sub<T, Output = Duration>,
+{
+    /// 区间时长
+    pub fn duration(&self) -> Duration {
+        self.end.clone() - self.start.clone()
+    }
+}
+
 /// 内部辅助函数：向日期添加月份
 fn add_months_to_date(date: NaiveDateTime, months: i32) -> NaiveDateTime {
     let mut year = date.year();
@@ -395,6 +441,66 @@ mod tests {
     use super::*;
     use formats::DATETIME;
 
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, DATETIME).unwrap()
+    }
+
+    fn range(start: &str, end: &str) -> Range<NaiveDateTime> {
+        Range::new(dt(start), dt(end)).unwrap()
+    }
+
+    #[test]
+    fn test_range_contains_and_duration() {
+        let r = range("2025-01-01 10:00:00", "2025-01-01 12:00:00");
+
+        assert!(r.contains(&dt("2025-01-01 10:00:00")));
+        assert!(r.contains(&dt("2025-01-01 11:59:59")));
+        assert!(!r.contains(&dt("2025-01-01 12:00:00")));
+        assert!(!r.contains(&dt("2025-01-01 09:59:59")));
+        assert_eq!(r.duration(), Duration::hours(2));
+
+        assert!(Range::new(dt("2025-01-02 00:00:00"), dt("2025-01-01 00:00:00")).is_none());
+    }
+
+    #[test]
+    fn test_range_overlaps() {
+        let r = range("2025-01-01 10:00:00", "2025-01-01 12:00:00");
+
+        // 首尾相接
+        let touching = range("2025-01-01 12:00:00", "2025-01-01 14:00:00");
+        assert!(!r.overlaps(&touching));
+        assert!(!touching.overlaps(&r));
+
+        // 完全包含
+        let inner = range("2025-01-01 10:30:00", "2025-01-01 11:00:00");
+        assert!(r.overlaps(&inner));
+        assert!(inner.overlaps(&r));
+        assert!(r.contains_range(&inner));
+        assert!(!inner.contains_range(&r));
+
+        // 部分重叠
+        let partial = range("2025-01-01 11:00:00", "2025-01-01 13:00:00");
+        assert!(r.overlaps(&partial));
+
+        // 不相交
+        let disjoint = range("2025-01-02 10:00:00", "2025-01-02 12:00:00");
+        assert!(!r.overlaps(&disjoint));
+        assert!(!disjoint.overlaps(&r));
+    }
+
+    #[test]
+    fn test_range_serde() {
+        let r = Range::new(
+            Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&r).unwrap();
+        assert_eq!(json, r#"{"start":"2025-01-01T10:00:00Z","end":"2025-01-01T12:00:00Z"}"#);
+        assert_eq!(serde_json::from_str::<Range<DateTime<Utc>>>(&json).unwrap(), r);
+    }
+
     #[test]
     fn time_util_example_usage() {
         // 获取当前时间
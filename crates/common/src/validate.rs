@@ -0,0 +1,222 @@
+//! 请求参数校验
+//!
+//! [`Validator`] 收集所有字段的校验错误，一次返回给调用方，而不是遇到第一个错误就返回。
+//!
+//! ```
+//! use common::Validator;
+//!
+//! let name = "";
+//! let age = 200;
+//!
+//! let result = Validator::new()
+//!     .length("name", name, 1, 32)
+//!     .range("age", age, 0, 150)
+//!     .finish();
+//!
+//! let errors = result.unwrap_err();
+//! assert_eq!(errors.errors().len(), 2);
+//! ```
+//!
+//! 启用 `axum` 特性后 [`ValidationErrors`] 可以直接作为处理函数的错误返回，响应为 422 和字段错误列表。
+
+use std::fmt::Display;
+use regex::Regex;
+use serde::Serialize;
+use crate::errors::ErrorCode;
+
+/// 单个字段的校验错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// 字段名，嵌套字段用 `.` 连接，如 `address.city`
+    pub field: String,
+    /// 错误类型，如 `required`、`range`，客户端据此处理
+    pub code: String,
+    /// 提示信息
+    pub message: String,
+}
+
+/// 校验失败时返回的全部字段错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("参数校验失败: {}", summary(.0))]
+pub struct ValidationErrors(Vec<FieldError>);
+
+fn summary(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{} {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl ValidationErrors {
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    /// 指定字段的错误
+    pub fn field(&self, field: &str) -> impl Iterator<Item = &FieldError> {
+        self.0.iter().filter(move |e| e.field == field)
+    }
+
+    /// 响应体，与服务统一响应格式一致，字段错误放在 `data.errors` 中
+    pub fn body(&self) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "code": ErrorCode::ValidationFailed.code(),
+            "message": ErrorCode::ValidationFailed.message(),
+            "data": { "errors": self.0 },
+        });
+        if let Some(trace_id) = crate::trace::current_trace_id() {
+            body["trace_id"] = trace_id.into();
+        }
+        body
+    }
+}
+
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for ValidationErrors {
+    fn into_response(self) -> axum::response::Response {
+        (axum::http::StatusCode::UNPROCESSABLE_ENTITY, axum::Json(self.body())).into_response()
+    }
+}
+
+/// 字段校验器，各检查方法可以链式调用，最后通过 [`Validator::finish`] 取得结果
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加自定义错误
+    pub fn add(&mut self, field: &str, code: &str, message: impl Into<String>) -> &mut Self {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: message.into(),
+        });
+        self
+    }
+
+    /// `condition` 不成立时添加错误
+    pub fn check(&mut self, condition: bool, field: &str, code: &str, message: impl Into<String>) -> &mut Self {
+        if !condition {
+            self.add(field, code, message);
+        }
+        self
+    }
+
+    /// 必填字段，`None` 时报错
+    pub fn required<T>(&mut self, field: &str, value: &Option<T>) -> &mut Self {
+        self.check(value.is_some(), field, "required", "不能为空")
+    }
+
+    /// 必填文本，`None` 或只包含空白字符时报错
+    pub fn required_text(&mut self, field: &str, value: Option<&str>) -> &mut Self {
+        let present = value.is_some_and(|v| !v.trim().is_empty());
+        self.check(present, field, "required", "不能为空")
+    }
+
+    /// 取值范围 `[min, max]`
+    pub fn range<T: PartialOrd + Display>(&mut self, field: &str, value: T, min: T, max: T) -> &mut Self {
+        let message = format!("必须在 {} 到 {} 之间", min, max);
+        self.check(value >= min && value <= max, field, "range", message)
+    }
+
+    /// 文本长度 `[min, max]`，按字符计数
+    pub fn length(&mut self, field: &str, value: &str, min: usize, max: usize) -> &mut Self {
+        let len = value.chars().count();
+        let message = format!("长度必须在 {} 到 {} 之间", min, max);
+        self.check(len >= min && len <= max, field, "length", message)
+    }
+
+    /// 文本需要匹配正则表达式，`hint` 说明期望的格式
+    pub fn pattern(&mut self, field: &str, value: &str, regex: &Regex, hint: &str) -> &mut Self {
+        self.check(regex.is_match(value), field, "pattern", format!("格式不正确，应为{}", hint))
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 没有错误时返回 `Ok`
+    pub fn finish(&mut self) -> Result<(), ValidationErrors> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors(std::mem::take(&mut self.errors)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct CreateCard {
+        title: Option<String>,
+        price: i64,
+        phone: String,
+    }
+
+    impl CreateCard {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let phone = Regex::new(r"^1\d{10}$").unwrap();
+            let mut validator = Validator::new();
+            validator
+                .required_text("title", self.title.as_deref())
+                .range("price", self.price, 1, 100_000)
+                .pattern("phone", &self.phone, &phone, "11 位手机号");
+            if let Some(title) = &self.title {
+                validator.length("title", title, 1, 20);
+            }
+            validator.finish()
+        }
+    }
+
+    #[test]
+    fn test_collects_all_field_errors() {
+        let card = CreateCard { title: Some("会员卡".to_string()), price: 0, phone: "1380013800".to_string() };
+        let errors = card.validate().unwrap_err();
+
+        assert_eq!(errors.errors().len(), 2);
+        assert_eq!(errors.field("price").next().unwrap().code, "range");
+        assert_eq!(errors.field("phone").next().unwrap().code, "pattern");
+        assert_eq!(
+            errors.body()["data"]["errors"],
+            json!([
+                { "field": "price", "code": "range", "message": "必须在 1 到 100000 之间" },
+                { "field": "phone", "code": "pattern", "message": "格式不正确，应为11 位手机号" },
+            ])
+        );
+        assert_eq!(errors.body()["code"], 42201);
+
+        let card = CreateCard { title: Some("会员卡".to_string()), price: 9900, phone: "13800138000".to_string() };
+        assert!(card.validate().is_ok());
+
+        let card = CreateCard { title: Some(" ".repeat(3)), price: 9900, phone: "13800138000".to_string() };
+        assert_eq!(card.validate().unwrap_err().errors()[0].code, "required");
+    }
+
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn test_into_response() {
+        use axum::response::IntoResponse;
+
+        let errors = Validator::new()
+            .required::<String>("title", &None)
+            .length("name", "这个名字有点太长了", 1, 4)
+            .finish()
+            .unwrap_err();
+        let response = errors.into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let fields: Vec<_> = body["data"]["errors"].as_array().unwrap().iter().map(|e| e["field"].clone()).collect();
+        assert_eq!(fields, vec![json!("title"), json!("name")]);
+    }
+}
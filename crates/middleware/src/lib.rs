@@ -1,6 +1,14 @@
 pub mod request_logger_v1;
 pub mod request_context;
 pub mod request_extractor;
+pub mod trace_context;
+pub mod maintenance;
+pub mod timeout;
+pub mod server_timing;
 
 pub use request_context::RequestContext;
-pub use request_extractor::RequestExtractor;
\ No newline at end of file
+pub use request_extractor::RequestExtractor;
+pub use trace_context::{TraceContext, TracePropagation};
+pub use maintenance::MaintenanceMode;
+pub use timeout::RequestTimeout;
+pub use server_timing::{ServerTiming, ServerTimings};
\ No newline at end of file
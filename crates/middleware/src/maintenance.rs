@@ -0,0 +1,273 @@
+//! 维护模式
+//!
+//! 开启后除白名单路径（默认 `/health`、`/metrics`）外的所有请求直接返回 503 和 `Retry-After`，
+//! 用于发布期间挡住业务流量。开关可以通过管理接口 [`set_maintenance`] 切换，
+//! 也可以在重新加载配置后调用 [`MaintenanceMode::apply_config`]，由 `extensions.maintenance.enabled` 控制。
+//!
+//! ```ignore
+//! let maintenance = MaintenanceMode::new();
+//! HttpServer::new(move || {
+//!     App::new()
+//!         .app_data(web::Data::new(maintenance.clone()))
+//!         .wrap(maintenance.clone())
+//!         .route("/admin/maintenance", web::put().to(set_maintenance))
+//! })
+//! ```
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header;
+use actix_web::{web, Error, HttpResponse};
+use futures::future::LocalBoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 配置中的扩展键，对应 `[extensions.maintenance]`
+pub const CONFIG_KEY: &str = "maintenance";
+
+/// 维护模式配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// 维护模式中间件
+///
+/// 克隆后共享同一个开关，可以同时注册为中间件和 `web::Data` 供管理接口使用
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode {
+    enabled: Arc<AtomicBool>,
+    allowlist: Arc<Vec<String>>,
+    retry_after_secs: u64,
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MaintenanceMode {
+    /// 默认关闭，白名单为 `/health` 和 `/metrics`，`Retry-After` 为 120 秒
+    pub fn new() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            allowlist: Arc::new(vec!["/health".to_string(), "/metrics".to_string()]),
+            retry_after_secs: 120,
+        }
+    }
+
+    /// 替换白名单，白名单路径及其子路径不受维护模式影响
+    pub fn with_allowlist<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowlist = Arc::new(paths.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_retry_after(mut self, secs: u64) -> Self {
+        self.retry_after_secs = secs;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        let previous = self.enabled.swap(enabled, Ordering::Relaxed);
+        if previous != enabled {
+            tracing::warn!("Maintenance mode {}", if enabled { "enabled" } else { "disabled" });
+        }
+    }
+
+    /// 按配置中的 `extensions.maintenance.enabled` 设置开关，配置重新加载后调用
+    ///
+    /// 未配置该扩展时关闭维护模式
+    pub fn apply_config(&self, config: &rconfig::AppConfig) -> Result<(), rconfig::ConfigError> {
+        let maintenance = match config.extensions.get(CONFIG_KEY) {
+            Some(_) => config.get_extension::<MaintenanceConfig>(CONFIG_KEY)?,
+            None => MaintenanceConfig::default(),
+        };
+        self.set_enabled(maintenance.enabled);
+        Ok(())
+    }
+
+    fn is_allowed(&self, path: &str) -> bool {
+        self.allowlist.iter().any(|allowed| {
+            path == allowed || path.strip_prefix(allowed.as_str()).is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    fn unavailable(&self) -> HttpResponse {
+        HttpResponse::ServiceUnavailable()
+            .insert_header((header::RETRY_AFTER, self.retry_after_secs.to_string()))
+            .json(json!({
+                "success": false,
+                "error": {
+                    "type": "Maintenance",
+                    "message": "服务维护中，请稍后重试"
+                }
+            }))
+    }
+}
+
+/// 管理接口：切换维护模式，请求体为 `{"enabled": true}`，返回切换后的状态
+///
+/// 需要通过 `web::Data<MaintenanceMode>` 注册，并自行加上鉴权
+pub async fn set_maintenance(
+    mode: web::Data<MaintenanceMode>,
+    body: web::Json<MaintenanceConfig>,
+) -> HttpResponse {
+    mode.set_enabled(body.enabled);
+    HttpResponse::Ok().json(MaintenanceConfig { enabled: mode.is_enabled() })
+}
+
+impl<S: 'static, B> Transform<S, ServiceRequest> for MaintenanceMode
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = MaintenanceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware {
+            service: Rc::new(service),
+            mode: self.clone(),
+        }))
+    }
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: Rc<S>,
+    mode: MaintenanceMode,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.mode.is_enabled() && !self.mode.is_allowed(req.path()) {
+            let res = req.into_response(self.mode.unavailable());
+            return Box::pin(async move { Ok(res.map_into_right_body()) });
+        }
+
+        let svc = self.service.clone();
+        Box::pin(async move {
+            let res = svc.call(req).await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::App;
+
+    #[actix_web::test]
+    async fn test_maintenance_mode() {
+        let mode = MaintenanceMode::new().with_retry_after(30);
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(mode.clone()))
+                .wrap(mode.clone())
+                .route("/health", web::get().to(HttpResponse::Ok))
+                .route("/api/orders", web::get().to(HttpResponse::Ok))
+                .route("/admin/maintenance", web::put().to(set_maintenance)),
+        )
+        .await;
+
+        let get = |uri: &str| TestRequest::get().uri(uri).to_request();
+
+        // 关闭时正常处理
+        assert_eq!(call_service(&app, get("/api/orders")).await.status(), StatusCode::OK);
+
+        mode.set_enabled(true);
+        let resp = call_service(&app, get("/api/orders")).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "30");
+        let body: serde_json::Value = read_body_json(resp).await;
+        assert_eq!(body["error"]["type"], "Maintenance");
+
+        // 白名单路径不受影响
+        assert_eq!(call_service(&app, get("/health")).await.status(), StatusCode::OK);
+
+        // 管理接口不在白名单内时同样被拦截
+        let req = TestRequest::put()
+            .uri("/admin/maintenance")
+            .set_json(json!({ "enabled": false }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // 把管理接口加入白名单后可以关闭维护模式
+        let mode = mode.with_allowlist(["/health", "/admin"]);
+        mode.set_enabled(true);
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(mode.clone()))
+                .wrap(mode.clone())
+                .route("/api/orders", web::get().to(HttpResponse::Ok))
+                .route("/admin/maintenance", web::put().to(set_maintenance)),
+        )
+        .await;
+        let req = TestRequest::put()
+            .uri("/admin/maintenance")
+            .set_json(json!({ "enabled": false }))
+            .to_request();
+        let body: serde_json::Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(body["enabled"], false);
+        assert_eq!(call_service(&app, get("/api/orders")).await.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_allowlist_prefix() {
+        let mode = MaintenanceMode::new();
+        assert!(mode.is_allowed("/health"));
+        assert!(mode.is_allowed("/metrics/prometheus"));
+        assert!(!mode.is_allowed("/healthz"));
+    }
+
+    #[test]
+    fn test_apply_config() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("maintenance-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("app.toml");
+
+        let mode = MaintenanceMode::new();
+        std::fs::write(&path, "[extensions.maintenance]\nenabled = true\n")?;
+        mode.apply_config(&rconfig::AppConfig::new().add_file(&path).build()?)?;
+        assert!(mode.is_enabled());
+
+        // 重新加载后关闭
+        std::fs::write(&path, "[extensions.maintenance]\nenabled = false\n")?;
+        mode.apply_config(&rconfig::AppConfig::new().add_file(&path).build()?)?;
+        assert!(!mode.is_enabled());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use actix_web::web;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use crate::server_timing::ServerTimings;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RequestData {
@@ -22,17 +24,41 @@ pub struct RequestData {
 pub struct RequestContext {
     pub trace_id: String,
     pub user_id: Option<String>,
+    pub tenant_id: Option<String>,
     pub token: Option<String>,
     pub client_ip: Option<String>,
     pub user_agent: Option<String>,
     pub request_data: Option<String>,  // 新增字段
+    /// 分段耗时，注册了 `ServerTiming` 中间件时通过 `Server-Timing` 响应头返回
+    #[serde(skip)]
+    pub timings: ServerTimings,
 }
 
 impl RequestContext {
+    /// 沿用外层中间件设置的追踪ID，没有时生成新的
     pub fn new() -> Self {
         Self {
-            trace_id: uuid::Uuid::new_v4().to_string(),
+            trace_id: common::trace::current_trace_id().unwrap_or_else(common::trace::new_trace_id),
             ..Default::default()
         }
     }
+
+    /// 请求日志的关联字段
+    pub fn log_fields(&self) -> rlog::ScopeFields {
+        rlog::ScopeFields {
+            request_id: Some(self.trace_id.clone()),
+            uid: self.user_id.clone(),
+            tenant: self.tenant_id.clone(),
+        }
+    }
+
+    /// 记录从上一次打点（或请求开始）到现在的耗时，如 `ctx.mark("db")`
+    pub fn mark(&self, name: impl Into<String>) {
+        self.timings.mark(name);
+    }
+
+    /// 记录自行测量的耗时
+    pub fn measure(&self, name: impl Into<String>, duration: Duration) {
+        self.timings.measure(name, duration);
+    }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use super::request_context::{RequestContext, RequestData};
+use super::server_timing::ServerTimings;
 use actix_http::h1;
 use actix_web::{dev, dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform}, error, Error, HttpMessage};
 use actix_web::{web, FromRequest};
@@ -7,6 +8,7 @@ use futures::StreamExt;
 use std::future::{ready, Future, Ready};
 use std::pin::Pin;
 use std::sync::Arc;
+use tracing::Instrument;
 
 pub struct RequestExtractor;
 
@@ -52,6 +54,10 @@ where
 
     fn call(&self, mut srv_req: ServiceRequest) -> Self::Future {
         let mut context = RequestContext::new();
+        // 与外层 ServerTiming 中间件共享计时
+        if let Some(timings) = srv_req.extensions().get::<ServerTimings>() {
+            context.timings = timings.clone();
+        }
 
         // 提取 header 参数
         if let Some(token) = srv_req.headers().get("Authorization") {
@@ -62,6 +68,10 @@ where
             context.user_id = user_id.to_str().ok().map(|s| s.to_string());
         }
 
+        if let Some(tenant_id) = srv_req.headers().get("X-Tenant-Id") {
+            context.tenant_id = tenant_id.to_str().ok().map(|s| s.to_string());
+        }
+
         // 提取客户端 IP
         context.client_ip = srv_req.connection_info().realip_remote_addr()
             .map(|s| s.to_string());
@@ -144,10 +154,14 @@ where
             }
 
             // Insert context into request extensions
+            let trace_id = context.trace_id.clone();
+            // 请求内的日志自动带上请求ID、用户、租户
+            let span = rlog::request_span(&context.log_fields());
             srv_req.extensions_mut().insert(context);
 
             // Call the next service in the chain
-            let res = svc.call(srv_req).await?;
+            // 后续处理通过 common::trace::current_trace_id 读取追踪ID
+            let res = common::trace::scope(trace_id, svc.call(srv_req)).instrument(span).await?;
             Ok(res)
         })
     }
@@ -0,0 +1,212 @@
+//! Server-Timing 响应头
+//!
+//! 处理器在关键步骤后打点，响应时通过 `Server-Timing` 头返回各阶段耗时和总耗时，
+//! 前端可以直接在浏览器开发者工具中查看延迟构成：
+//!
+//! ```text
+//! Server-Timing: db;dur=12.4, channel;dur=85.0, total;dur=101.3
+//! ```
+//!
+//! 需要显式注册 [`ServerTiming`] 中间件才会输出响应头，注册在 [`RequestExtractor`](crate::RequestExtractor)
+//! 之外时 [`RequestContext`](crate::RequestContext) 共享同一份计时：
+//!
+//! ```ignore
+//! App::new()
+//!     .wrap(RequestExtractor)
+//!     .wrap(ServerTiming)
+//!
+//! async fn create_order(req: HttpRequest) -> HttpResponse {
+//!     let ctx = req.extensions().get::<RequestContext>().unwrap();
+//!     load_merchant().await;
+//!     ctx.mark("db");
+//!     ctx.measure("channel", channel_elapsed);
+//! }
+//! ```
+//!
+//! 也可以直接以 [`ServerTimings`] 作为处理器参数。阶段名应为 HTTP token（字母、数字、`-`、`_` 等），
+//! 响应头会暴露内部耗时，对外网关可按需剥离。
+
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use futures::future::LocalBoxFuture;
+use std::fmt::Write;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// `Server-Timing` 响应头
+pub const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// 一次请求的分段耗时
+///
+/// 克隆后共享同一份记录，请求开始时创建
+#[derive(Debug, Clone)]
+pub struct ServerTimings {
+    inner: Arc<Mutex<Timings>>,
+}
+
+#[derive(Debug)]
+struct Timings {
+    started: Instant,
+    /// 上一次打点的时间
+    checkpoint: Instant,
+    entries: Vec<(String, Duration)>,
+}
+
+impl Default for ServerTimings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerTimings {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            inner: Arc::new(Mutex::new(Timings {
+                started: now,
+                checkpoint: now,
+                entries: Vec::new(),
+            })),
+        }
+    }
+
+    /// 记录从上一次打点（或请求开始）到现在的耗时
+    pub fn mark(&self, name: impl Into<String>) {
+        let mut timings = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now - timings.checkpoint;
+        timings.checkpoint = now;
+        timings.entries.push((name.into(), elapsed));
+    }
+
+    /// 记录自行测量的耗时，不影响打点位置
+    pub fn measure(&self, name: impl Into<String>, duration: Duration) {
+        let mut timings = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        timings.entries.push((name.into(), duration));
+    }
+
+    /// 已记录的分段
+    pub fn entries(&self) -> Vec<(String, Duration)> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).entries.clone()
+    }
+
+    /// 生成响应头的值，最后附加 `total`
+    pub fn header_value(&self) -> String {
+        let timings = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut value = String::new();
+        let total = ("total", timings.started.elapsed());
+        for (name, duration) in timings.entries.iter().map(|(name, d)| (name.as_str(), *d)).chain([total]) {
+            if !value.is_empty() {
+                value.push_str(", ");
+            }
+            let _ = write!(value, "{};dur={:.1}", name, duration.as_secs_f64() * 1000.0);
+        }
+        value
+    }
+}
+
+impl FromRequest for ServerTimings {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    /// 未注册 [`ServerTiming`] 时返回独立的计时，记录不会输出
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(req.extensions().get::<ServerTimings>().cloned().unwrap_or_default()))
+    }
+}
+
+/// 输出 `Server-Timing` 响应头的中间件
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerTiming;
+
+impl<S: 'static, B> Transform<S, ServiceRequest> for ServerTiming
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ServerTimingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ServerTimingMiddleware { service: Rc::new(service) }))
+    }
+}
+
+pub struct ServerTimingMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ServerTimingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let timings = ServerTimings::new();
+        req.extensions_mut().insert(timings.clone());
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let mut res = service.call(req).await?;
+            if let Ok(value) = HeaderValue::from_str(&timings.header_value()) {
+                res.headers_mut().insert(SERVER_TIMING, value);
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    async fn handler(timings: ServerTimings) -> HttpResponse {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        timings.mark("db");
+        timings.measure("channel", Duration::from_millis(35));
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn test_server_timing_header() {
+        let app = init_service(App::new().wrap(ServerTiming).route("/orders", web::get().to(handler))).await;
+        let resp = call_service(&app, TestRequest::get().uri("/orders").to_request()).await;
+
+        let header = resp.headers().get(SERVER_TIMING).unwrap().to_str().unwrap();
+        let segments: Vec<(&str, f64)> = header
+            .split(", ")
+            .map(|segment| {
+                let (name, dur) = segment.split_once(";dur=").unwrap();
+                (name, dur.parse().unwrap())
+            })
+            .collect();
+        let names: Vec<&str> = segments.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["db", "channel", "total"]);
+        assert!(segments[0].1 >= 20.0);
+        assert_eq!(segments[1].1, 35.0);
+        assert!(segments[2].1 >= segments[0].1);
+    }
+
+    #[actix_web::test]
+    async fn test_opt_in() {
+        let app = init_service(App::new().route("/orders", web::get().to(handler))).await;
+        let resp = call_service(&app, TestRequest::get().uri("/orders").to_request()).await;
+        assert!(resp.headers().get(SERVER_TIMING).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! 请求超时
+//!
+//! 处理超过时限的请求直接返回 408，同时丢弃处理器的 future：处理器中尚未完成的数据库、HTTP 调用
+//! 随之取消，客户端放弃后不会继续占用连接。单独路由可以通过 [`RequestTimeout::with_route`] 覆盖默认时限：
+//!
+//! ```ignore
+//! App::new()
+//!     .wrap(
+//!         RequestTimeout::new(Duration::from_secs(10))
+//!             .with_route("/api/reports", Duration::from_secs(60)),
+//!     )
+//! ```
+//!
+//! 注意处理器中通过 `tokio::spawn` 启动的任务不受影响，需要随请求取消的工作不要放到独立任务中。
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::InternalError;
+use actix_web::{Error, HttpResponse};
+use futures::future::LocalBoxFuture;
+use serde_json::json;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 请求超时中间件
+#[derive(Debug, Clone)]
+pub struct RequestTimeout {
+    default: Duration,
+    routes: Arc<Vec<(String, Duration)>>,
+}
+
+impl RequestTimeout {
+    pub fn new(default: Duration) -> Self {
+        Self {
+            default,
+            routes: Arc::new(Vec::new()),
+        }
+    }
+
+    /// 覆盖指定路径及其子路径的超时时间，多个路径匹配时使用最长的路径
+    pub fn with_route(mut self, path: impl Into<String>, timeout: Duration) -> Self {
+        Arc::make_mut(&mut self.routes).push((path.into(), timeout));
+        self
+    }
+
+    /// 请求路径对应的超时时间
+    pub fn timeout_for(&self, path: &str) -> Duration {
+        self.routes
+            .iter()
+            .filter(|(prefix, _)| {
+                path == prefix || path.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, timeout)| *timeout)
+    }
+}
+
+impl<S: 'static, B> Transform<S, ServiceRequest> for RequestTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTimeoutMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTimeoutMiddleware {
+            service: Rc::new(service),
+            timeout: self.clone(),
+        }))
+    }
+}
+
+pub struct RequestTimeoutMiddleware<S> {
+    service: Rc<S>,
+    timeout: RequestTimeout,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let timeout = self.timeout.timeout_for(req.path());
+        let target = format!("{} {}", req.method(), req.path());
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, fut).await {
+                Ok(res) => res,
+                // 超时后 fut 已被丢弃，处理器随之取消
+                Err(_) => {
+                    tracing::warn!("Request timed out after {:?}: {}", timeout, target);
+                    let response = HttpResponse::RequestTimeout().json(json!({
+                        "success": false,
+                        "error": {
+                            "type": "RequestTimeout",
+                            "message": "请求处理超时"
+                        }
+                    }));
+                    Err(InternalError::from_response("request timeout", response).into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::body::to_bytes;
+    use actix_web::test::{call_service, init_service, try_call_service, TestRequest};
+    use actix_web::{web, App};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[actix_web::test]
+    async fn test_timeout_cancels_handler() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let handler = {
+            let finished = finished.clone();
+            move || {
+                let finished = finished.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    finished.store(true, Ordering::SeqCst);
+                    HttpResponse::Ok().finish()
+                }
+            }
+        };
+
+        let app = init_service(
+            App::new()
+                .wrap(RequestTimeout::new(Duration::from_millis(50)).with_route("/slow", Duration::from_secs(5)))
+                .route("/api/orders", web::get().to(handler.clone()))
+                .route("/slow/report", web::get().to(handler)),
+        )
+        .await;
+
+        // 超时以错误返回，由服务端转换为 408 响应
+        let err = try_call_service(&app, TestRequest::get().uri("/api/orders").to_request()).await.unwrap_err();
+        let resp = err.error_response();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body["error"]["type"], "RequestTimeout");
+
+        // 处理器已被取消，等到原本的完成时间之后也不会执行后续逻辑
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+
+        // 覆盖了超时时间的路由正常完成
+        let resp = call_service(&app, TestRequest::get().uri("/slow/report").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_timeout_for() {
+        let timeout = RequestTimeout::new(Duration::from_secs(10))
+            .with_route("/api", Duration::from_secs(20))
+            .with_route("/api/reports", Duration::from_secs(60));
+
+        assert_eq!(timeout.timeout_for("/health"), Duration::from_secs(10));
+        assert_eq!(timeout.timeout_for("/api/orders"), Duration::from_secs(20));
+        assert_eq!(timeout.timeout_for("/api/reports/daily"), Duration::from_secs(60));
+        assert_eq!(timeout.timeout_for("/apiv2"), Duration::from_secs(10));
+    }
+}
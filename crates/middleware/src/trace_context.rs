@@ -0,0 +1,306 @@
+//! W3C Trace Context 传播
+//!
+//! - 入站：解析 `traceparent`/`tracestate` 请求头，为本服务生成子 span，写入请求扩展和 tracing span
+//! - 出站：通过 [`inject`] 把当前上下文写入 `reqwest` 请求头，使调用链在服务之间保持连续
+//!
+//! 规范参考 <https://www.w3.org/TR/trace-context/>
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use tracing::Instrument;
+
+pub const TRACEPARENT: &str = "traceparent";
+pub const TRACESTATE: &str = "tracestate";
+
+/// 仅支持的版本号
+const VERSION: &str = "00";
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// 调用链上下文
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 整条调用链的ID，32位十六进制
+    pub trace_id: String,
+    /// 当前 span 的ID，16位十六进制
+    pub span_id: String,
+    /// 上游 span 的ID，根 span 为空
+    pub parent_span_id: Option<String>,
+    /// 采样等标志位
+    pub flags: u8,
+    /// 厂商自定义状态，原样透传
+    pub trace_state: Option<String>,
+}
+
+impl TraceContext {
+    /// 创建新的调用链（根 span）
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: common::trace::new_trace_id(),
+            span_id: new_span_id(),
+            parent_span_id: None,
+            flags: 0x01,
+            trace_state: None,
+        }
+    }
+
+    /// 解析 `traceparent` 请求头，格式为 `00-{trace_id}-{span_id}-{flags}`
+    pub fn parse(traceparent: &str, trace_state: Option<&str>) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let (version, trace_id, span_id, flags) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() || version != VERSION {
+            return None;
+        }
+
+        if !is_valid_id(trace_id, 32) || !is_valid_id(span_id, 16) {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok().filter(|_| flags.len() == 2)?;
+
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            span_id: span_id.to_string(),
+            parent_span_id: None,
+            flags,
+            trace_state: trace_state.map(|s| s.to_string()).filter(|s| !s.is_empty()),
+        })
+    }
+
+    /// 在同一调用链下创建子 span
+    pub fn child(&self) -> Self {
+        Self {
+            trace_id: self.trace_id.clone(),
+            span_id: new_span_id(),
+            parent_span_id: Some(self.span_id.clone()),
+            flags: self.flags,
+            trace_state: self.trace_state.clone(),
+        }
+    }
+
+    /// 格式化为 `traceparent` 请求头
+    pub fn traceparent(&self) -> String {
+        format!("{}-{}-{}-{:02x}", VERSION, self.trace_id, self.span_id, self.flags)
+    }
+
+    /// 当前任务中的调用链上下文，仅在 [`TracePropagation`] 处理的请求内可用
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|ctx| ctx.clone()).ok()
+    }
+
+    /// 在指定上下文中执行异步任务，任务内可以通过 [`TraceContext::current`] 获取
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CURRENT.scope(self, fut).await
+    }
+}
+
+fn new_span_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+/// ID 必须为小写十六进制且不能全为0
+fn is_valid_id(id: &str, len: usize) -> bool {
+    id.len() == len
+        && id.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        && id.bytes().any(|b| b != b'0')
+}
+
+/// 为出站请求注入调用链上下文
+///
+/// 每次出站调用都会生成新的子 span，下游服务会把它作为父 span
+///
+/// ```ignore
+/// let resp = trace_context::inject(client.post(url)).json(&body).send().await?;
+/// ```
+pub fn inject(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match TraceContext::current() {
+        Some(ctx) => {
+            let outbound = ctx.child();
+            let builder = builder.header(TRACEPARENT, outbound.traceparent());
+            match &outbound.trace_state {
+                Some(state) => builder.header(TRACESTATE, state),
+                None => builder,
+            }
+        }
+        None => builder,
+    }
+}
+
+/// 处理函数中直接提取调用链上下文
+impl FromRequest for TraceContext {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
+        let ctx = req.extensions().get::<TraceContext>().cloned().unwrap_or_else(TraceContext::new_root);
+        ready(Ok(ctx))
+    }
+}
+
+/// 调用链传播中间件
+pub struct TracePropagation;
+
+impl Default for TracePropagation {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl<S: 'static, B> Transform<S, ServiceRequest> for TracePropagation
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TracePropagationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TracePropagationMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct TracePropagationMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TracePropagationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let trace_state = req.headers().get(TRACESTATE).and_then(|v| v.to_str().ok());
+
+        // 上游传入有效的 traceparent 时继续该调用链，否则开启新的调用链
+        let ctx = req.headers()
+            .get(TRACEPARENT)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| TraceContext::parse(v, trace_state))
+            .map(|incoming| incoming.child())
+            .unwrap_or_else(TraceContext::new_root);
+
+        let span = tracing::info_span!(
+            "http_request",
+            method = %req.method(),
+            path = %req.path(),
+            trace_id = %ctx.trace_id,
+            span_id = %ctx.span_id,
+            parent_span_id = ctx.parent_span_id.as_deref().unwrap_or(""),
+        );
+
+        req.extensions_mut().insert(ctx.clone());
+
+        let svc = self.service.clone();
+        let traceparent = ctx.traceparent();
+
+        let trace_id = ctx.trace_id.clone();
+
+        Box::pin(
+            ctx.scope(common::trace::scope(trace_id, async move {
+                let mut res = svc.call(req).await?;
+
+                // 响应中回写本服务的 span，便于调用方关联
+                if let Ok(value) = HeaderValue::from_str(&traceparent) {
+                    res.headers_mut().insert(HeaderName::from_static(TRACEPARENT), value);
+                }
+                Ok(res)
+            }))
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_and_read_body_json, call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    const INCOMING: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse_traceparent() {
+        let ctx = TraceContext::parse(INCOMING, Some("vendor=value")).unwrap();
+        assert_eq!(ctx.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(ctx.span_id, "00f067aa0ba902b7");
+        assert_eq!(ctx.flags, 0x01);
+        assert_eq!(ctx.traceparent(), INCOMING);
+
+        // 非法格式
+        assert!(TraceContext::parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01", None).is_none());
+        assert!(TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01", None).is_none());
+        assert!(TraceContext::parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01", None).is_none());
+        assert!(TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7", None).is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_propagation() {
+        let app = init_service(
+            App::new().wrap(TracePropagation).route(
+                "/",
+                web::get().to(|ctx: TraceContext| async move {
+                    // 模拟调用下游服务
+                    let outbound = inject(reqwest::Client::new().get("http://payment-service/api"))
+                        .build()
+                        .unwrap();
+                    let header = |name: &str| {
+                        outbound.headers().get(name).map(|v| v.to_str().unwrap().to_string())
+                    };
+
+                    HttpResponse::Ok().json(serde_json::json!({
+                        "trace_id": ctx.trace_id,
+                        "current_trace_id": common::trace::current_trace_id(),
+                        "span_id": ctx.span_id,
+                        "parent_span_id": ctx.parent_span_id,
+                        "outbound_traceparent": header(TRACEPARENT),
+                        "outbound_tracestate": header(TRACESTATE),
+                    }))
+                }),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header((TRACEPARENT, INCOMING))
+            .insert_header((TRACESTATE, "vendor=value"))
+            .to_request();
+        let body: serde_json::Value = call_and_read_body_json(&app, req).await;
+
+        // 入站的 span 成为本服务 span 的父级
+        assert_eq!(body["trace_id"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(body["current_trace_id"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(body["parent_span_id"], "00f067aa0ba902b7");
+        assert_ne!(body["span_id"], "00f067aa0ba902b7");
+
+        // 出站请求沿用同一调用链，并以本服务 span 为父级
+        let outbound = TraceContext::parse(body["outbound_traceparent"].as_str().unwrap(), None).unwrap();
+        assert_eq!(outbound.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_ne!(outbound.span_id, body["span_id"].as_str().unwrap());
+        assert_eq!(body["outbound_tracestate"], "vendor=value");
+
+        // 没有 traceparent 时开启新的调用链
+        let req = TestRequest::get().uri("/").to_request();
+        let resp = call_service(&app, req).await;
+        let traceparent = resp.headers().get(TRACEPARENT).unwrap().to_str().unwrap();
+        assert!(TraceContext::parse(traceparent, None).is_some());
+    }
+}
@@ -13,7 +13,7 @@ static RABBITMQ_POOL: Lazy<Arc<Pool>> = Lazy::new(|| {
     // let rabbit = get_config().unwrap().rabbit;
 
     let cfg = Config {
-        url: Some(rabbit.unwrap().clone().connection_url()),
+        url: Some(rabbit.unwrap().clone().connection_uri()),
         pool: Some(PoolConfig {
             max_size: 10,
             ..PoolConfig::default()
@@ -1,15 +1,22 @@
 //! 主配置结构和构建器
 
 use crate::error::{ConfigError, Result};
+use crate::glob::expand;
+use crate::include::resolve_includes;
+use crate::profile::apply_profile;
+use crate::signal::Subscriptions;
+use crate::strict::unknown_keys;
+use crate::template::{collect_missing_vars, expand_value};
 use crate::presets::*;
 use config::{Config, Environment, File};
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 use crate::{LogConfig, RabbitMqConfig, RedisConfig};
 
 /// 应用配置，包含所有预设服务配置
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct AppConfig {
     
     /// 环境变量
@@ -39,6 +46,14 @@ pub struct AppConfig {
     /// 自定义扩展配置
     #[serde(default)]
     pub extensions: HashMap<String, serde_json::Value>,
+
+    /// 配置文件中存在但未被任何配置段使用的键，通常是拼写错误
+    #[serde(skip)]
+    pub unknown_keys: Vec<String>,
+
+    /// [`subscribe`](AppConfig::subscribe) 创建的订阅，重新加载时更新，克隆时不复制
+    #[serde(skip)]
+    pub(crate) subscriptions: Subscriptions,
 }
 
 impl AppConfig {
@@ -98,6 +113,30 @@ impl AppConfig {
             .map_err(ConfigError::from)
     }
 
+    /// 按点分路径读取配置值，如 `redis.host`、`databases.sources.order.url`，
+    /// 不属于预设配置段的路径在扩展配置中查找，如 `payment.notify_url`
+    pub fn get_value(&self, key: &str) -> Option<serde_json::Value> {
+        let mut parts = key.split('.');
+        let section = parts.next()?;
+        let root = match section {
+            "env" => serde_json::to_value(&self.env).ok()?,
+            "server" => serde_json::to_value(&self.server).ok()?,
+            "database" => serde_json::to_value(&self.database).ok()?,
+            "databases" => serde_json::to_value(&self.databases).ok()?,
+            "redis" => serde_json::to_value(&self.redis).ok()?,
+            "rabbitmq" => serde_json::to_value(&self.rabbitmq).ok()?,
+            "log" => serde_json::to_value(&self.log).ok()?,
+            "extensions" => serde_json::to_value(&self.extensions).ok()?,
+            extension => self.extensions.get(extension)?.clone(),
+        };
+
+        let value = parts.try_fold(root, |value, part| match value {
+            serde_json::Value::Object(mut map) => map.remove(part),
+            _ => None,
+        })?;
+        (!value.is_null()).then_some(value)
+    }
+
     /// 验证配置是否有效
     pub fn validate(&self) -> Result<()> {
         self.server.validate()?;
@@ -119,6 +158,14 @@ impl AppConfig {
 /// 配置构建器
 pub struct AppConfigBuilder {
     config_builder: config::ConfigBuilder<config::builder::DefaultState>,
+    /// 添加配置文件时产生的错误（如 include 循环），在 build 时返回
+    error: Option<ConfigError>,
+    /// 激活的 profile，未指定时使用配置中的 `env`
+    profile: Option<String>,
+    /// 调用方额外读取的配置键，按前缀匹配
+    known_keys: Vec<String>,
+    /// 严格模式下存在未知键时构建失败
+    strict: bool,
 }
 
 impl AppConfigBuilder {
@@ -126,34 +173,54 @@ impl AppConfigBuilder {
     pub fn new() -> Self {
         Self {
             config_builder: Config::builder(),
+            error: None,
+            profile: None,
+            known_keys: Vec::new(),
+            strict: false,
         }
     }
 
+    /// 添加已存在的配置文件，先加载其 include 的文件，再叠加文件本身
+    fn add_file_with_includes(mut self, path: &Path) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+
+        let files = match resolve_includes(path) {
+            Ok(files) => files,
+            Err(e) => {
+                self.error = Some(e);
+                return self;
+            }
+        };
+
+        for file in files {
+            self.config_builder = self.config_builder.add_source(File::from(file));
+        }
+        self
+    }
+
     /// 添加默认配置文件，支持 .json, .toml, .yaml, .hjson, .ini
-    pub fn add_default<P: AsRef<Path>>(mut self, path: P) -> Self {
+    pub fn add_default<P: AsRef<Path>>(self, path: P) -> Self {
         let path = path.as_ref();
         // 尝试不同扩展名，使用找到的第一个
         for ext in &["json", "toml", "yaml", "hjson", "ini"] {
             let file_path = format!("{}.{}", path.display(), ext);
             println!("<{}> default config file path: {}", ext, file_path);
             if Path::new(&file_path).exists() {
-                self.config_builder = self.config_builder
-                    .add_source(File::with_name(&file_path).required(false));
-                break;
+                return self.add_file_with_includes(Path::new(&file_path));
             }
         }
         self
     }
 
     /// 添加指定环境的配置文件
-    pub fn add_environment_file<P: AsRef<Path>>(mut self, env: &str, path: P) -> Self {
+    pub fn add_environment_file<P: AsRef<Path>>(self, env: &str, path: P) -> Self {
         let path = path.as_ref();
         for ext in &["json", "toml", "yaml", "hjson", "ini"] {
             let file_path = format!("{}_{}.{}", path.display(), env, ext);
             if Path::new(&file_path).exists() {
-                self.config_builder = self.config_builder
-                    .add_source(File::with_name(&file_path).required(false));
-                break;
+                return self.add_file_with_includes(Path::new(&file_path));
             }
         }
         self
@@ -167,13 +234,61 @@ impl AppConfigBuilder {
         self
     }
 
-    /// 从特定文件加载配置
+    /// 从特定文件加载配置，支持 `include` 指令
     pub fn add_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        if path.as_ref().is_file() {
+            return self.add_file_with_includes(path.as_ref());
+        }
+
+        // 未带扩展名时交由 config 自动查找
         self.config_builder = self.config_builder
             .add_source(File::with_name(path.as_ref().to_str().unwrap()).required(false));
         self
     }
 
+    /// 加载所有匹配通配路径的配置文件（如 `conf.d/*.toml`），按路径排序后依次叠加，
+    /// 没有匹配的文件时忽略，目录或文件无法读取时在 build 时返回错误
+    pub fn add_glob(mut self, pattern: &str) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+
+        match expand(pattern) {
+            Ok(files) => {
+                if files.is_empty() {
+                    tracing::debug!("没有匹配 {} 的配置文件", pattern);
+                }
+                files.iter().fold(self, |builder, file| builder.add_file_with_includes(file))
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self
+            }
+        }
+    }
+
+    /// 指定激活的 profile，对应配置中的 `profiles.<name>` 段
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    /// 声明调用方自行读取的配置键（如 `payment`、`app.feature_flags`），这些键不会被视为未知键
+    pub fn known_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.known_keys.extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// 开启严格模式，存在未知配置键时 `build` 返回 `ConfigError::UnknownKeys`
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// 从.env文件加载环境变量
     pub fn add_dotenv(self) -> Self {
         // 加载.env文件，忽略错误
@@ -183,8 +298,48 @@ impl AppConfigBuilder {
 
     /// 构建最终配置
     pub fn build(self) -> Result<AppConfig> {
-        let config = self.config_builder.build()?;
-        let mut app_config: AppConfig = config.try_deserialize()?;
+        self.load().map(|(app_config, _)| app_config)
+    }
+
+    /// 构建配置，同时返回合并 profile 后的原始配置，供 [`check`](crate::check::check) 检查实际出现的键
+    pub(crate) fn load(self) -> Result<(AppConfig, Config)> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        let mut config = self.config_builder.build()?;
+
+        // 按合并后的值检查引用的环境变量，一次报告全部缺失项；被后加载的来源覆盖的值和注释不检查，
+        // `add_dotenv` 加载的变量同样生效
+        let mut missing: Vec<String> = Vec::new();
+        collect_missing_vars(&config.cache, &mut missing);
+        if !missing.is_empty() {
+            // 配置表不保证顺序，按名称排序使错误信息稳定
+            missing.sort();
+            return Err(ConfigError::MissingTemplateVar(missing.join(", ")));
+        }
+        expand_value(&mut config.cache)?;
+
+        // 合并激活的 profile
+        let profile = match self.profile {
+            Some(profile) => Some(profile),
+            None => config.get::<Option<String>>("env").ok().flatten(),
+        };
+        if let Some(profile) = profile {
+            config = apply_profile(config, &profile)?;
+        }
+
+        // 检查未被使用的配置键
+        let unknown = unknown_keys(&config, &self.known_keys);
+        if !unknown.is_empty() {
+            if self.strict {
+                return Err(ConfigError::UnknownKeys(unknown.join(", ")));
+            }
+            tracing::warn!("未知的配置项: {}", unknown.join(", "));
+        }
+
+        let mut app_config: AppConfig = config.clone().try_deserialize()?;
+        app_config.unknown_keys = unknown;
 
         // 后处理：如果主数据库已配置但databases.default未配置，则同步
         // 检查default是否为默认值（未配置）
@@ -198,7 +353,7 @@ impl AppConfigBuilder {
         // 验证配置
         app_config.validate()?;
 
-        Ok(app_config)
+        Ok((app_config, config))
     }
 }
 
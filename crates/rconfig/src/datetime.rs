@@ -0,0 +1,78 @@
+//! 配置中的日期时间
+//!
+//! config 读取 TOML 时会把日期时间（如 `2024-06-01T02:00:00+08:00`）转成字符串，
+//! 预设中的日期时间字段使用 [`ConfigDatetime`]，按 TOML 日期时间格式解析回来；
+//! 序列化时仍输出同样的字符串，经过 profile 合并、[`AppConfig::merge`](crate::AppConfig::merge) 后保持原值。
+//!
+//! 环境变量等字符串来源中按同样的格式书写即可。
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+use toml::value::Datetime;
+
+/// TOML 日期时间，支持带时区偏移、本地日期时间、本地日期和本地时间四种形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ConfigDatetime(Datetime);
+
+impl ConfigDatetime {
+    pub fn as_datetime(&self) -> &Datetime {
+        &self.0
+    }
+
+    /// 是否带时区偏移，不带偏移的日期时间按部署机器的本地时间理解
+    pub fn has_offset(&self) -> bool {
+        self.0.offset.is_some()
+    }
+}
+
+impl fmt::Display for ConfigDatetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for ConfigDatetime {
+    type Err = toml::value::DatetimeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl TryFrom<String> for ConfigDatetime {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse().map_err(|e| format!("无效的日期时间 {}: {}", value, e))
+    }
+}
+
+impl From<ConfigDatetime> for String {
+    fn from(value: ConfigDatetime) -> Self {
+        value.to_string()
+    }
+}
+
+/// 在 JSON Schema 中按字符串描述，与序列化结果一致
+impl JsonSchema for ConfigDatetime {
+    fn schema_name() -> Cow<'static, str> {
+        "ConfigDatetime".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": "TOML 日期时间，如 2024-06-01T02:00:00+08:00",
+        })
+    }
+}
+
+impl From<Datetime> for ConfigDatetime {
+    fn from(value: Datetime) -> Self {
+        Self(value)
+    }
+}
@@ -16,6 +16,15 @@ pub enum ConfigError {
     #[error("验证错误: {0}")]
     ValidationError(String),
 
+    #[error("配置文件循环引用: {0}")]
+    IncludeCycle(String),
+
+    #[error("缺少配置引用的环境变量: {0}")]
+    MissingTemplateVar(String),
+
+    #[error("未知的配置项: {0}")]
+    UnknownKeys(String),
+
     #[error("URL解析错误: {0}")]
     UrlParseError(#[from] url::ParseError),
 }
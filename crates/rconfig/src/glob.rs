@@ -0,0 +1,130 @@
+//! 按通配符批量加载配置片段
+//!
+//! 部署时常把配置片段放到 `conf.d/` 之类的目录中，通过
+//! [`AppConfigBuilder::add_glob`](crate::config::AppConfigBuilder::add_glob) 一次加载全部匹配的文件，
+//! 按路径排序后依次叠加，后加载的覆盖先加载的。路径的每一段都可以使用 `*`（任意长度）和 `?`（单个字符），
+//! 如 `conf.d/*.toml`、`services/*/config.toml`。
+
+use crate::error::{ConfigError, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// 展开通配路径，返回排序后的匹配文件，没有匹配时返回空列表
+///
+/// 不存在的目录视为没有匹配，存在但无法读取的目录返回错误
+pub fn expand(pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut candidates = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let part = match component {
+            Component::Normal(part) => part.to_string_lossy(),
+            // 根目录、`.`、`..` 等原样拼接
+            other => {
+                candidates.iter_mut().for_each(|c| c.push(other));
+                continue;
+            }
+        };
+
+        if !part.contains(['*', '?']) {
+            candidates.iter_mut().for_each(|c| c.push(part.as_ref()));
+            continue;
+        }
+
+        let mut matched = Vec::new();
+        for dir in &candidates {
+            let read_from = if dir.as_os_str().is_empty() { Path::new(".") } else { dir.as_path() };
+            let entries = match std::fs::read_dir(read_from) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(ConfigError::MissingConfig(format!("读取配置目录失败: {} ({})", read_from.display(), e)));
+                }
+            };
+            for entry in entries {
+                let entry = entry
+                    .map_err(|e| ConfigError::MissingConfig(format!("读取配置目录失败: {} ({})", read_from.display(), e)))?;
+                let name = entry.file_name();
+                // 与 shell 一致，通配符不匹配隐藏文件
+                let name = name.to_string_lossy();
+                if !name.starts_with('.') && wildcard_match(part.as_bytes(), name.as_bytes()) {
+                    matched.push(dir.join(name.as_ref()));
+                }
+            }
+        }
+        candidates = matched;
+    }
+
+    let mut files: Vec<PathBuf> = candidates.into_iter().filter(|path| path.is_file()).collect();
+    files.sort();
+    Ok(files)
+}
+
+/// `*` 匹配任意长度，`?` 匹配单个字符
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // 最近一次 `*` 的位置及其匹配到的位置，失配时回溯
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfig;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_merge_fragments_in_sorted_order() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let conf_d = temp.path().join("conf.d");
+        fs::create_dir(&conf_d)?;
+
+        fs::write(conf_d.join("20-workers.toml"), "[server]\nport = 9090\nworkers = 8\n")?;
+        fs::write(conf_d.join("10-server.toml"), "[server]\nhost = \"0.0.0.0\"\nport = 8080\n")?;
+        fs::write(conf_d.join("README.md"), "not a config")?;
+
+        let config = AppConfig::new()
+            .add_glob(conf_d.join("*.toml").to_str().unwrap())
+            .build()?;
+        assert_eq!(config.server.host, "0.0.0.0");
+        // 20-workers.toml 后加载，覆盖端口
+        assert_eq!(config.server.port, 9090);
+        assert_eq!(config.server.workers, 8);
+
+        // 没有匹配的文件时不影响构建
+        let pattern = temp.path().join("missing.d/*.toml");
+        assert!(expand(pattern.to_str().unwrap())?.is_empty());
+        AppConfig::new().add_glob(pattern.to_str().unwrap()).build()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match(b"*.toml", b"10-server.toml"));
+        assert!(wildcard_match(b"??-*.toml", b"10-server.toml"));
+        assert!(!wildcard_match(b"*.toml", b"server.yaml"));
+        assert!(!wildcard_match(b"?.toml", b"10.toml"));
+    }
+}
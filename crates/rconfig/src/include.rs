@@ -0,0 +1,114 @@
+//! 配置文件 include 指令
+//!
+//! 配置文件顶层可以声明 `include = ["common.toml", "secrets.toml"]`，
+//! 被引用的文件（相对于当前文件所在目录）会先于当前文件加载，当前文件的配置覆盖在其之上。
+
+use crate::error::{ConfigError, Result};
+use config::{Config, File};
+use std::path::{Path, PathBuf};
+
+/// include 指令的键名
+pub const INCLUDE_KEY: &str = "include";
+
+/// 展开配置文件的 include 链
+///
+/// 返回按加载顺序排列的文件列表，被引用的文件在前，`path` 本身在最后
+pub fn resolve_includes(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut stack = Vec::new();
+    collect(path, &mut stack, &mut files)?;
+    Ok(files)
+}
+
+fn collect(path: &Path, stack: &mut Vec<PathBuf>, files: &mut Vec<PathBuf>) -> Result<()> {
+    let path = path.canonicalize()
+        .map_err(|e| ConfigError::MissingConfig(format!("配置文件不存在: {} ({})", path.display(), e)))?;
+
+    if let Some(pos) = stack.iter().position(|p| p == &path) {
+        let chain = stack[pos..]
+            .iter()
+            .chain(std::iter::once(&path))
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(ConfigError::IncludeCycle(chain));
+    }
+
+    let includes: Vec<String> = match Config::builder()
+        .add_source(File::from(path.as_path()))
+        .build()?
+        .get(INCLUDE_KEY)
+    {
+        Ok(includes) => includes,
+        Err(config::ConfigError::NotFound(_)) => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    stack.push(path.clone());
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    for include in includes {
+        collect(&base.join(include), stack, files)?;
+    }
+    stack.pop();
+
+    files.push(path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfig;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_two_level_include_chain() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        fs::create_dir(temp.path().join("shared"))?;
+
+        fs::write(
+            temp.path().join("shared/base.toml"),
+            "[server]\nhost = \"0.0.0.0\"\nport = 8000\nworkers = 2\n",
+        )?;
+        fs::write(
+            temp.path().join("shared/common.toml"),
+            "include = [\"base.toml\"]\n\n[server]\nport = 8080\n",
+        )?;
+        fs::write(
+            temp.path().join("app.toml"),
+            "include = [\"shared/common.toml\"]\n\n[server]\nworkers = 8\n",
+        )?;
+
+        let files = resolve_includes(&temp.path().join("app.toml"))?;
+        let names: Vec<_> = files
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["base.toml", "common.toml", "app.toml"]);
+
+        let config = AppConfig::new()
+            .add_file(temp.path().join("app.toml"))
+            .build()?;
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.workers, 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_cycle_detected() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        fs::write(temp.path().join("a.toml"), "include = [\"b.toml\"]\n")?;
+        fs::write(temp.path().join("b.toml"), "include = [\"a.toml\"]\n")?;
+
+        let result = resolve_includes(&temp.path().join("a.toml"));
+        assert!(matches!(result, Err(ConfigError::IncludeCycle(_))));
+
+        let result = AppConfig::new().add_file(temp.path().join("a.toml")).build();
+        assert!(matches!(result, Err(ConfigError::IncludeCycle(_))));
+
+        Ok(())
+    }
+}
@@ -15,12 +15,26 @@
 //! ```
 
 pub mod error;
+pub mod check;
 pub mod config;
+pub mod datetime;
 pub mod presets;
 pub mod extension;
+pub mod glob;
+pub mod include;
+pub mod overlay;
+pub mod profile;
+pub mod reload;
+pub mod schema;
+pub mod signal;
+pub mod strict;
+pub mod template;
 
 pub use config::AppConfig;
+pub use check::{check, CheckReport};
+pub use datetime::ConfigDatetime;
 pub use error::ConfigError;
+pub use signal::ConfigSignal;
 
 // 重导出常用预设，方便使用
 pub use presets::server::ServerConfig;
@@ -0,0 +1,100 @@
+//! 代码中叠加配置
+//!
+//! 测试或嵌入场景下，在已构建的配置上覆盖少量字段，不需要再写配置文件：
+//!
+//! ```ignore
+//! let mut config = base_config();
+//! config.merge(json!({ "server": { "port": 0 }, "extensions": { "payment": { "sandbox": true } } }))?;
+//! ```
+//!
+//! 叠加的值优先于原配置，合并规则与 profile 一致：对象逐键合并，其他值（包括数组）整体替换，
+//! 未出现在叠加值中的字段保持不变。
+
+use crate::error::Result;
+use crate::AppConfig;
+use serde_json::Value;
+
+impl AppConfig {
+    /// 将 `overlay` 合并到当前配置之上，合并后重新校验
+    ///
+    /// 合并结果无法反序列化或校验失败时返回错误，当前配置保持不变；合并成功后更新 [`subscribe`](AppConfig::subscribe) 的订阅
+    pub fn merge(&mut self, overlay: Value) -> Result<()> {
+        let mut value = serde_json::to_value(&*self)?;
+        merge_value(&mut value, overlay);
+
+        let mut merged: AppConfig = serde_json::from_value(value)?;
+        merged.unknown_keys = std::mem::take(&mut self.unknown_keys);
+        if let Err(e) = merged.validate() {
+            self.unknown_keys = merged.unknown_keys;
+            return Err(e);
+        }
+
+        self.reload(merged);
+        Ok(())
+    }
+}
+
+/// 深度合并，`overlay` 中的值优先
+pub(crate) fn merge_value(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_merge_overlays_fields() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("application.toml");
+        fs::write(
+            &path,
+            r#"
+[server]
+host = "127.0.0.1"
+port = 8080
+workers = 4
+
+[extensions.payment]
+notify_url = "https://pay.example.com/notify"
+channels = ["wechat", "alipay"]
+"#,
+        )?;
+        let mut config = AppConfig::new().add_file(&path).build()?;
+
+        config.merge(json!({
+            "server": { "port": 9090 },
+            "extensions": { "payment": { "channels": ["unionpay"], "sandbox": true } }
+        }))?;
+
+        assert_eq!(config.server.port, 9090);
+        // 未覆盖的字段保持不变
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(config.server.workers, 4);
+        assert_eq!(
+            config.extensions["payment"],
+            json!({ "notify_url": "https://pay.example.com/notify", "channels": ["unionpay"], "sandbox": true })
+        );
+
+        // 合并结果无效时保持原配置
+        assert!(config.merge(json!({ "server": { "port": "not a port" } })).is_err());
+        assert_eq!(config.server.port, 9090);
+
+        Ok(())
+    }
+}
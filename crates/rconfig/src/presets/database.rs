@@ -1,5 +1,6 @@
 //! 数据库配置 - 支持多数据源
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use url::Url;
 use std::collections::HashMap;
@@ -7,7 +8,7 @@ use crate::error::{ConfigError, Result};
 use super::Validate;
 
 /// 单个数据库配置
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct DatabaseConfig {
     /// 数据库类型: mysql, postgres等
     #[serde(default = "default_db_type")]
@@ -55,7 +56,7 @@ pub struct DatabaseConfig {
 }
 
 /// 多数据源配置，管理多个命名的数据库连接
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
 pub struct DatabaseSources {
     /// 默认/主数据库配置
     #[serde(default)]
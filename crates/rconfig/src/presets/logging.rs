@@ -1,13 +1,14 @@
 //! 日志配置
 
 use std::collections::HashMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use crate::error::Result;
 use super::Validate;
 use std::path::PathBuf;
 use tracing::log;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct LogConfig {
     /// 日志级别: trace, debug, info, warn, error
     #[serde(default = "default_level")]
@@ -57,9 +58,189 @@ pub struct LogConfig {
     /// 是否显示线程ID
     #[serde(default)]
     pub show_thread_id: bool,
+    /// 只有不低于该级别的日志输出源码位置和线程信息（如 `warn`），为空时所有日志按上面的开关统一输出
+    #[serde(default)]
+    pub verbose_from_level: Option<String>,
     /// 模块级别过滤器
     pub module_filters: HashMap<String, String>,
-    
+
+    /// Chrome Trace 输出文件路径，设置后记录 span 的开始/结束事件，可在 chrome://tracing 中打开
+    #[serde(default)]
+    pub chrome_trace_path: Option<PathBuf>,
+
+    /// span 关闭时通过 `metrics` 记录忙碌/空闲耗时直方图，指标名为 `<target>::<span 名称>`
+    #[serde(default)]
+    pub span_metrics: bool,
+
+    /// `log` crate 日志转发到 tracing 的最高级别，用于屏蔽基于 `log` 的依赖库的调试日志，为空时全部转发
+    #[serde(default)]
+    pub log_crate_max_level: Option<String>,
+
+    /// 按级别/目标分流的文件输出，如错误单独写入 `error.log`、审计写入 `audit.log`
+    #[serde(default)]
+    pub files: Vec<FileSink>,
+
+    /// 内存环形缓冲保留的最近日志条数，设置后可通过管理接口查看，为空时不启用
+    #[serde(default)]
+    pub ring_buffer_capacity: Option<usize>,
+
+    /// 直接推送到日志平台（Loki / Elasticsearch），需要启用 rlog 的 `ship` 特性
+    #[serde(default)]
+    pub ship: Option<ShipConfig>,
+
+    /// 脱敏规则，按顺序应用到输出的每条日志（包括消息和字段），为空时不处理
+    #[serde(default)]
+    pub redactions: Vec<RedactionRule>,
+}
+
+/// 日志脱敏规则
+///
+/// ```toml
+/// [[log.redactions]]
+/// pattern = "Bearer [A-Za-z0-9._~+/-]+=*"
+/// replacement = "Bearer ***"
+///
+/// [[log.redactions]]
+/// pattern = "\\b(\\d{6})\\d{6,9}(\\d{4})\\b"
+/// replacement = "${1}******${2}"
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct RedactionRule {
+    /// 正则表达式
+    pub pattern: String,
+
+    /// 替换内容，可用 `${1}` 引用捕获组；JSON 格式输出时不要包含引号等需要转义的字符
+    #[serde(default = "default_redaction_replacement")]
+    pub replacement: String,
+}
+
+impl RedactionRule {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            replacement: default_redaction_replacement(),
+        }
+    }
+
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = replacement.into();
+        self
+    }
+}
+
+fn default_redaction_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+/// 日志平台类型
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ShipKind {
+    /// Loki push API，如 `http://loki:3100/loki/api/v1/push`
+    Loki,
+    /// Elasticsearch bulk API，如 `http://es:9200/app-logs/_bulk`
+    Elasticsearch,
+}
+
+/// 日志推送配置
+///
+/// 日志先进入内存队列，按批推送；队列已满时丢弃新日志，不阻塞业务线程
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ShipConfig {
+    pub kind: ShipKind,
+
+    /// 推送地址
+    pub endpoint: String,
+
+    /// 附加到每条日志的标签，Loki 中作为流标签，Elasticsearch 中作为文档字段
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    /// 每批最多推送的日志条数
+    #[serde(default = "default_ship_batch_size")]
+    pub batch_size: usize,
+
+    /// 未攒满一批时的推送间隔(毫秒)
+    #[serde(default = "default_ship_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+
+    /// 等待推送的最大日志条数，超过后丢弃
+    #[serde(default = "default_ship_queue_capacity")]
+    pub queue_capacity: usize,
+}
+
+impl ShipConfig {
+    pub fn new(kind: ShipKind, endpoint: impl Into<String>) -> Self {
+        Self {
+            kind,
+            endpoint: endpoint.into(),
+            labels: HashMap::new(),
+            batch_size: default_ship_batch_size(),
+            flush_interval_ms: default_ship_flush_interval_ms(),
+            queue_capacity: default_ship_queue_capacity(),
+        }
+    }
+
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+fn default_ship_batch_size() -> usize {
+    100
+}
+
+fn default_ship_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_ship_queue_capacity() -> usize {
+    10000
+}
+
+/// 日志文件输出目标
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct FileSink {
+    /// 日志文件路径
+    pub path: PathBuf,
+
+    /// 写入该文件的最低级别，如 `error` 只写入错误日志
+    #[serde(default = "default_sink_level")]
+    pub min_level: String,
+
+    /// 只写入目标（模块路径）以此为前缀的日志，如 `audit`
+    #[serde(default)]
+    pub target_filter: Option<String>,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            min_level: default_sink_level(),
+            target_filter: None,
+        }
+    }
+
+    pub fn with_min_level(mut self, level: impl Into<String>) -> Self {
+        self.min_level = level.into();
+        self
+    }
+
+    pub fn with_target_filter(mut self, target: impl Into<String>) -> Self {
+        self.target_filter = Some(target.into());
+        self
+    }
+}
+
+fn default_sink_level() -> String {
+    "trace".to_string()
 }
 
 fn default_level() -> String {
@@ -106,24 +287,84 @@ impl Default for LogConfig {
             show_timestamp: false,
             show_target: false,
             show_thread_id: false,
+            verbose_from_level: None,
             module_filters: HashMap::new(),
+            chrome_trace_path: None,
+            log_crate_max_level: None,
+            files: Vec::new(),
+            ring_buffer_capacity: None,
+            ship: None,
+            span_metrics: false,
+            redactions: Vec::new(),
         }
     }
 }
 
 impl LogConfig {
     /// 将字符串日志级别转换为log crate的Level
+    ///
+    /// 级别在加载配置时已经校验，这里不会遇到无效值
     pub fn parse_level(&self) -> log::LevelFilter {
-        match self.level.to_lowercase().as_str() {
-            "trace" => log::LevelFilter::Trace,
-            "debug" => log::LevelFilter::Debug,
-            "info" => log::LevelFilter::Info,
-            "warn" => log::LevelFilter::Warn,
-            "error" => log::LevelFilter::Error,
-            "off" => log::LevelFilter::Off,
-            _ => log::LevelFilter::Info,
-        }
+        parse_level_filter(&self.level)
+    }
+
+    /// `log` crate 日志转发的最高级别，未配置时不限制
+    pub fn parse_log_crate_max_level(&self) -> log::LevelFilter {
+        self.log_crate_max_level
+            .as_deref()
+            .map(parse_level_filter)
+            .unwrap_or(log::LevelFilter::Trace)
     }
+
+    /// 模块级别过滤器，按模块名排序
+    pub fn module_levels(&self) -> Vec<(&str, log::LevelFilter)> {
+        let mut levels: Vec<_> = self.module_filters
+            .iter()
+            .map(|(module, level)| (module.as_str(), parse_level_filter(level)))
+            .collect();
+        levels.sort_by_key(|(module, _)| *module);
+        levels
+    }
+}
+
+/// 解析日志级别名称，不区分大小写，无效时返回 `None`
+pub fn level_filter_from_str(level: &str) -> Option<log::LevelFilter> {
+    match level.to_lowercase().as_str() {
+        "trace" => Some(log::LevelFilter::Trace),
+        "debug" => Some(log::LevelFilter::Debug),
+        "info" => Some(log::LevelFilter::Info),
+        "warn" => Some(log::LevelFilter::Warn),
+        "error" => Some(log::LevelFilter::Error),
+        "off" => Some(log::LevelFilter::Off),
+        _ => None,
+    }
+}
+
+fn parse_level_filter(level: &str) -> log::LevelFilter {
+    level_filter_from_str(level).unwrap_or(log::LevelFilter::Info)
+}
+
+/// 校验日志级别，错误信息包含配置路径和原始值
+fn check_level(path: &str, level: &str) -> Result<log::LevelFilter> {
+    level_filter_from_str(level).ok_or_else(|| crate::error::ConfigError::ValidationError(
+        format!("{}: 无效的日志级别 '{}'，可选值: trace, debug, info, warn, error, off", path, level)
+    ))
+}
+
+/// 模块过滤器的目标必须是模块路径，如 `sqlx` 或 `my_app::db`，
+/// 不能包含 `=`、`[`、`,` 等指令语法，否则 rlog 拼出的过滤指令无法解析
+fn check_module_filter(module: &str, level: &str) -> Result<()> {
+    let path = format!("log.module_filters.{}", module);
+    let valid_target = !module.is_empty()
+        && module.split("::").all(|segment| {
+            !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+    if !valid_target {
+        return Err(crate::error::ConfigError::ValidationError(
+            format!("{}: 无效的模块过滤指令 '{}={}'", path, module, level)
+        ));
+    }
+    check_level(&path, level).map(|_| ())
 }
 
 impl Validate for LogConfig {
@@ -135,14 +376,52 @@ impl Validate for LogConfig {
             ));
         }
 
-        // 检查日志级别是否有效
-        if !["trace", "debug", "info", "warn", "error", "off"]
-            .contains(&self.level.to_lowercase().as_str()) {
+        // 检查日志级别是否有效，避免到 rlog 初始化时才失败
+        check_level("log.level", &self.level)?;
+
+        if let Some(level) = &self.log_crate_max_level {
+            check_level("log.log_crate_max_level", level)?;
+        }
+
+        if let Some(level) = &self.verbose_from_level
+            && check_level("log.verbose_from_level", level)? == log::LevelFilter::Off
+        {
             return Err(crate::error::ConfigError::ValidationError(
-                format!("无效的日志级别: {}", self.level)
+                format!("log.verbose_from_level: 无效的详细字段日志级别 '{}'", level)
             ));
         }
 
+        let mut modules: Vec<_> = self.module_filters.iter().collect();
+        modules.sort();
+        for (module, level) in modules {
+            check_module_filter(module, level)?;
+        }
+
+        for (i, sink) in self.files.iter().enumerate() {
+            check_level(&format!("log.files[{}].min_level", i), &sink.min_level)?;
+        }
+
+        if let Some(ship) = &self.ship {
+            if url::Url::parse(&ship.endpoint).is_err() {
+                return Err(crate::error::ConfigError::ValidationError(
+                    format!("无效的日志推送地址: {}", ship.endpoint)
+                ));
+            }
+            if ship.batch_size == 0 || ship.queue_capacity == 0 {
+                return Err(crate::error::ConfigError::ValidationError(
+                    "日志推送的 batch_size 和 queue_capacity 必须大于 0".to_string()
+                ));
+            }
+        }
+
+        for rule in &self.redactions {
+            if let Err(e) = regex::Regex::new(&rule.pattern) {
+                return Err(crate::error::ConfigError::ValidationError(
+                    format!("无效的日志脱敏规则: {} ({})", rule.pattern, e)
+                ));
+            }
+        }
+
         // 检查日志格式是否有效
         if !["json", "text"].contains(&self.format.to_lowercase().as_str()) {
             return Err(crate::error::ConfigError::ValidationError(
@@ -153,3 +432,78 @@ impl Validate for LogConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfig;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn build(content: &str) -> Result<AppConfig> {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("application.toml");
+        fs::write(&path, content).unwrap();
+        AppConfig::new().add_file(&path).build()
+    }
+
+    #[test]
+    fn test_valid_level() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let config = build(r#"
+[log]
+level = "DEBUG"
+
+[log.module_filters]
+sqlx = "warn"
+"my_app::db" = "trace"
+"#)?;
+
+        let log = config.log.unwrap();
+        assert_eq!(log.parse_level(), log::LevelFilter::Debug);
+        assert_eq!(
+            log.module_levels(),
+            vec![("my_app::db", log::LevelFilter::Trace), ("sqlx", log::LevelFilter::Warn)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_level() {
+        let err = build(r#"
+[log]
+level = "debgu"
+module_filters = {}
+"#).unwrap_err();
+
+        match err {
+            crate::error::ConfigError::ValidationError(message) => {
+                assert!(message.contains("log.level"), "{}", message);
+                assert!(message.contains("debgu"), "{}", message);
+            }
+            other => panic!("expected ValidationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_module_filter() {
+        let err = build(r#"
+[log]
+level = "info"
+
+[log.module_filters]
+"hyper=debug,sqlx" = "warn"
+"#).unwrap_err();
+
+        match err {
+            crate::error::ConfigError::ValidationError(message) => {
+                assert!(message.contains("log.module_filters.hyper=debug,sqlx"), "{}", message);
+            }
+            other => panic!("expected ValidationError, got {:?}", other),
+        }
+
+        // 模块名合法但级别无效
+        let mut log = LogConfig::default();
+        log.module_filters.insert("sqlx".to_string(), "loud".to_string());
+        assert!(log.validate().is_err());
+    }
+}
@@ -1,10 +1,11 @@
 //! Redis配置
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use crate::error::{ConfigError, Result};
 use super::Validate;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct RedisConfig {
     /// Redis主机
     #[serde(default = "default_host")]
@@ -26,6 +27,10 @@ pub struct RedisConfig {
     #[serde(default)]
     pub database: u8,
 
+    /// 分布式锁使用的数据库索引，未设置时与 `database` 相同
+    #[serde(default)]
+    pub lock_database: Option<u8>,
+
     /// 连接池大小
     #[serde(default = "default_pool_size")]
     pub pool_size: u32,
@@ -71,6 +76,7 @@ impl Default for RedisConfig {
             username: None,
             password: None,
             database: 0,
+            lock_database: None,
             pool_size: default_pool_size(),
             timeout: default_timeout(),
             url: None,
@@ -120,6 +126,11 @@ impl Validate for RedisConfig {
                 "Redis集群模式启用时，集群节点列表不能为空".to_string()
             ));
         }
+        if self.cluster_mode && self.lock_database.is_some_and(|db| db != 0) {
+            return Err(ConfigError::ValidationError(
+                "Redis集群模式只支持0号数据库，不能设置 lock_database".to_string()
+            ));
+        }
         Ok(())
     }
 }
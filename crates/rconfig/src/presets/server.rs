@@ -1,10 +1,12 @@
 //! 服务器配置
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use crate::datetime::ConfigDatetime;
 use crate::error::Result;
 use super::Validate;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct ServerConfig {
     /// 服务器主机名或IP
     #[serde(default = "default_host")]
@@ -37,6 +39,46 @@ pub struct ServerConfig {
     /// 密钥文件路径（如果use_tls=true）
     #[serde(default)]
     pub key_path: Option<String>,
+
+    /// 额外监听的地址，TOML 中以 `[[server.listeners]]` 声明；为空时只监听 `host:port`
+    #[serde(default)]
+    pub listeners: Vec<Listener>,
+
+    /// 维护窗口
+    #[serde(default)]
+    pub maintenance_window: Option<MaintenanceWindow>,
+}
+
+/// 监听地址
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct Listener {
+    #[serde(default = "default_host")]
+    pub host: String,
+
+    pub port: u16,
+}
+
+/// 维护窗口，起止时间使用 TOML 日期时间
+///
+/// ```toml
+/// [server.maintenance_window]
+/// start = 2024-06-01T02:00:00+08:00
+/// end = 2024-06-01T04:00:00+08:00
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct MaintenanceWindow {
+    pub start: ConfigDatetime,
+    pub end: ConfigDatetime,
+}
+
+impl ServerConfig {
+    /// 需要绑定的全部地址：配置了 `listeners` 时使用其中的地址，否则为 `host:port`
+    pub fn bind_addresses(&self) -> Vec<(String, u16)> {
+        if self.listeners.is_empty() {
+            return vec![(self.host.clone(), self.port)];
+        }
+        self.listeners.iter().map(|l| (l.host.clone(), l.port)).collect()
+    }
 }
 
 fn default_host() -> String {
@@ -70,6 +112,8 @@ impl Default for ServerConfig {
             use_tls: false,
             cert_path: None,
             key_path: None,
+            listeners: Vec::new(),
+            maintenance_window: None,
         }
     }
 }
@@ -89,8 +133,97 @@ impl Validate for ServerConfig {
                 ));
             }
         }
+
+        // 同一地址不能重复监听
+        for (i, listener) in self.listeners.iter().enumerate() {
+            if self.listeners[..i].contains(listener) {
+                return Err(crate::error::ConfigError::ValidationError(
+                    format!("重复的监听地址: {}:{}", listener.host, listener.port)
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfig;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const CONTENT: &str = r#"
+env = "prod"
+
+[server]
+port = 8080
+
+[[server.listeners]]
+host = "0.0.0.0"
+port = 8080
+
+[[server.listeners]]
+host = "::"
+port = 8443
+
+[server.maintenance_window]
+start = 2024-06-01T02:00:00+08:00
+end = 2024-06-01T04:00:00+08:00
+
+[[profiles.prod.server.listeners]]
+port = 80
+"#;
+
+    #[test]
+    fn test_listeners_and_datetime() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("application.toml");
+        fs::write(&path, CONTENT)?;
+
+        // 未激活 profile 时使用文件中的两个监听地址
+        let config = AppConfig::new().add_file(&path).profile("dev").strict(true).build()?;
+        assert_eq!(
+            config.server.bind_addresses(),
+            vec![("0.0.0.0".to_string(), 8080), ("::".to_string(), 8443)]
+        );
+        let window = config.server.maintenance_window.clone().unwrap();
+        assert_eq!(window.start.to_string(), "2024-06-01T02:00:00+08:00");
+        assert!(window.end.has_offset());
+
+        // 数组在 profile 中整体替换，日期时间保持不变
+        let config = AppConfig::new().add_file(&path).build()?;
+        assert_eq!(config.server.bind_addresses(), vec![("127.0.0.1".to_string(), 80)]);
+        assert_eq!(config.server.maintenance_window, Some(window.clone()));
+
+        // 序列化后再反序列化保持原值
+        let mut merged = config.clone();
+        merged.merge(json!({ "server": { "port": 9090 } }))?;
+        assert_eq!(merged.server.maintenance_window, Some(window));
+        assert_eq!(
+            merged.get_value("server.maintenance_window.end"),
+            Some(json!("2024-06-01T04:00:00+08:00"))
+        );
+
         Ok(())
     }
+
+    #[test]
+    fn test_invalid_listeners_and_datetime() {
+        let server: std::result::Result<ServerConfig, _> = serde_json::from_value(json!({
+            "maintenance_window": { "start": "tomorrow", "end": "2024-06-01" }
+        }));
+        assert!(server.is_err());
+
+        let listener = Listener { host: "0.0.0.0".to_string(), port: 8080 };
+        let server = ServerConfig {
+            listeners: vec![listener.clone(), listener],
+            ..Default::default()
+        };
+        assert!(server.validate().is_err());
+    }
 }
 
 
@@ -0,0 +1,100 @@
+//! 单文件多环境配置
+//!
+//! 除了 `application_{env}` 多文件叠加外，也可以在同一个文件中声明 `profiles.<name>`：
+//!
+//! ```toml
+//! [server]
+//! port = 8080
+//!
+//! [profiles.prod.server]
+//! port = 80
+//! ```
+//!
+//! 当前激活的 profile 对应的配置会深度合并到基础配置之上。
+
+use crate::error::Result;
+use config::{Config, Value, ValueKind};
+
+/// profiles 配置的键名
+pub const PROFILES_KEY: &str = "profiles";
+
+/// 将激活的 profile 合并到基础配置上
+///
+/// profile 中的表会逐键合并，其他值（包括数组）整体替换；未声明该 profile 时原样返回
+pub fn apply_profile(config: Config, profile: &str) -> Result<Config> {
+    let section = match config.get::<Value>(&format!("{}.{}", PROFILES_KEY, profile)) {
+        Ok(section) => section,
+        Err(config::ConfigError::NotFound(_)) => return Ok(config),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut overrides = Vec::new();
+    flatten(String::new(), section, &mut overrides);
+
+    let mut builder = Config::builder().add_source(config);
+    for (key, value) in overrides {
+        builder = builder.set_override(key, value)?;
+    }
+    Ok(builder.build()?)
+}
+
+/// 展开为 `a.b.c` 形式的叶子节点
+pub(crate) fn flatten(prefix: String, value: Value, out: &mut Vec<(String, Value)>) {
+    match value.kind {
+        ValueKind::Table(table) if !table.is_empty() => {
+            for (key, value) in table {
+                let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                flatten(path, value, out);
+            }
+        }
+        _ => out.push((prefix, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::AppConfig;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const CONTENT: &str = r#"
+env = "prod"
+
+[server]
+host = "127.0.0.1"
+port = 8080
+workers = 4
+
+[profiles.dev.server]
+port = 3000
+
+[profiles.prod.server]
+host = "0.0.0.0"
+port = 80
+"#;
+
+    #[test]
+    fn test_active_profile_overrides_base() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("application.toml");
+        fs::write(&path, CONTENT)?;
+
+        // 未指定时使用配置中的 env
+        let config = AppConfig::new().add_file(&path).build()?;
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, 80);
+        // 未被 profile 覆盖的值保持不变
+        assert_eq!(config.server.workers, 4);
+
+        // 显式指定 profile
+        let config = AppConfig::new().add_file(&path).profile("dev").build()?;
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(config.server.port, 3000);
+
+        // 不存在的 profile 使用基础配置
+        let config = AppConfig::new().add_file(&path).profile("test").build()?;
+        assert_eq!(config.server.port, 8080);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+//! 配置变更后重建派生资源
+//!
+//! 连接池、日志过滤器等由配置派生的资源，在配置变更时需要按新配置重建并替换。
+//! [`ReloadableResource`] 持有当前实例和重建函数，作为 [`ConfigChangeObserver`] 注册后，
+//! 相关配置段变化时重建并整体替换，重建失败时保留旧实例继续使用。
+//! [`ConfigObservers`] 通过 [`AppConfig::observe`] 挂到配置上，[`AppConfig::reload`] 和
+//! [`AppConfig::merge`] 更新配置后自动通知：
+//!
+//! ```ignore
+//! let pool = Arc::new(
+//!     ReloadableResource::new(&config, |c| DbPool::connect(c.database()))?
+//!         .when(|old, new| section_changed(old, new, "database")),
+//! );
+//! let log_filter = Arc::new(
+//!     ReloadableResource::new(&config, |c| EnvFilter::try_new(&c.log.clone().unwrap_or_default().level))?
+//!         .when(|old, new| section_changed(old, new, "log")),
+//! );
+//! let mut observers = ConfigObservers::new();
+//! observers.register(pool.clone());
+//! observers.register(log_filter.clone());
+//! config.observe(Arc::new(observers));
+//!
+//! // 配置文件变化后重新加载，相关资源随之重建
+//! config.reload(AppConfig::new().add_file("config/application.toml").build()?);
+//! let conn = pool.load().get().await?;
+//! ```
+//!
+//! 当前实例保存在 [`ArcSwap`] 中，[`ReloadableResource::load`] 无锁读取，重建完成后原子替换，
+//! 不会阻塞读取；已拿到旧实例的读取方继续使用旧实例，直到自行释放。
+
+use crate::AppConfig;
+use arc_swap::ArcSwap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+/// 配置变更观察者
+pub trait ConfigChangeObserver: Send + Sync {
+    /// 配置从 `old` 变为 `new` 后调用
+    fn on_change(&self, old: &AppConfig, new: &AppConfig);
+}
+
+/// 已注册的配置变更观察者
+#[derive(Default)]
+pub struct ConfigObservers {
+    observers: Vec<Arc<dyn ConfigChangeObserver>>,
+}
+
+impl ConfigObservers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, observer: Arc<dyn ConfigChangeObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// 按注册顺序通知所有观察者
+    pub fn notify(&self, old: &AppConfig, new: &AppConfig) {
+        for observer in &self.observers {
+            observer.on_change(old, new);
+        }
+    }
+}
+
+impl ConfigChangeObserver for ConfigObservers {
+    fn on_change(&self, old: &AppConfig, new: &AppConfig) {
+        self.notify(old, new);
+    }
+}
+
+/// 两份配置中 `key`（点分路径，同 [`AppConfig::get_value`]）的值是否不同
+pub fn section_changed(old: &AppConfig, new: &AppConfig, key: &str) -> bool {
+    old.get_value(key) != new.get_value(key)
+}
+
+type Rebuild<T, E> = Box<dyn Fn(&AppConfig) -> Result<T, E> + Send + Sync>;
+type Relevant = Box<dyn Fn(&AppConfig, &AppConfig) -> bool + Send + Sync>;
+
+/// 随配置重建的资源
+pub struct ReloadableResource<T, E> {
+    current: ArcSwap<T>,
+    rebuild: Rebuild<T, E>,
+    relevant: Relevant,
+}
+
+impl<T, E> ReloadableResource<T, E> {
+    /// 按当前配置创建初始实例，失败时返回错误
+    pub fn new<F>(config: &AppConfig, rebuild: F) -> Result<Self, E>
+    where
+        F: Fn(&AppConfig) -> Result<T, E> + Send + Sync + 'static,
+    {
+        let initial = rebuild(config)?;
+        Ok(Self {
+            current: ArcSwap::from_pointee(initial),
+            rebuild: Box::new(rebuild),
+            relevant: Box::new(|_, _| true),
+        })
+    }
+
+    /// 只在 `relevant` 返回 `true` 时重建，默认任何变更都重建
+    pub fn when<F>(mut self, relevant: F) -> Self
+    where
+        F: Fn(&AppConfig, &AppConfig) -> bool + Send + Sync + 'static,
+    {
+        self.relevant = Box::new(relevant);
+        self
+    }
+
+    /// 当前实例
+    pub fn load(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+
+    /// 按新配置重建并替换，失败时保留当前实例
+    pub fn reload(&self, config: &AppConfig) -> Result<(), E> {
+        let rebuilt = (self.rebuild)(config)?;
+        self.current.store(Arc::new(rebuilt));
+        Ok(())
+    }
+}
+
+impl<T, E> ConfigChangeObserver for ReloadableResource<T, E>
+where
+    T: Send + Sync,
+    E: Display,
+{
+    fn on_change(&self, old: &AppConfig, new: &AppConfig) {
+        if !(self.relevant)(old, new) {
+            return;
+        }
+        if let Err(e) = self.reload(new) {
+            tracing::warn!("配置变更后重建资源失败，继续使用原实例: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogConfig;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn base_config() -> AppConfig {
+        AppConfig::new().build().unwrap()
+    }
+
+    #[test]
+    fn test_config_change_rebuilds_resource() {
+        let old = base_config();
+        let rebuilds = Arc::new(AtomicUsize::new(0));
+        let counter = rebuilds.clone();
+
+        // 模拟按服务器配置创建的资源，端口为 0 时创建失败
+        let resource = Arc::new(
+            ReloadableResource::new(&old, move |config: &AppConfig| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                match config.server.port {
+                    0 => Err("端口无效".to_string()),
+                    port => Ok(format!("listener:{}", port)),
+                }
+            })
+            .unwrap()
+            .when(|old, new| section_changed(old, new, "server.port")),
+        );
+        let mut observers = ConfigObservers::new();
+        observers.register(resource.clone());
+
+        let before = resource.load();
+        let mut new = old.clone();
+        new.merge(json!({ "server": { "port": 9090 } })).unwrap();
+        observers.notify(&old, &new);
+
+        assert_eq!(*resource.load(), "listener:9090");
+        // 已取出的旧实例不受影响
+        assert_eq!(*before, format!("listener:{}", old.server.port));
+        assert_eq!(rebuilds.load(Ordering::SeqCst), 2);
+
+        // 无关配置变化不重建
+        let mut unrelated = new.clone();
+        unrelated.merge(json!({ "extensions": { "payment": { "sandbox": true } } })).unwrap();
+        observers.notify(&new, &unrelated);
+        assert_eq!(rebuilds.load(Ordering::SeqCst), 2);
+
+        // 重建失败时保留原实例
+        let mut invalid = unrelated.clone();
+        invalid.server.port = 0;
+        observers.notify(&unrelated, &invalid);
+        assert_eq!(rebuilds.load(Ordering::SeqCst), 3);
+        assert_eq!(*resource.load(), "listener:9090");
+    }
+
+    /// 按数据库配置创建的连接池
+    #[derive(Debug, PartialEq)]
+    struct Pool {
+        host: String,
+        max_connections: u32,
+    }
+
+    fn logged_config() -> AppConfig {
+        let mut config = base_config();
+        config.log = Some(LogConfig::default());
+        config
+    }
+
+    #[test]
+    fn test_reload_rebuilds_pool_and_log_filter() {
+        let mut config = logged_config();
+        let pool_builds = Arc::new(AtomicUsize::new(0));
+        let counter = pool_builds.clone();
+
+        let pool = Arc::new(
+            ReloadableResource::new(&config, move |config: &AppConfig| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, String>(Pool {
+                    host: config.database.host.clone(),
+                    max_connections: config.database.max_connections,
+                })
+            })
+            .unwrap()
+            .when(|old, new| section_changed(old, new, "database")),
+        );
+        // 模拟按日志级别创建的过滤器
+        let log_filter = Arc::new(
+            ReloadableResource::new(&config, |config: &AppConfig| {
+                Ok::<_, String>(config.log.clone().unwrap_or_default().level)
+            })
+            .unwrap()
+            .when(|old, new| section_changed(old, new, "log")),
+        );
+        let mut observers = ConfigObservers::new();
+        observers.register(pool.clone());
+        observers.register(log_filter.clone());
+        config.observe(Arc::new(observers));
+
+        // 只改日志级别时重建过滤器，连接池保持不变
+        let before = pool.load();
+        config.merge(json!({ "log": { "level": "debug" } })).unwrap();
+        assert_eq!(*log_filter.load(), "debug");
+        assert!(Arc::ptr_eq(&before, &pool.load()));
+        assert_eq!(pool_builds.load(Ordering::SeqCst), 1);
+
+        // 数据库配置变化后重建连接池，过滤器沿用当前实例
+        let filter = log_filter.load();
+        config.merge(json!({ "database": { "host": "db.internal", "max_connections": 20 } })).unwrap();
+        assert_eq!(*pool.load(), Pool { host: "db.internal".to_string(), max_connections: 20 });
+        assert!(Arc::ptr_eq(&filter, &log_filter.load()));
+        assert_eq!(pool_builds.load(Ordering::SeqCst), 2);
+
+        // 整体重新加载同样通知，观察者在重新加载之间保留
+        config.reload(logged_config());
+        assert_eq!(*log_filter.load(), LogConfig::default().level);
+        assert_eq!(pool.load().host, base_config().database.host);
+        config.merge(json!({ "log": { "level": "warn" } })).unwrap();
+        assert_eq!(*log_filter.load(), "warn");
+    }
+}
@@ -0,0 +1,84 @@
+//! 配置文件的 JSON Schema
+//!
+//! 由预设结构体上的 `#[derive(JsonSchema)]` 生成，描述各配置段（server、database、redis、rabbitmq、log）
+//! 的字段、类型和必填项，编辑器加载后可以对配置文件做校验和补全：
+//!
+//! ```ignore
+//! std::fs::write("config/schema.json", serde_json::to_string_pretty(&rconfig::schema::generate())?)?;
+//! ```
+//!
+//! 字段说明取自结构体的文档注释，默认值取自 `#[serde(default)]`，新增预设字段时不需要另外维护。
+//! 配置段的结构放在 `$defs` 中，属性通过 `$ref` 引用。
+
+use crate::config::AppConfig;
+use crate::include::INCLUDE_KEY;
+use crate::profile::PROFILES_KEY;
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+/// JSON Schema 草案版本，与 schemars 默认生成的版本一致
+pub const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// 生成 [`AppConfig`] 的 JSON Schema
+///
+/// `include` 和 `profiles` 在加载时就已处理，不是 [`AppConfig`] 的字段，这里单独补充
+pub fn generate() -> Value {
+    let mut schema = schema_for!(AppConfig).to_value();
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        properties.insert(
+            INCLUDE_KEY.to_string(),
+            json!({
+                "type": "array",
+                "description": "引入的配置文件路径，相对当前文件",
+                "items": { "type": "string" },
+            }),
+        );
+        properties.insert(
+            PROFILES_KEY.to_string(),
+            json!({
+                "type": "object",
+                "description": "按 profile 覆盖的配置，结构与顶层相同",
+                "additionalProperties": { "type": "object" },
+            }),
+        );
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::logging::LogConfig;
+    use crate::presets::rabbitmq::RabbitMqConfig;
+    use crate::presets::redis::RedisConfig;
+
+    #[test]
+    fn test_server_port_is_integer() {
+        let schema = generate();
+        assert_eq!(schema["$schema"], SCHEMA_DRAFT);
+
+        let port = &schema["$defs"]["ServerConfig"]["properties"]["port"];
+        assert_eq!(port["type"], "integer");
+        assert_eq!(port["default"], 8080);
+        assert_eq!(schema["$defs"]["LogConfig"]["required"], json!(["module_filters"]));
+        assert_eq!(schema["properties"][PROFILES_KEY]["type"], "object");
+    }
+
+    #[test]
+    fn test_default_config_validates() {
+        let schema = generate();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+
+        let mut config: AppConfig = serde_json::from_value(json!({})).unwrap();
+        config.redis = Some(RedisConfig::default());
+        config.rabbitmq = Some(RabbitMqConfig::default());
+        config.log = Some(LogConfig::default());
+        let config = serde_json::to_value(&config).unwrap();
+
+        let errors: Vec<String> = validator.iter_errors(&config).map(|e| e.to_string()).collect();
+        assert!(errors.is_empty(), "默认配置不符合 schema: {:?}", errors);
+
+        // 类型错误的配置无法通过校验
+        assert!(!validator.is_valid(&json!({ "server": { "port": "8080" } })));
+    }
+}
@@ -0,0 +1,224 @@
+//! 随配置重新加载更新的类型化配置
+//!
+//! 缓存的配置段在配置热加载后会过期。[`AppConfig::subscribe`] 返回的 [`ConfigSignal`] 始终持有路径对应的最新值，
+//! 配置通过 [`AppConfig::reload`] 或 [`AppConfig::merge`] 更新后，所有订阅自动按新配置重新解析：
+//!
+//! ```ignore
+//! let payment: ConfigSignal<PaymentSettings> = config.subscribe("payment")?; // extensions.payment
+//! let port: ConfigSignal<u16> = config.subscribe("server.port")?;
+//!
+//! // 配置文件变化后重新加载
+//! config.reload(AppConfig::new().add_file("config/application.toml").build()?);
+//! let sandbox = payment.get().sandbox;
+//! ```
+//!
+//! 新值解析失败时保留旧值。[`ConfigSignal`] 同时实现了 [`ConfigChangeObserver`]，也可以注册到
+//! [`ConfigObservers`](crate::reload::ConfigObservers) 中，通过 [`AppConfig::observe`] 随配置更新一起通知。
+
+use crate::error::{ConfigError, Result};
+use crate::reload::{section_changed, ConfigChangeObserver};
+use crate::AppConfig;
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// 配置路径对应的类型化值，克隆后共享同一份值
+pub struct ConfigSignal<T> {
+    inner: Arc<SignalInner<T>>,
+}
+
+struct SignalInner<T> {
+    path: String,
+    value: RwLock<Arc<T>>,
+}
+
+impl<T> Clone for ConfigSignal<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<T> std::fmt::Debug for ConfigSignal<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigSignal").field("path", &self.inner.path).finish()
+    }
+}
+
+impl<T: DeserializeOwned> ConfigSignal<T> {
+    fn new(config: &AppConfig, path: &str) -> Result<Self> {
+        Ok(Self {
+            inner: Arc::new(SignalInner {
+                path: path.to_string(),
+                value: RwLock::new(Arc::new(parse(config, path)?)),
+            }),
+        })
+    }
+
+    /// 订阅的配置路径
+    pub fn path(&self) -> &str {
+        &self.inner.path
+    }
+
+    /// 当前值
+    pub fn get(&self) -> Arc<T> {
+        self.inner.value.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 按新配置重新解析，失败时保留当前值
+    fn update(&self, config: &AppConfig) -> Result<()> {
+        let value = Arc::new(parse(config, &self.inner.path)?);
+        *self.inner.value.write().unwrap_or_else(|e| e.into_inner()) = value;
+        Ok(())
+    }
+}
+
+/// 路径不存在时按 `null` 解析，`Option` 等可以为空的类型得到默认值
+fn parse<T: DeserializeOwned>(config: &AppConfig, path: &str) -> Result<T> {
+    let value = config.get_value(path).unwrap_or(serde_json::Value::Null);
+    serde_json::from_value(value).map_err(|e| ConfigError::ValidationError(format!("{}: {}", path, e)))
+}
+
+impl<T> ConfigChangeObserver for ConfigSignal<T>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    fn on_change(&self, old: &AppConfig, new: &AppConfig) {
+        if !section_changed(old, new, &self.inner.path) {
+            return;
+        }
+        if let Err(e) = self.update(new) {
+            tracing::warn!("配置变更后解析订阅值失败，继续使用原值: {}", e);
+        }
+    }
+}
+
+/// 配置的订阅列表，重新加载后沿用；克隆的 [`AppConfig`] 从空列表开始，修改克隆不会通知原配置的订阅
+#[derive(Default)]
+pub(crate) struct Subscriptions {
+    signals: Arc<Mutex<Vec<Arc<dyn ConfigChangeObserver>>>>,
+}
+
+impl Subscriptions {
+    fn add(&self, signal: Arc<dyn ConfigChangeObserver>) {
+        self.signals.lock().unwrap_or_else(|e| e.into_inner()).push(signal);
+    }
+
+    pub(crate) fn notify(&self, old: &AppConfig, new: &AppConfig) {
+        let signals = self.signals.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        for signal in signals {
+            signal.on_change(old, new);
+        }
+    }
+}
+
+impl Clone for Subscriptions {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for Subscriptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.signals.lock().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("Subscriptions").field("count", &count).finish()
+    }
+}
+
+impl AppConfig {
+    /// 订阅 `path`（点分路径，同 [`AppConfig::get_value`]）对应的配置，当前值无法解析为 `T` 时返回错误
+    pub fn subscribe<T>(&self, path: &str) -> Result<ConfigSignal<T>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let signal = ConfigSignal::new(self, path)?;
+        self.subscriptions.add(Arc::new(signal.clone()));
+        Ok(signal)
+    }
+
+    /// 注册配置变更观察者（如 [`ConfigObservers`](crate::reload::ConfigObservers)），
+    /// [`reload`](AppConfig::reload) 或 [`merge`](AppConfig::merge) 更新配置后与订阅一起通知
+    pub fn observe(&self, observer: Arc<dyn ConfigChangeObserver>) {
+        self.subscriptions.add(observer);
+    }
+
+    /// 替换为重新加载的配置，沿用当前的订阅并按新配置更新
+    pub fn reload(&mut self, new: AppConfig) {
+        let mut old = std::mem::replace(self, new);
+        self.subscriptions = std::mem::take(&mut old.subscriptions);
+        self.subscriptions.notify(&old, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct PaymentSettings {
+        sandbox: bool,
+        #[serde(default)]
+        channels: Vec<String>,
+    }
+
+    #[test]
+    fn test_signal_follows_reload() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("application.toml");
+        let load = |content: &str| -> Result<AppConfig> {
+            fs::write(&path, content).unwrap();
+            AppConfig::new().add_file(&path).build()
+        };
+
+        let mut config = load("[server]\nport = 8080\n\n[extensions.payment]\nsandbox = true\n")?;
+        let port: ConfigSignal<u16> = config.subscribe("server.port")?;
+        let payment: ConfigSignal<PaymentSettings> = config.subscribe("payment")?;
+        let cached = port.clone();
+        assert_eq!(*port.get(), 8080);
+        assert!(payment.get().sandbox);
+
+        // 修改配置文件后重新加载，已有的订阅反映新值
+        config.reload(load("[server]\nport = 9090\n\n[extensions.payment]\nsandbox = false\nchannels = [\"unionpay\"]\n")?);
+        assert_eq!(*cached.get(), 9090);
+        assert_eq!(*payment.get(), PaymentSettings { sandbox: false, channels: vec!["unionpay".to_string()] });
+
+        // 订阅在多次重新加载之间保留，合并叠加值同样生效
+        config.reload(load("[server]\nport = 7070\n\n[extensions.payment]\nsandbox = false\n")?);
+        assert_eq!(*port.get(), 7070);
+        config.merge(json!({ "server": { "port": 6060 } }))?;
+        assert_eq!(*port.get(), 6060);
+
+        // 新值无法解析时保留原值
+        config.merge(json!({ "extensions": { "payment": { "sandbox": "maybe" } } }))?;
+        assert!(!payment.get().sandbox);
+        assert_eq!(payment.get().channels, Vec::<String>::new());
+
+        // 当前值无法解析时订阅失败
+        assert!(config.subscribe::<u16>("payment").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_clone_does_not_share_subscriptions() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let mut config = AppConfig::new().build()?;
+        config.merge(json!({ "server": { "port": 8080 } }))?;
+        let port: ConfigSignal<u16> = config.subscribe("server.port")?;
+
+        // 修改克隆不影响原配置的订阅
+        let mut copy = config.clone();
+        copy.merge(json!({ "server": { "port": 9090 } }))?;
+        assert_eq!(*port.get(), 8080);
+
+        // 克隆上的订阅只跟随克隆
+        let copied_port: ConfigSignal<u16> = copy.subscribe("server.port")?;
+        config.merge(json!({ "server": { "port": 7070 } }))?;
+        assert_eq!(*port.get(), 7070);
+        assert_eq!(*copied_port.get(), 9090);
+        copy.merge(json!({ "server": { "port": 6060 } }))?;
+        assert_eq!(*copied_port.get(), 6060);
+        assert_eq!(*port.get(), 7070);
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! 未知配置键检查
+//!
+//! 拼写错误的键（如 `databse.host`、`server.prot`）不会被任何配置段读取，只会让对应配置悄悄使用默认值。
+//! 构建配置时把文件中出现的键与 [`schema`](crate::schema::generate) 描述的字段比较，找出没有被使用的键。
+
+use crate::profile::flatten;
+use crate::schema;
+use config::Config;
+use serde_json::Value;
+use std::sync::LazyLock;
+
+/// serde 别名不出现在 schema 中，检查前换成对应的字段名
+const KEY_ALIASES: &[(&str, &str)] = &[("rabbitmq.tls", "rabbitmq.use_tls")];
+
+static SCHEMA: LazyLock<Value> = LazyLock::new(schema::generate);
+
+/// 找出不属于任何已知键的配置项
+///
+/// 预设配置段按 schema 逐级检查到叶子字段，没有声明字段的表（如 `extensions`、`profiles`）内容不限；
+/// `known` 为调用方自行读取的键，按前缀匹配：`payment` 覆盖 `payment.notify_url` 等全部子键。
+/// 返回按字典序排列的叶子键路径
+pub fn unknown_keys<S: AsRef<str>>(config: &Config, known: &[S]) -> Vec<String> {
+    let mut leaves = Vec::new();
+    flatten(String::new(), config.cache.clone(), &mut leaves);
+
+    let mut unknown: Vec<String> = leaves
+        .into_iter()
+        .map(|(key, _)| key)
+        .filter(|key| !key.is_empty() && !is_schema_key(key) && !is_known(key, known))
+        .collect();
+    unknown.sort();
+    unknown
+}
+
+/// 键是否对应 schema 中的字段
+fn is_schema_key(key: &str) -> bool {
+    let key = KEY_ALIASES
+        .iter()
+        .find_map(|(alias, field)| key.strip_prefix(alias).map(|rest| format!("{}{}", field, rest)))
+        .unwrap_or_else(|| key.to_string());
+    let parts: Vec<&str> = key.split('.').collect();
+    schema_allows(&SCHEMA, &parts)
+}
+
+fn schema_allows(schema: &Value, parts: &[&str]) -> bool {
+    // 配置段的结构定义在 `$defs` 中，属性只保存引用
+    if let Some(definition) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| SCHEMA.pointer(pointer))
+    {
+        return schema_allows(definition, parts);
+    }
+    let Some((part, rest)) = parts.split_first() else {
+        return true;
+    };
+    // 可以为空的字段或配置段
+    if let Some(Value::Array(variants)) = schema.get("anyOf") {
+        return variants.iter().any(|variant| schema_allows(variant, parts));
+    }
+    if let Some(property) = schema.get("properties").and_then(|properties| properties.get(*part)) {
+        return schema_allows(property, rest);
+    }
+    match schema.get("additionalProperties") {
+        Some(additional) if additional.is_object() => schema_allows(additional, rest),
+        // 没有声明字段的表内容不限
+        _ => schema.get("type").and_then(Value::as_str) == Some("object") && schema.get("properties").is_none(),
+    }
+}
+
+pub(crate) fn is_known<S: AsRef<str>>(key: &str, known: &[S]) -> bool {
+    known.iter().any(|known| {
+        let known = known.as_ref();
+        key == known || key.strip_prefix(known).is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppConfig, ConfigError};
+    use std::fs;
+    use tempfile::tempdir;
+
+    const CONTENT: &str = r#"
+[server]
+host = "0.0.0.0"
+port = 8080
+
+[databse]
+host = "127.0.0.1"
+
+[payment]
+notify_url = "https://example.com/notify"
+"#;
+
+    #[test]
+    fn test_known_key_prefix() {
+        assert!(is_known("server", &["server"]));
+        assert!(is_known("server.host", &["server"]));
+        assert!(!is_known("servers.host", &["server"]));
+    }
+
+    #[test]
+    fn test_nested_typo_flagged() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let config = Config::builder()
+            .add_source(config::File::from_str(
+                r#"
+[server]
+prot = 8080
+
+[[server.listeners]]
+host = "127.0.0.1"
+port = 8081
+
+[databases.sources.orders]
+hots = "127.0.0.1"
+url = "mysql://root@127.0.0.1/orders"
+
+[rabbitmq]
+tls = true
+
+[log]
+level = "info"
+
+[log.module_filters]
+sqlx = "warn"
+
+[log.ship.labels]
+app = "payment"
+
+[extensions.payment.wechat]
+mch_id = "1900000109"
+
+[profiles.prod.server]
+port = 80
+"#,
+                config::FileFormat::Toml,
+            ))
+            .build()?;
+
+        let known: [&str; 0] = [];
+        assert_eq!(unknown_keys(&config, &known), vec!["databases.sources.orders.hots", "server.prot"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_key_flagged() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("app.toml");
+        fs::write(&path, CONTENT)?;
+
+        // 默认只记录未知键
+        let config = AppConfig::new().add_file(&path).build()?;
+        assert_eq!(config.unknown_keys, vec!["databse.host", "payment.notify_url"]);
+
+        // 调用方声明的键不再视为未知
+        let config = AppConfig::new().add_file(&path).known_keys(["payment"]).build()?;
+        assert_eq!(config.unknown_keys, vec!["databse.host"]);
+
+        // 严格模式下未知键导致构建失败
+        let result = AppConfig::new().add_file(&path).known_keys(["payment"]).strict(true).build();
+        match result {
+            Err(ConfigError::UnknownKeys(keys)) => assert_eq!(keys, "databse.host"),
+            other => panic!("expected UnknownKeys, got {:?}", other.map(|_| ())),
+        }
+
+        Ok(())
+    }
+}
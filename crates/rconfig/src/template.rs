@@ -0,0 +1,283 @@
+//! 配置模板变量检查
+//!
+//! 配置文件中可以通过 `${VAR}` 引用环境变量：
+//!
+//! - `${VAR}`：必填，环境变量未设置时报错
+//! - `${VAR:?说明}`：必填，环境变量未设置或为空时报错
+//! - `${VAR:-默认值}`：可选，未设置或为空时使用默认值
+//!
+//! 构建配置时先扫描合并后字符串值中的所有引用（注释和被后加载的文件覆盖的值不检查），一次性报告全部缺失的变量，
+//! 避免缺失的变量被替换为空值后产生难以排查的配置。
+//! 随后把字符串值中的引用替换为环境变量的值，如 `uri = "redis://${REDIS_HOST:-localhost}:6379"`。
+
+use crate::error::{ConfigError, Result};
+use config::{Value, ValueKind};
+
+/// 配置文本中的一个环境变量引用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateVar {
+    pub name: String,
+    /// `:-` 提供的默认值，有默认值的变量不是必填的
+    pub default: Option<String>,
+    /// 使用 `:?` 声明，空值也视为缺失
+    pub non_empty: bool,
+}
+
+impl TemplateVar {
+    /// 当前环境中是否缺少该变量
+    pub fn is_missing(&self) -> bool {
+        self.resolve().is_none()
+    }
+
+    /// 变量在当前环境中的值，缺失时返回 `None`
+    pub fn resolve(&self) -> Option<String> {
+        match std::env::var(&self.name) {
+            Ok(value) if !value.is_empty() => Some(value),
+            Ok(value) if self.default.is_none() && !self.non_empty => Some(value),
+            _ => self.default.clone(),
+        }
+    }
+}
+
+/// 扫描文本中所有 `${...}` 引用
+pub fn template_vars(text: &str) -> Vec<TemplateVar> {
+    let mut vars = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        if let Some(var) = parse_var(&after[..end]) {
+            vars.push(var);
+        }
+        rest = &after[end + 1..];
+    }
+    vars
+}
+
+fn parse_var(expr: &str) -> Option<TemplateVar> {
+    let (name, default, non_empty) = if let Some((name, default)) = expr.split_once(":-") {
+        (name, Some(default.to_string()), false)
+    } else if let Some((name, _)) = expr.split_once(":?") {
+        (name, None, true)
+    } else {
+        (expr, None, false)
+    };
+
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| TemplateVar {
+        name: name.to_string(),
+        default,
+        non_empty,
+    })
+}
+
+/// 配置值中缺失的必填变量，追加到 `missing` 中并去重
+///
+/// 只检查解析后的字符串值，注释里的 `${...}` 不会被当作引用
+pub(crate) fn collect_missing_vars(value: &Value, missing: &mut Vec<String>) {
+    match &value.kind {
+        ValueKind::String(text) => {
+            for var in template_vars(text) {
+                if var.is_missing() && !missing.contains(&var.name) {
+                    missing.push(var.name);
+                }
+            }
+        }
+        ValueKind::Table(table) => {
+            for value in table.values() {
+                collect_missing_vars(value, missing);
+            }
+        }
+        ValueKind::Array(values) => {
+            for value in values {
+                collect_missing_vars(value, missing);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 替换文本中的环境变量引用，无法解析的 `${...}` 原样保留
+///
+/// 存在缺失的必填变量时返回 `ConfigError::MissingTemplateVar`，列出全部缺失的变量
+pub fn expand(text: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut missing: Vec<String> = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        match parse_var(&after[..end]) {
+            Some(var) => match var.resolve() {
+                Some(value) => expanded.push_str(&value),
+                None if !missing.contains(&var.name) => missing.push(var.name),
+                None => {}
+            },
+            None => expanded.push_str(&rest[start..start + end + 3]),
+        }
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(expanded)
+    } else {
+        Err(ConfigError::MissingTemplateVar(missing.join(", ")))
+    }
+}
+
+/// 替换配置中所有字符串值里的环境变量引用
+pub(crate) fn expand_value(value: &mut Value) -> Result<()> {
+    match &mut value.kind {
+        ValueKind::String(text) if text.contains("${") => *text = expand(text)?,
+        ValueKind::Table(table) => {
+            for value in table.values_mut() {
+                expand_value(value)?;
+            }
+        }
+        ValueKind::Array(values) => {
+            for value in values {
+                expand_value(value)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfig;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_template_vars() {
+        let vars = template_vars("a=${HOST} b=${PORT:-8080} c=${SECRET:?required} d=${not valid} e=$PLAIN");
+        assert_eq!(vars.len(), 3);
+        assert_eq!(vars[0], TemplateVar { name: "HOST".to_string(), default: None, non_empty: false });
+        assert_eq!(vars[1].default.as_deref(), Some("8080"));
+        assert!(vars[2].non_empty);
+    }
+
+    #[test]
+    fn test_expand() {
+        // 只读取已存在的 PATH，不修改进程环境
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(expand("bin=${PATH}").unwrap(), format!("bin={}", path));
+        assert_eq!(expand("${PATH:-/usr/bin}").unwrap(), path);
+
+        assert_eq!(
+            expand("redis://${RCONFIG_TEST_ABSENT_HOST:-localhost}:6379").unwrap(),
+            "redis://localhost:6379"
+        );
+        assert_eq!(expand("${RCONFIG_TEST_ABSENT_EMPTY:-}").unwrap(), "");
+        assert_eq!(expand("keep ${not valid} and $PLAIN").unwrap(), "keep ${not valid} and $PLAIN");
+
+        match expand("${RCONFIG_TEST_ABSENT_A}/${RCONFIG_TEST_ABSENT_B:?必填}/${RCONFIG_TEST_ABSENT_A}") {
+            Err(ConfigError::MissingTemplateVar(names)) => {
+                assert_eq!(names, "RCONFIG_TEST_ABSENT_A, RCONFIG_TEST_ABSENT_B");
+            }
+            other => panic!("expected MissingTemplateVar, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_values_expanded_on_build() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("app.toml");
+        fs::write(
+            &path,
+            r#"
+[server]
+host = "${RCONFIG_TEST_ABSENT_SERVER_HOST:-127.0.0.1}"
+port = "${RCONFIG_TEST_ABSENT_SERVER_PORT:-9090}"
+
+[extensions.cache]
+uri = "redis://${RCONFIG_TEST_ABSENT_REDIS_HOST:-localhost}:6379"
+path = "${PATH}"
+"#,
+        )?;
+
+        let config = AppConfig::new().add_file(&path).build()?;
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(config.server.port, 9090);
+        assert_eq!(config.extensions["cache"]["uri"], "redis://localhost:6379");
+        assert_eq!(config.extensions["cache"]["path"], std::env::var("PATH")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_missing_vars_reported() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("app.toml");
+        fs::write(
+            &path,
+            r#"
+[server]
+host = "${RCONFIG_TEST_MISSING_HOST}"
+port = "${RCONFIG_TEST_MISSING_PORT:-8080}"
+
+[database]
+password = "${RCONFIG_TEST_MISSING_PASSWORD:?数据库密码}"
+username = "${RCONFIG_TEST_MISSING_HOST}"
+"#,
+        )?;
+
+        match AppConfig::new().add_file(&path).build() {
+            Err(ConfigError::MissingTemplateVar(names)) => {
+                assert_eq!(names, "RCONFIG_TEST_MISSING_HOST, RCONFIG_TEST_MISSING_PASSWORD");
+            }
+            other => panic!("expected MissingTemplateVar, got {:?}", other.map(|_| ())),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_overridden_vars_not_required() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let base = temp.path().join("app.toml");
+        let local = temp.path().join("app_local.toml");
+        fs::write(&base, "[server]\nhost = \"${RCONFIG_TEST_OVERRIDDEN_HOST}\"\nport = 8080\n")?;
+        fs::write(&local, "[server]\nhost = \"127.0.0.1\"\n")?;
+
+        // 后加载的文件覆盖了引用缺失变量的值，合并后的配置不再需要该变量
+        let config = AppConfig::new().add_file(&base).add_file(&local).build()?;
+        assert_eq!(config.server.host, "127.0.0.1");
+
+        match AppConfig::new().add_file(&base).build() {
+            Err(ConfigError::MissingTemplateVar(names)) => assert_eq!(names, "RCONFIG_TEST_OVERRIDDEN_HOST"),
+            other => panic!("expected MissingTemplateVar, got {:?}", other.map(|_| ())),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_commented_vars_ignored() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("app.toml");
+        fs::write(
+            &path,
+            r#"
+# 旧配置: host = "${RCONFIG_TEST_COMMENTED_HOST}"
+[server]
+host = "127.0.0.1" # 也可以写成 "${RCONFIG_TEST_COMMENTED_HOST}"
+port = 8080
+"#,
+        )?;
+
+        let config = AppConfig::new().add_file(&path).build()?;
+        assert_eq!(config.server.host, "127.0.0.1");
+        Ok(())
+    }
+}
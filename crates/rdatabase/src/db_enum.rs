@@ -0,0 +1,190 @@
+//! 整数枚举映射
+//!
+//! 数据库中常用 `TINYINT` 存储状态、类型等枚举值，通过 [`db_int_enum!`](crate::db_int_enum)
+//! 定义的枚举可以直接在 sqlx 中读写，避免到处手写数字与枚举的转换。
+//! 手写 [`IntEnum`] 实现的枚举可以包装为 [`DbEnum`] 使用。
+
+use crate::error::DbError;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+/// 以 `i8` 存储的枚举
+pub trait IntEnum: Sized + Copy {
+    /// 转换为数据库中存储的值
+    fn to_i8(self) -> i8;
+
+    /// 从数据库中的值转换，没有对应成员时返回 [`DbError::UnknownVariant`]
+    fn from_i8(value: i8) -> Result<Self, DbError>;
+}
+
+/// 以整数列存储的枚举字段
+///
+/// 数据库中按 [`IntEnum`] 读写整数，序列化时与内部枚举一致（通常为成员名称），
+/// 实体字段可以声明为 `status: DbEnum<UserStatus>` 并直接用于 `#[derive(FromRow)]`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DbEnum<E>(pub E);
+
+impl<E> DbEnum<E> {
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+impl<E> From<E> for DbEnum<E> {
+    fn from(value: E) -> Self {
+        DbEnum(value)
+    }
+}
+
+impl<E> Deref for DbEnum<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.0
+    }
+}
+
+impl<E: IntEnum, DB: sqlx::Database> sqlx::Type<DB> for DbEnum<E>
+where
+    i8: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <i8 as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <i8 as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, E: IntEnum, DB: sqlx::Database> sqlx::Encode<'q, DB> for DbEnum<E>
+where
+    i8: sqlx::Encode<'q, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <i8 as sqlx::Encode<'q, DB>>::encode_by_ref(&self.0.to_i8(), buf)
+    }
+}
+
+impl<'r, E: IntEnum, DB: sqlx::Database> sqlx::Decode<'r, DB> for DbEnum<E>
+where
+    i8: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <i8 as sqlx::Decode<'r, DB>>::decode(value)?;
+        Ok(DbEnum(E::from_i8(value)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{FromRow, SqlitePool};
+
+    crate::db_int_enum! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+        pub enum UserStatus {
+            Normal = 0 => "normal",
+            Frozen = 1 => "frozen",
+            Banned = -1 => "banned",
+        }
+    }
+
+    #[derive(Debug, FromRow)]
+    struct UserMain {
+        id: i64,
+        status: UserStatus,
+    }
+
+    #[derive(Debug, FromRow, Serialize)]
+    struct UserFlags {
+        id: i64,
+        status: DbEnum<UserStatus>,
+    }
+
+    #[test]
+    fn test_int_conversion() {
+        assert_eq!(UserStatus::Banned.to_i8(), -1);
+        assert_eq!(UserStatus::from_i8(1).unwrap(), UserStatus::Frozen);
+        assert!(matches!(UserStatus::from_i8(9), Err(DbError::UnknownVariant(9))));
+        // 名称和字符串转换由 common::int_enum! 生成
+        assert_eq!(UserStatus::Banned.as_str(), "banned");
+        assert_eq!("frozen".parse::<UserStatus>(), Ok(UserStatus::Frozen));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let pool = SqlitePool::connect("sqlite::memory:").await?;
+        sqlx::query("CREATE TABLE user_main (id INTEGER PRIMARY KEY, status INTEGER NOT NULL)")
+            .execute(&pool)
+            .await?;
+
+        for (id, status) in [(1, UserStatus::Normal), (2, UserStatus::Banned)] {
+            sqlx::query("INSERT INTO user_main (id, status) VALUES (?, ?)")
+                .bind(id)
+                .bind(status)
+                .execute(&pool)
+                .await?;
+        }
+
+        let users: Vec<UserMain> = sqlx::query_as("SELECT id, status FROM user_main ORDER BY id")
+            .fetch_all(&pool)
+            .await?;
+        assert_eq!((users[0].id, users[0].status), (1, UserStatus::Normal));
+        assert_eq!((users[1].id, users[1].status), (2, UserStatus::Banned));
+
+        let raw: i64 = sqlx::query_scalar("SELECT status FROM user_main WHERE id = 2")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(raw, -1);
+
+        // 未声明的值解码失败
+        sqlx::query("INSERT INTO user_main (id, status) VALUES (3, 7)")
+            .execute(&pool)
+            .await?;
+        let result = sqlx::query_as::<_, UserMain>("SELECT id, status FROM user_main WHERE id = 3")
+            .fetch_one(&pool)
+            .await;
+        match result {
+            Err(sqlx::Error::ColumnDecode { source, .. }) => {
+                assert!(matches!(source.downcast_ref::<DbError>(), Some(DbError::UnknownVariant(7))));
+            }
+            other => panic!("expected UnknownVariant, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_db_enum_field() -> Result<(), Box<dyn std::error::Error>> {
+        let pool = SqlitePool::connect("sqlite::memory:").await?;
+        sqlx::query("CREATE TABLE user_main (id INTEGER PRIMARY KEY, status INTEGER NOT NULL)")
+            .execute(&pool)
+            .await?;
+        sqlx::query("INSERT INTO user_main (id, status) VALUES (?, ?)")
+            .bind(1)
+            .bind(DbEnum(UserStatus::Frozen))
+            .execute(&pool)
+            .await?;
+
+        let user: UserFlags = sqlx::query_as("SELECT id, status FROM user_main WHERE id = 1")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(user.status, DbEnum(UserStatus::Frozen));
+        assert_eq!(*user.status, UserStatus::Frozen);
+
+        let raw: i64 = sqlx::query_scalar("SELECT status FROM user_main WHERE id = 1")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(raw, 1);
+
+        // 序列化为成员名称而不是数据库中的整数
+        assert_eq!(serde_json::to_value(&user)?, serde_json::json!({ "id": 1, "status": "Frozen" }));
+
+        Ok(())
+    }
+}
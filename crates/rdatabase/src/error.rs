@@ -33,6 +33,10 @@ pub enum DbError {
     #[error("数据源不存在: {0}")]
     SourceNotFound(String),
 
+    /// 多个数据源加载失败，按数据源名称列出各自的错误
+    #[error("数据源加载失败: {}", format_source_errors(.0))]
+    MultipleSources(Vec<(String, DbError)>),
+
     /// 序列化错误
     #[error("序列化错误: {0}")]
     SerializationError(#[from] serde_json::Error),
@@ -41,11 +45,42 @@ pub enum DbError {
     #[error("IO错误: {0}")]
     IoError(#[from] std::io::Error),
 
+    /// 查询未在指定时间内完成，数据库端的查询已尽量取消
+    #[error("查询超时: 超过 {0:?}")]
+    QueryTimeout(std::time::Duration),
+
+    /// 整数列中的值没有对应的枚举成员
+    #[error("未知的枚举值: {0}")]
+    UnknownVariant(i8),
+
     /// 其他错误
     #[error("其他错误: {0}")]
     Other(String),
 }
 
+impl DbError {
+    /// 是否为可以重试的临时错误
+    ///
+    /// 目前包括死锁和序列化失败，即 SQLSTATE `40001`（MySQL 1213 死锁、PostgreSQL 序列化失败）
+    /// 和 `40P01`（PostgreSQL 死锁）。这类错误发生时事务已被数据库回滚，整体重新执行即可
+    pub fn is_transient(&self) -> bool {
+        match self {
+            DbError::QueryError(sqlx::Error::Database(e)) => {
+                matches!(e.code().as_deref(), Some("40001") | Some("40P01"))
+            }
+            _ => false,
+        }
+    }
+}
+
+fn format_source_errors(errors: &[(String, DbError)]) -> String {
+    errors
+        .iter()
+        .map(|(name, err)| format!("[{}] {}", name, err))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// 数据库操作结果类型
 pub type Result<T> = std::result::Result<T, DbError>;
 
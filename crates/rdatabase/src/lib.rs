@@ -8,7 +8,8 @@
 //! - 支持多种数据库（MySQL, PostgreSQL, SQLite）
 //! - 直接从rconfig配置创建连接池
 //! - 支持多数据源管理
-//! - 便捷的查询和事务API
+//! - 便捷的查询和事务API，支持事务内保存点和指定隔离级别
+//! - 单次查询超时，超时后尽量取消数据库端的查询
 //!
 //! ## 示例
 //!
@@ -40,13 +41,25 @@
 pub mod error;
 pub mod pool;
 pub mod query;
+pub mod db_enum;
+pub mod page;
+pub mod transaction;
+pub mod timeout;
 
 
 mod macros;
 
+// 供 `db_int_enum!` 展开时调用 `common::int_enum!`，使用方不需要单独依赖 common
+#[doc(hidden)]
+pub use common;
+
 // 主要类型重导出
-pub use pool::{DbPool, PoolOptions, DbType};
+pub use pool::{DbPool, PoolOptions, DbType, LoadOptions};
 pub use error::{DbError, Result};
+pub use db_enum::{DbEnum, IntEnum};
+pub use page::{fetch_page, Keyset, Page};
+pub use timeout::{fetch_all_with_timeout, fetch_one_with_timeout, fetch_optional_with_timeout, CancelQuery};
+pub use transaction::{begin_with_isolation, with_transaction, IsolationLevel, Savepoint, TransactionExt};
 
 
 // 方便使用的类型别名
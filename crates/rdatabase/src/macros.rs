@@ -59,3 +59,84 @@ macro_rules! bind_params {
         }
     };
 }
+
+/// 定义以整数列存储的枚举
+///
+/// 以 `i8` 调用 `common::int_enum!` 生成枚举及其名称、字符串转换，再补上
+/// [`IntEnum`](crate::db_enum::IntEnum) 实现以及 sqlx 的 `Type`/`Encode`/`Decode`，
+/// 可直接用于 `#[derive(FromRow)]` 的字段和 `bind` 参数。数据库中出现未声明的值时解码返回
+/// [`DbError::UnknownVariant`](crate::error::DbError::UnknownVariant)。
+///
+/// ```ignore
+/// db_int_enum! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     pub enum UserStatus {
+///         Normal = 0 => "normal",
+///         Banned = 1 => "banned",
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! db_int_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:literal => $str:literal),+ $(,)?
+        }
+    ) => {
+        $crate::common::int_enum! {
+            $(#[$meta])*
+            $vis enum $name: i8 {
+                $($(#[$variant_meta])* $variant = $value => $str),+
+            }
+        }
+
+        impl $crate::db_enum::IntEnum for $name {
+            fn to_i8(self) -> i8 {
+                self.value()
+            }
+
+            fn from_i8(value: i8) -> ::std::result::Result<Self, $crate::error::DbError> {
+                <$name as ::std::convert::TryFrom<i8>>::try_from(value)
+                    .map_err(|_| $crate::error::DbError::UnknownVariant(value))
+            }
+        }
+
+        impl<DB: sqlx::Database> sqlx::Type<DB> for $name
+        where
+            i8: sqlx::Type<DB>,
+        {
+            fn type_info() -> DB::TypeInfo {
+                <i8 as sqlx::Type<DB>>::type_info()
+            }
+
+            fn compatible(ty: &DB::TypeInfo) -> bool {
+                <i8 as sqlx::Type<DB>>::compatible(ty)
+            }
+        }
+
+        impl<'q, DB: sqlx::Database> sqlx::Encode<'q, DB> for $name
+        where
+            i8: sqlx::Encode<'q, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+            ) -> ::std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+                <i8 as sqlx::Encode<'q, DB>>::encode_by_ref(&$crate::db_enum::IntEnum::to_i8(*self), buf)
+            }
+        }
+
+        impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for $name
+        where
+            i8: sqlx::Decode<'r, DB>,
+        {
+            fn decode(
+                value: <DB as sqlx::Database>::ValueRef<'r>,
+            ) -> ::std::result::Result<Self, sqlx::error::BoxDynError> {
+                let value = <i8 as sqlx::Decode<'r, DB>>::decode(value)?;
+                Ok(<$name as $crate::db_enum::IntEnum>::from_i8(value)?)
+            }
+        }
+    };
+}
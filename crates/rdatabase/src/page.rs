@@ -0,0 +1,135 @@
+//! 键集分页
+//!
+//! 按自增主键翻页，`WHERE id > ? ORDER BY id LIMIT ?` 的开销与页码无关，
+//! 替代深分页时越翻越慢的 `LIMIT offset, size`。
+//!
+//! ```ignore
+//! let page: Page<UserMain> = pool.fetch_page("default", "SELECT id, status FROM user_main", None, 20).await?;
+//! let next: Page<UserMain> = pool.fetch_page("default", "SELECT id, status FROM user_main", page.next_cursor, 20).await?;
+//! ```
+
+use serde::Serialize;
+use sqlx::{Database, Encode, Executor, FromRow, IntoArguments, Type};
+
+use crate::error::Result;
+
+/// 可按键集分页的记录
+pub trait Keyset {
+    /// 分页使用的列，需要唯一且递增
+    const KEY_COLUMN: &'static str = "id";
+
+    /// 记录在分页列上的值
+    fn keyset(&self) -> i64;
+}
+
+/// 一页数据
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 查询下一页时传入的游标，没有下一页时为 `None`
+    pub next_cursor: Option<i64>,
+    pub has_next: bool,
+}
+
+/// 查询一页数据
+///
+/// `base_sql` 为不带排序和分页的查询，可以包含自己的 `WHERE` 条件；
+/// `cursor` 为上一页返回的 `next_cursor`，首页传 `None`。
+/// 多查询一条记录判断是否还有下一页
+pub async fn fetch_page<'c, DB, E, T>(executor: E, base_sql: &str, cursor: Option<i64>, limit: u32) -> Result<Page<T>>
+where
+    DB: Database,
+    E: Executor<'c, Database = DB>,
+    T: for<'r> FromRow<'r, DB::Row> + Keyset + Send + Unpin,
+    i64: for<'q> Encode<'q, DB> + Type<DB>,
+    for<'q> <DB as Database>::Arguments<'q>: IntoArguments<'q, DB>,
+{
+    let limit = limit.max(1);
+    let sql = keyset_sql(base_sql, T::KEY_COLUMN, DB::NAME == "PostgreSQL");
+
+    let mut items: Vec<T> = sqlx::query_as(&sql)
+        .bind(cursor.unwrap_or(i64::MIN))
+        .bind(limit as i64 + 1)
+        .fetch_all(executor)
+        .await?;
+
+    let has_next = items.len() > limit as usize;
+    items.truncate(limit as usize);
+    let next_cursor = if has_next { items.last().map(Keyset::keyset) } else { None };
+
+    Ok(Page { items, next_cursor, has_next })
+}
+
+/// 把原查询包装为子查询，再追加键集条件，避免与原查询的 `WHERE` 冲突
+fn keyset_sql(base_sql: &str, key_column: &str, numbered_params: bool) -> String {
+    let (cursor, limit) = if numbered_params { ("$1", "$2") } else { ("?", "?") };
+    format!(
+        "SELECT * FROM ({}) AS keyset_page WHERE {} > {} ORDER BY {} LIMIT {}",
+        base_sql.trim().trim_end_matches(';'),
+        key_column,
+        cursor,
+        key_column,
+        limit
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+
+    #[derive(Debug, FromRow)]
+    struct UserMain {
+        id: i64,
+        name: String,
+    }
+
+    impl Keyset for UserMain {
+        fn keyset(&self) -> i64 {
+            self.id
+        }
+    }
+
+    #[test]
+    fn test_keyset_sql() {
+        assert_eq!(
+            keyset_sql("SELECT id FROM user_main WHERE status = 0;", "id", false),
+            "SELECT * FROM (SELECT id FROM user_main WHERE status = 0) AS keyset_page WHERE id > ? ORDER BY id LIMIT ?"
+        );
+        assert!(keyset_sql("SELECT id FROM t", "id", true).ends_with("WHERE id > $1 ORDER BY id LIMIT $2"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_paging() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let pool = SqlitePool::connect("sqlite::memory:").await?;
+        sqlx::query("CREATE TABLE user_main (id INTEGER PRIMARY KEY, name TEXT NOT NULL, status INTEGER NOT NULL)")
+            .execute(&pool)
+            .await?;
+        for id in 1..=7 {
+            sqlx::query("INSERT INTO user_main (id, name, status) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(format!("user{}", id))
+                .bind(if id == 4 { 1 } else { 0 })
+                .execute(&pool)
+                .await?;
+        }
+
+        // 原查询带过滤条件，id=4 被排除
+        let base_sql = "SELECT id, name FROM user_main WHERE status = 0";
+        let mut cursor = None;
+        let mut pages = Vec::new();
+        loop {
+            let page: Page<UserMain> = fetch_page(&pool, base_sql, cursor, 2).await?;
+            assert!(page.items.iter().all(|u| u.name == format!("user{}", u.id)));
+            pages.push(page.items.iter().map(|u| u.id).collect::<Vec<_>>());
+            assert_eq!(page.has_next, page.next_cursor.is_some());
+            if !page.has_next {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+
+        assert_eq!(pages, vec![vec![1, 2], vec![3, 5], vec![6, 7]]);
+        Ok(())
+    }
+}
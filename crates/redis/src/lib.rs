@@ -1,11 +1,17 @@
 
 mod redis_helper;
+mod redis_keyspace;
 mod redis_locker;
 mod redis_manager;
+mod redis_rate_limiter;
+mod redis_stream;
 
 
-pub use redis_helper::RedisHelper;
+pub use redis_helper::{RedisDbHelper, RedisHelper};
 pub use redis_locker::{RedisLocker, RedisLock, RedisLockGuard};
+pub use redis_rate_limiter::RateGuard;
+pub use redis_stream::StreamEntry;
+pub use redis_keyspace::KeyEvent;
 
 
 
@@ -13,6 +19,7 @@ pub use redis_locker::{RedisLocker, RedisLock, RedisLockGuard};
 mod tests {
     use crate::redis_manager::{init_redis_pool, RedisPoolError};
     use crate::redis_helper::RedisHelper;
+    use crate::redis_keyspace::KeyEvent;
     use futures_util::future::join_all;
     use serde_json::Value;
     use std::io::Write;
@@ -116,6 +123,147 @@ mod tests {
     }
 
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rate_guard_releases_on_drop() {
+        init_redis_pool().await.unwrap();
+
+        let key = "rust:test:rate_guard";
+        let window = Duration::from_secs(10);
+
+        {
+            let _guard = RedisHelper.acquire_rate(key, 1, window).await.unwrap();
+
+            // 名额已满
+            let result = RedisHelper.acquire_rate(key, 1, window).await;
+            assert!(matches!(result, Err(RedisPoolError::RateLimited(_))));
+        }
+
+        // 守卫释放后名额在后台归还
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let guard = RedisHelper.acquire_rate(key, 1, window).await.unwrap();
+        assert!(guard.release().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn redis_with_db_isolated() {
+        init_redis_pool().await.unwrap();
+
+        let key = "rust:test:with_db";
+        let db0 = RedisHelper::with_db(0);
+        let db1 = RedisHelper::with_db(1);
+        db0.del(key).await.unwrap();
+
+        db1.set(key, "db1").await.unwrap();
+        assert!(!db0.exists(key).await.unwrap());
+        let value: Option<String> = db1.get(key).await.unwrap();
+        assert_eq!(value.as_deref(), Some("db1"));
+
+        db1.del(key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn redis_mset_mget() {
+        init_redis_pool().await.unwrap();
+
+        let keys = ["rust:test:mset:1", "rust:test:mset:2", "rust:test:mset:3"];
+        let missing = "rust:test:mset:missing";
+        RedisHelper.del(missing).await.unwrap();
+
+        RedisHelper
+            .mset(&[(keys[0], "v1"), (keys[1], "v2"), (keys[2], "v3")]).await
+            .unwrap();
+
+        let values: Vec<Option<String>> = RedisHelper
+            .mget(&[keys[2], missing, keys[0], keys[1]]).await
+            .unwrap();
+        assert_eq!(values, vec![Some("v3".to_string()), None, Some("v1".to_string()), Some("v2".to_string())]);
+
+        RedisHelper.del_keys(keys.to_vec()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn redis_set_with_wait() {
+        init_redis_pool().await.unwrap();
+
+        let key = "rust:test:set_with_wait";
+        let timeout = Duration::from_millis(100);
+
+        // 单节点没有副本，超时后返回 0 而不是一直阻塞
+        let acked = tokio::time::timeout(
+            Duration::from_secs(2),
+            RedisHelper.set_with_wait(key, "value", 1, timeout),
+        )
+        .await
+        .expect("WAIT should not block forever")
+        .unwrap();
+        assert_eq!(acked, 0);
+
+        let value: Option<String> = RedisHelper.get(key).await.unwrap();
+        assert_eq!(value.as_deref(), Some("value"));
+        RedisHelper.del(key).await.unwrap();
+    }
+
+
+    #[tokio::test]
+    async fn redis_stream_consumer_group() {
+        init_redis_pool().await.unwrap();
+
+        let stream = "rust:test:stream";
+        let group = "rust:test:group";
+        RedisHelper.del(stream).await.unwrap();
+        assert!(RedisHelper.xgroup_create(stream, group, "$").await.unwrap());
+        // 重复创建不报错
+        assert!(!RedisHelper.xgroup_create(stream, group, "$").await.unwrap());
+
+        for i in 0..3 {
+            RedisHelper.xadd(stream, &serde_json::json!({ "seq": i })).await.unwrap();
+        }
+
+        let entries = RedisHelper
+            .xreadgroup::<Value>(group, "consumer-1", &[stream], 10).await
+            .unwrap();
+        let seqs: Vec<i64> = entries.iter().map(|e| e.payload["seq"].as_i64().unwrap()).collect();
+        assert_eq!(seqs, vec![0, 1, 2]);
+        assert_eq!(RedisHelper.xpending_count(stream, group).await.unwrap(), 3);
+
+        // 已投递的消息不会再次投递给同组的其他消费者
+        let again = RedisHelper
+            .xreadgroup::<Value>(group, "consumer-2", &[stream], 10).await
+            .unwrap();
+        assert!(again.is_empty());
+
+        let ids: Vec<&str> = entries.iter().take(2).map(|e| e.id.as_str()).collect();
+        assert_eq!(RedisHelper.xack(stream, group, &ids).await.unwrap(), 2);
+        assert_eq!(RedisHelper.xpending_count(stream, group).await.unwrap(), 1);
+
+        RedisHelper.del(stream).await.unwrap();
+    }
+
+
+    #[tokio::test]
+    #[ignore = "需要开启 notify-keyspace-events 的 Redis"]
+    async fn redis_watch_keyspace() {
+        use futures_util::StreamExt;
+
+        init_redis_pool().await.unwrap();
+
+        let events = RedisHelper.watch_keyspace("rust:test:keyspace:*").await.unwrap();
+        let mut events = Box::pin(events);
+
+        // 不匹配的键不会产生事件
+        RedisHelper.set("rust:test:other", "value").await.unwrap();
+        RedisHelper.set("rust:test:keyspace:session", "value").await.unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(2), events.next())
+            .await
+            .expect("no keyspace event received")
+            .unwrap();
+        assert_eq!(event, KeyEvent { key: "rust:test:keyspace:session".to_string(), event: "set".to_string() });
+
+        RedisHelper.del_keys(vec!["rust:test:other", "rust:test:keyspace:session"]).await.unwrap();
+    }
+
+
     fn setup() -> String {
         // 创建临时文件，返回文件路径
         let file_path = "redis_config.toml".to_string();
@@ -1,4 +1,5 @@
 use crate::redis_locker::RedisLocker;
+use crate::redis_rate_limiter::RateGuard;
 use crate::redis_manager::{get_redis_pool_manager, RedisPoolError};
 use bb8::PooledConnection;
 use bb8_redis::{
@@ -7,15 +8,68 @@ use bb8_redis::{
 };
 use redis::FromRedisValue;
 use redis::ToRedisArgs;
+use std::ops::Deref;
 use std::time::Duration;
 
 /// Redis 命令辅助工具
+///
+/// 使用连接 URI 中的逻辑库，命令由 [`RedisDbHelper`] 提供；
+/// [`RedisHelper::with_db`] 返回在其他库上执行命令的句柄，如缓存和锁分开存放
 pub struct RedisHelper;
 
+/// URI 中的逻辑库
+static DEFAULT_DB: RedisDbHelper = RedisDbHelper { db: None };
+
 impl RedisHelper {
-    pub(crate) async fn get_connection(&self) -> Result<PooledConnection<RedisConnectionManager>, RedisPoolError> {
-        let pool = get_redis_pool_manager()?.get_pool();
-        let conn = pool.get().await?;
+    /// 使用指定逻辑库的辅助工具，命令都在该库上执行
+    ///
+    /// ```ignore
+    /// RedisHelper::with_db(1).set("session:42", "token").await?;
+    /// ```
+    pub const fn with_db(index: u8) -> RedisDbHelper {
+        RedisDbHelper { db: Some(index) }
+    }
+}
+
+impl Deref for RedisHelper {
+    type Target = RedisDbHelper;
+
+    fn deref(&self) -> &Self::Target {
+        &DEFAULT_DB
+    }
+}
+
+// 为 RedisHelper 实现 Clone
+impl Clone for RedisHelper {
+    fn clone(&self) -> Self {
+        RedisHelper
+    }
+}
+
+/// 绑定逻辑库的 Redis 命令句柄，由 [`RedisHelper::with_db`] 创建
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedisDbHelper {
+    db: Option<u8>,
+}
+
+impl From<RedisHelper> for RedisDbHelper {
+    fn from(_: RedisHelper) -> Self {
+        DEFAULT_DB
+    }
+}
+
+impl RedisDbHelper {
+    /// 指定的逻辑库，为空时使用 URI 中的库
+    pub fn db(&self) -> Option<u8> {
+        self.db
+    }
+
+    pub(crate) async fn get_connection(&self) -> Result<PooledConnection<'static, RedisConnectionManager>, RedisPoolError> {
+        let manager = get_redis_pool_manager()?;
+        let conn = match self.db {
+            Some(db) => manager.get_db_pool(db)?.get_owned().await?,
+            None => manager.get_pool().get().await?,
+        };
         Ok(conn)
     }
 
@@ -40,6 +94,30 @@ impl RedisHelper {
         Ok(result)
     }
 
+    /// 设置键值对，并等待写入同步到指定数量的副本
+    ///
+    /// 先执行 `SET`，再在同一连接上执行 `WAIT numreplicas timeout`，返回确认写入的副本数量。
+    /// 返回值小于 `replicas` 表示超时前未同步完成，调用方自行决定是否继续。
+    /// `WAIT` 的超时为 0 时会一直阻塞，这里最少等待 1ms
+    pub async fn set_with_wait<K, V>(&self, key: K, value: V, replicas: usize, timeout: Duration) -> Result<usize, RedisPoolError>
+    where
+        K: ToRedisArgs + Send + Sync,
+        V: ToRedisArgs + Send + Sync,
+    {
+        let timeout_ms = timeout.as_millis().max(1) as u64;
+
+        let mut conn = self.get_connection().await?;
+        let (acked,): (usize,) = redis::pipe()
+            .set(key, value)
+            .ignore()
+            .cmd("WAIT")
+            .arg(replicas)
+            .arg(timeout_ms)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(acked)
+    }
+
     /// 当不存在 key 时 设置键值对
     pub async fn set_nx<K, V>(&self, key: K, value: V) -> Result<bool, RedisPoolError>
     where
@@ -62,6 +140,39 @@ impl RedisHelper {
         Ok(result)
     }
 
+    /// 批量获取键值，结果与 `keys` 顺序一致，不存在的键为 `None`
+    ///
+    /// ```ignore
+    /// let values: Vec<Option<String>> = RedisHelper.mget(&["user:1", "user:2"]).await?;
+    /// ```
+    pub async fn mget<K, V>(&self, keys: &[K]) -> Result<Vec<Option<V>>, RedisPoolError>
+    where
+        K: ToRedisArgs + Send + Sync,
+        V: FromRedisValue + Send + Sync,
+    {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.get_connection().await?;
+        // 单个键时 AsyncCommands::mget 会退化为 GET，这里固定使用 MGET 保证返回数组
+        let result = redis::cmd("MGET").arg(keys).query_async(&mut *conn).await?;
+        Ok(result)
+    }
+
+    /// 批量设置键值对
+    pub async fn mset<K, V>(&self, pairs: &[(K, V)]) -> Result<(), RedisPoolError>
+    where
+        K: ToRedisArgs + Send + Sync,
+        V: ToRedisArgs + Send + Sync,
+    {
+        if pairs.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.get_connection().await?;
+        let _: () = conn.mset(pairs).await?;
+        Ok(())
+    }
+
     /// 删除键
     pub async fn del<K>(&self, key: K) -> Result<bool, RedisPoolError>
     where
@@ -146,18 +257,28 @@ impl RedisHelper {
 
 
 
-    // 获取 RedisLocker 实例
-    pub fn locker(&self) -> RedisLocker {
-        RedisLocker::new(self.clone())
+    /// 占用分布式限流名额
+    ///
+    /// 同一 `key` 在所有实例中最多同时持有 `limit` 个名额，返回的守卫离开作用域时归还名额；
+    /// `window` 为单个名额的最长占用时间，防止进程异常退出后名额无法归还
+    ///
+    /// ```ignore
+    /// let _guard = RedisHelper.acquire_rate("export:report", 5, Duration::from_secs(60)).await?;
+    /// ```
+    pub async fn acquire_rate(&self, key: &str, limit: usize, window: Duration) -> Result<RateGuard, RedisPoolError> {
+        RateGuard::acquire(*self, key, limit, window).await
     }
 
-}
-
-
-// 为 RedisHelper 实现 Clone
-impl Clone for RedisHelper {
-    fn clone(&self) -> Self {
-        RedisHelper
+    /// 获取 RedisLocker 实例
+    ///
+    /// 未指定逻辑库时，锁存放在配置的 `redis.lock_database` 中，避免与缓存数据混在同一个库
+    pub fn locker(&self) -> RedisLocker {
+        let lock_db = get_redis_pool_manager().ok().and_then(|manager| manager.lock_db());
+        match (self.db, lock_db) {
+            (None, Some(db)) => RedisLocker::new(RedisHelper::with_db(db)),
+            _ => RedisLocker::new(*self),
+        }
     }
+
 }
 
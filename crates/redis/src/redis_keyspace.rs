@@ -0,0 +1,92 @@
+use crate::redis_helper::RedisDbHelper;
+use crate::redis_manager::{get_redis_pool_manager, RedisPoolError};
+use futures_util::future::ready;
+use futures_util::{Stream, StreamExt};
+
+/// 键事件通知
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// 发生事件的键
+    pub key: String,
+    /// 事件名称，如 `set`、`del`、`expired`
+    pub event: String,
+}
+
+/// Redis 键空间通知
+///
+/// 服务端默认关闭键空间通知，需要在 redis.conf 或通过 `CONFIG SET` 开启，且必须包含 `E`（键事件）标志，
+/// 例如 `notify-keyspace-events Eg$x` 开启通用命令、字符串命令和过期事件。
+/// 通知基于 Pub/Sub，订阅断开期间发生的事件会丢失，不适合作为唯一的数据来源
+impl RedisDbHelper {
+    /// 订阅当前库的键事件（`__keyevent@<db>__:*`），只返回键名匹配 `pattern` 的事件
+    ///
+    /// `pattern` 使用与 `KEYS` 相同的通配规则，支持 `*` 和 `?`。订阅使用独立连接，不占用连接池，
+    /// 返回的 Stream 被丢弃时连接随之关闭
+    pub async fn watch_keyspace(&self, pattern: &str) -> Result<impl Stream<Item = KeyEvent> + use<>, RedisPoolError> {
+        let client = get_redis_pool_manager()?.get_client();
+        // Pub/Sub 不区分逻辑库，按频道名中的库编号订阅
+        let db = self.db().map(i64::from).unwrap_or(client.get_connection_info().redis.db);
+
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.psubscribe(format!("__keyevent@{}__:*", db)).await?;
+
+        let pattern = pattern.to_string();
+        let events = pubsub.into_on_message().filter_map(move |msg| {
+            // 频道名为 __keyevent@<db>__:<event>，消息体为键名
+            let event = msg.get_channel_name().split_once("__:").map(|(_, event)| event.to_string());
+            let key = msg.get_payload::<String>().ok();
+            let event = match (event, key) {
+                (Some(event), Some(key)) if glob_match(pattern.as_bytes(), key.as_bytes()) => Some(KeyEvent { key, event }),
+                _ => None,
+            };
+            ready(event)
+        });
+        Ok(events)
+    }
+}
+
+/// 按 Redis 的通配规则匹配键名，`*` 匹配任意长度，`?` 匹配单个字符
+fn glob_match(pattern: &[u8], key: &[u8]) -> bool {
+    let (mut p, mut k) = (0, 0);
+    // 最近一次 `*` 的位置及其匹配到的键位置，失配时回溯
+    let mut star: Option<(usize, usize)> = None;
+
+    while k < key.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, k));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == key[k] => {
+                p += 1;
+                k += 1;
+            }
+            _ => match star {
+                Some((star_p, star_k)) => {
+                    p = star_p + 1;
+                    k = star_k + 1;
+                    star = Some((star_p, star_k + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*", b"session:1"));
+        assert!(glob_match(b"session:*", b"session:1"));
+        assert!(glob_match(b"session:?", b"session:1"));
+        assert!(glob_match(b"*:token:*", b"user:token:42"));
+        assert!(!glob_match(b"session:?", b"session:12"));
+        assert!(!glob_match(b"session:*", b"user:1"));
+        assert!(glob_match(b"", b""));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::redis_manager::RedisPoolError;
-use crate::redis_helper::RedisHelper;
+use crate::redis_helper::RedisDbHelper;
 use bb8_redis::redis::{ToRedisArgs};
 use std::fmt::Display;
 use std::sync::Arc;
@@ -11,12 +11,13 @@ use uuid::Uuid;
 
 /// Redis 分布式锁管理器
 pub struct RedisLocker {
-    redis_helper: RedisHelper,
+    redis_helper: RedisDbHelper,
 }
 
 impl RedisLocker {
-    pub fn new(redis_helper: RedisHelper) -> Self {
-        Self { redis_helper }
+    /// 可以传入 [`RedisHelper`](crate::RedisHelper) 或 [`RedisHelper::with_db`](crate::RedisHelper::with_db) 返回的句柄
+    pub fn new(redis_helper: impl Into<RedisDbHelper>) -> Self {
+        Self { redis_helper: redis_helper.into() }
     }
 
     /// 尝试获取分布式锁
@@ -110,7 +111,7 @@ impl RedisLocker {
 
 /// Redis分布式锁实现
 pub struct RedisLock {
-    redis_helper: RedisHelper,
+    redis_helper: RedisDbHelper,
     lock_name: String,
     lock_id: String,
     lease_time: Duration,
@@ -120,7 +121,7 @@ pub struct RedisLock {
 impl RedisLock {
     /// 创建一个新的Redis锁实例
     fn new(
-        redis_helper: RedisHelper,
+        redis_helper: RedisDbHelper,
         lock_name: String,
         lock_id: String,
         lease_time: Duration
@@ -214,7 +215,7 @@ impl RedisLock {
 
 /// 使用Lua脚本更新锁的过期时间（仅当锁被当前实例持有时才更新）
 async fn update_lock_expiry<K, V>(
-    redis_helper: &RedisHelper,
+    redis_helper: &RedisDbHelper,
     key: K,
     expected_value: V,
     ttl: u64
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use bb8::{Pool, RunError};
 use bb8_redis::RedisConnectionManager;
@@ -31,6 +33,12 @@ pub enum RedisPoolError {
     #[error("Custom error: {0}")]
     Custom(String),
 
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+
 }
 
 
@@ -59,6 +67,8 @@ pub struct RedisPoolConfig {
     pub min_idle: u32,
     pub connection_timeout: Duration,
     pub idle_timeout: Duration,
+    /// 分布式锁使用的逻辑库
+    pub lock_db: Option<u8>,
 }
 
 
@@ -66,6 +76,11 @@ pub struct RedisPoolConfig {
 #[derive(Clone)]
 pub struct RedisPoolManager {
     pool: Pool<RedisConnectionManager>,
+    /// 订阅等需要独占连接的场景直接从客户端建立连接，不占用连接池
+    client: redis::Client,
+    /// URI 之外其他逻辑库的连接池，首次使用时创建
+    db_pools: Arc<Mutex<HashMap<u8, Pool<RedisConnectionManager>>>>,
+    config: Arc<RedisPoolConfig>,
 }
 
 impl RedisPoolManager {
@@ -83,6 +98,8 @@ impl RedisPoolManager {
 
         let manager = RedisConnectionManager::new(&*config.uri)
             .map_err(|e| RedisPoolError::InitializationError(e.to_string()))?;
+        let client = redis::Client::open(&*config.uri)
+            .map_err(|e| RedisPoolError::InitializationError(e.to_string()))?;
 
         let pool = Pool::builder()
             .max_size(config.max_size)
@@ -93,7 +110,12 @@ impl RedisPoolManager {
             .await
             .map_err(|e| RedisPoolError::InitializationError(e.to_string()))?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            client,
+            db_pools: Arc::new(Mutex::new(HashMap::new())),
+            config: Arc::new(config),
+        })
     }
 
     /// 获取连接池配置
@@ -101,13 +123,15 @@ impl RedisPoolManager {
         // let config = get_config().map_err(|e| RedisPoolError::InitializationError(e.to_string()))?;
 
         let config = AppConfigBuilder::default().build()?;
-        
+        let redis = config.redis.unwrap();
+
         Ok(RedisPoolConfig {
-            uri: config.redis.unwrap().connection_url().clone(),
+            uri: redis.connection_url(),
             max_size: 10,
             min_idle: 5,
             connection_timeout: Duration::from_secs(10),
             idle_timeout: Duration::from_secs(300),
+            lock_db: redis.lock_database,
         })
     }
 
@@ -116,6 +140,44 @@ impl RedisPoolManager {
         &self.pool
     }
 
+    /// 获取 Redis 客户端
+    pub fn get_client(&self) -> &redis::Client {
+        &self.client
+    }
+
+    /// 获取指定逻辑库的连接池
+    ///
+    /// 与 URI 中的库相同时返回默认连接池，其他库各自使用独立的连接池；
+    /// 不在借出的连接上执行 `SELECT`，避免连接归还后停留在其他库上，被后续使用者误用
+    pub fn get_db_pool(&self, db: u8) -> Result<Pool<RedisConnectionManager>, RedisPoolError> {
+        let mut info = self.client.get_connection_info().clone();
+        if info.redis.db == i64::from(db) {
+            return Ok(self.pool.clone());
+        }
+
+        let mut pools = self.db_pools.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pool) = pools.get(&db) {
+            return Ok(pool.clone());
+        }
+
+        info.redis.db = i64::from(db);
+        let manager = RedisConnectionManager::new(info)
+            .map_err(|e| RedisPoolError::InitializationError(e.to_string()))?;
+        // 按需建立连接，不预留空闲连接
+        let pool = Pool::builder()
+            .max_size(self.config.max_size)
+            .connection_timeout(self.config.connection_timeout)
+            .idle_timeout(Some(self.config.idle_timeout))
+            .build_unchecked(manager);
+        pools.insert(db, pool.clone());
+        Ok(pool)
+    }
+
+    /// 分布式锁使用的逻辑库，对应配置 `redis.lock_database`
+    pub fn lock_db(&self) -> Option<u8> {
+        self.config.lock_db
+    }
+
 }
 
 // 全局静态连接池
@@ -0,0 +1,106 @@
+use crate::redis_helper::RedisDbHelper;
+use crate::redis_manager::RedisPoolError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// 占用一个名额的 Lua 脚本
+///
+/// 名额以有序集合保存，score 为过期时间（毫秒）。先清理已过期的名额，
+/// 未达到上限时写入新名额，保证多实例并发时计数准确
+const ACQUIRE_SCRIPT: &str = r"
+    redis.call('zremrangebyscore', KEYS[1], '-inf', ARGV[1])
+    if redis.call('zcard', KEYS[1]) < tonumber(ARGV[2]) then
+        redis.call('zadd', KEYS[1], ARGV[3], ARGV[4])
+        redis.call('pexpire', KEYS[1], ARGV[5])
+        return 1
+    else
+        return 0
+    end
+";
+
+/// 分布式限流名额守卫
+///
+/// 代表已占用的一个名额，离开作用域时自动归还；
+/// 进程异常退出时名额会在 `window` 到期后自动失效
+pub struct RateGuard {
+    redis_helper: RedisDbHelper,
+    key: String,
+    slot_id: String,
+    released: bool,
+}
+
+impl RateGuard {
+    /// 尝试占用名额，达到上限时返回 `RedisPoolError::RateLimited`
+    pub(crate) async fn acquire(
+        redis_helper: RedisDbHelper,
+        key: &str,
+        limit: usize,
+        window: Duration,
+    ) -> Result<Self, RedisPoolError> {
+        let key = format!("redis_rate:{}", key);
+        let slot_id = Uuid::new_v4().to_string();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let window_ms = window.as_millis().max(1) as u64;
+
+        let mut conn = redis_helper.get_connection().await?;
+        let acquired: i32 = redis::Script::new(ACQUIRE_SCRIPT)
+            .key(&key)
+            .arg(now)
+            .arg(limit)
+            .arg(now + window_ms)
+            .arg(&slot_id)
+            .arg(window_ms)
+            .invoke_async(&mut *conn)
+            .await?;
+
+        if acquired != 1 {
+            return Err(RedisPoolError::RateLimited(key));
+        }
+
+        Ok(Self {
+            redis_helper,
+            key,
+            slot_id,
+            released: false,
+        })
+    }
+
+    /// 手动归还名额
+    pub async fn release(mut self) -> Result<bool, RedisPoolError> {
+        self.released = true;
+        release_slot(&self.redis_helper, &self.key, &self.slot_id).await
+    }
+}
+
+async fn release_slot(redis_helper: &RedisDbHelper, key: &str, slot_id: &str) -> Result<bool, RedisPoolError> {
+    let mut conn = redis_helper.get_connection().await?;
+    let removed: i32 = redis::cmd("ZREM")
+        .arg(key)
+        .arg(slot_id)
+        .query_async(&mut *conn)
+        .await?;
+    Ok(removed == 1)
+}
+
+impl Drop for RateGuard {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+
+        // Drop 中无法等待异步操作，交给当前运行时后台归还；没有运行时则等待名额自然过期
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let redis_helper = self.redis_helper.clone();
+            let key = std::mem::take(&mut self.key);
+            let slot_id = std::mem::take(&mut self.slot_id);
+            handle.spawn(async move {
+                if let Err(e) = release_slot(&redis_helper, &key, &slot_id).await {
+                    tracing::warn!("Failed to release rate slot {}: {}", key, e);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::redis_helper::RedisDbHelper;
+use crate::redis_manager::RedisPoolError;
+use bb8_redis::redis::AsyncCommands;
+use redis::streams::{StreamPendingReply, StreamReadOptions, StreamReadReply};
+use redis::ToRedisArgs;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// 消息体在 Stream 条目中的字段名
+const PAYLOAD_FIELD: &str = "payload";
+
+/// 从 Stream 读取到的一条消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry<T> {
+    /// 所属的 Stream
+    pub stream: String,
+    /// 条目 ID，确认消息时使用
+    pub id: String,
+    pub payload: T,
+}
+
+/// Redis Streams 命令
+///
+/// 消息体序列化为 JSON 写入 `payload` 字段，通过消费组读取的消息在 `xack` 之前保持待确认状态，
+/// 消费者崩溃后可以被重新认领，适合需要持久化和回放的事件
+impl RedisDbHelper {
+    /// 创建消费组，Stream 不存在时一并创建
+    ///
+    /// `start_id` 为 `$` 时只消费之后写入的消息，为 `0` 时从头消费。消费组已存在时返回 `false`
+    pub async fn xgroup_create(&self, stream: &str, group: &str, start_id: &str) -> Result<bool, RedisPoolError> {
+        let mut conn = self.get_connection().await?;
+        let result: redis::RedisResult<()> = conn.xgroup_create_mkstream(stream, group, start_id).await;
+        match result {
+            Ok(()) => Ok(true),
+            Err(e) if e.code() == Some("BUSYGROUP") => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 删除消费组，返回是否存在
+    pub async fn xgroup_destroy(&self, stream: &str, group: &str) -> Result<bool, RedisPoolError> {
+        let mut conn = self.get_connection().await?;
+        let result = conn.xgroup_destroy(stream, group).await?;
+        Ok(result)
+    }
+
+    /// 追加一条消息，返回条目 ID
+    pub async fn xadd<T>(&self, stream: &str, payload: &T) -> Result<String, RedisPoolError>
+    where
+        T: Serialize + Sync,
+    {
+        let payload = serde_json::to_string(payload)?;
+        let mut conn = self.get_connection().await?;
+        let id = conn.xadd(stream, "*", &[(PAYLOAD_FIELD, payload)]).await?;
+        Ok(id)
+    }
+
+    /// 以消费组读取各 Stream 中尚未投递给本组的消息，每个 Stream 最多 `count` 条
+    ///
+    /// 没有新消息时立即返回空列表。读取到的消息处理完成后需要调用 [`xack`](Self::xack)
+    pub async fn xreadgroup<T>(
+        &self,
+        group: &str,
+        consumer: &str,
+        streams: &[&str],
+        count: usize,
+    ) -> Result<Vec<StreamEntry<T>>, RedisPoolError>
+    where
+        T: DeserializeOwned,
+    {
+        let ids = vec![">"; streams.len()];
+        let options = StreamReadOptions::default().group(group, consumer).count(count);
+
+        let mut conn = self.get_connection().await?;
+        let reply: Option<StreamReadReply> = conn.xread_options(streams, &ids, &options).await?;
+
+        let mut entries = Vec::new();
+        for key in reply.map(|reply| reply.keys).unwrap_or_default() {
+            for stream_id in key.ids {
+                let payload: String = stream_id.get(PAYLOAD_FIELD).ok_or_else(|| {
+                    RedisPoolError::Custom(format!("stream entry {} has no {} field", stream_id.id, PAYLOAD_FIELD))
+                })?;
+                entries.push(StreamEntry {
+                    stream: key.key.clone(),
+                    id: stream_id.id,
+                    payload: serde_json::from_str(&payload)?,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 确认消息已处理，返回确认成功的数量
+    pub async fn xack<I>(&self, stream: &str, group: &str, ids: &[I]) -> Result<usize, RedisPoolError>
+    where
+        I: ToRedisArgs + Send + Sync,
+    {
+        let mut conn = self.get_connection().await?;
+        let acked = conn.xack(stream, group, ids).await?;
+        Ok(acked)
+    }
+
+    /// 消费组中已投递但尚未确认的消息数量
+    pub async fn xpending_count(&self, stream: &str, group: &str) -> Result<usize, RedisPoolError> {
+        let mut conn = self.get_connection().await?;
+        let reply: StreamPendingReply = conn.xpending(stream, group).await?;
+        Ok(reply.count())
+    }
+}
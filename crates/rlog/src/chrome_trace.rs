@@ -0,0 +1,269 @@
+//! Chrome Trace 导出
+//!
+//! 记录 span 的进入/退出事件，由后台线程以 Chrome Trace Event JSON 数组格式流式写入文件，
+//! 可直接在 `chrome://tracing` 或 Perfetto 中打开。
+//!
+//! 事件经有界队列交给写入线程，内存占用不随运行时间增长；队列已满时丢弃事件并计数。
+//! [`ChromeTraceGuard`] 释放时（如 [`shutdown`](crate::shutdown)）写入剩余事件并补上数组结尾，
+//! 未释放就退出的进程留下的文件缺少结尾的 `]`，两个查看器都可以正常打开。
+
+use serde_json::{json, Value};
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Instant;
+use tracing::span;
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// 默认的待写入事件队列容量
+pub const DEFAULT_QUEUE_CAPACITY: usize = 65_536;
+
+static NEXT_TID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // Chrome Trace 需要数值型线程ID，为每个线程分配一个递增编号
+    static TID: Cell<u64> = Cell::new(NEXT_TID.fetch_add(1, Ordering::Relaxed));
+}
+
+enum Command {
+    Event(Value),
+    /// 把已入队的事件写入文件，完成后回复写入结果
+    Flush(mpsc::Sender<std::io::Result<()>>),
+    Shutdown,
+}
+
+/// 记录 span 事件的 Layer
+pub struct ChromeTraceLayer {
+    sender: SyncSender<Command>,
+    dropped: Arc<AtomicU64>,
+    start: Instant,
+}
+
+/// 持有写入线程，释放时写入剩余事件并结束文件
+pub struct ChromeTraceGuard {
+    sender: SyncSender<Command>,
+    dropped: Arc<AtomicU64>,
+    path: PathBuf,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ChromeTraceLayer {
+    /// 创建 Layer 以及负责落盘的 Guard，队列容量为 [`DEFAULT_QUEUE_CAPACITY`]
+    pub fn new(path: impl AsRef<Path>) -> Result<(Self, ChromeTraceGuard), String> {
+        Self::with_capacity(path, DEFAULT_QUEUE_CAPACITY)
+    }
+
+    /// 创建 Layer 以及负责落盘的 Guard，最多缓存 `capacity` 条待写入的事件
+    pub fn with_capacity(path: impl AsRef<Path>, capacity: usize) -> Result<(Self, ChromeTraceGuard), String> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create chrome trace directory: {}", e))?;
+        }
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create chrome trace file {}: {}", path.display(), e))?;
+
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+        let worker = std::thread::Builder::new()
+            .name("rlog-chrome-trace".to_string())
+            .spawn(move || write_events(BufWriter::new(file), receiver))
+            .map_err(|e| format!("Failed to spawn chrome trace thread: {}", e))?;
+
+        let dropped = Arc::new(AtomicU64::new(0));
+        let layer = Self {
+            sender: sender.clone(),
+            dropped: dropped.clone(),
+            start: Instant::now(),
+        };
+        let guard = ChromeTraceGuard {
+            sender,
+            dropped,
+            path,
+            worker: Some(worker),
+        };
+        Ok((layer, guard))
+    }
+
+    fn record<S>(&self, phase: &str, id: &span::Id, ctx: &Context<'_, S>)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let Some(metadata) = ctx.metadata(id) else {
+            return;
+        };
+
+        let event = json!({
+            "name": metadata.name(),
+            "cat": metadata.target(),
+            "ph": phase,
+            "ts": self.start.elapsed().as_micros() as u64,
+            "pid": std::process::id(),
+            "tid": TID.with(|tid| tid.get()),
+        });
+
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(Command::Event(event)) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<S> Layer<S> for ChromeTraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.record("B", id, &ctx);
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.record("E", id, &ctx);
+    }
+}
+
+impl ChromeTraceGuard {
+    /// 等待已入队的事件写入文件
+    pub fn flush(&self) -> std::io::Result<()> {
+        let (ack, done) = mpsc::channel();
+        let stopped = || std::io::Error::other("chrome trace writer stopped");
+        self.sender.send(Command::Flush(ack)).map_err(|_| stopped())?;
+        done.recv().map_err(|_| stopped())?
+    }
+
+    /// 因队列已满丢弃的事件数
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for ChromeTraceGuard {
+    fn drop(&mut self) {
+        if self.sender.send(Command::Shutdown).is_ok()
+            && let Some(worker) = self.worker.take()
+        {
+            let _ = worker.join();
+        }
+        let dropped = self.dropped();
+        if dropped > 0 {
+            eprintln!("Chrome trace {} dropped {} events, queue full", self.path.display(), dropped);
+        }
+    }
+}
+
+/// 写入线程，逐条追加到 JSON 数组中，结束时补上数组结尾
+fn write_events(mut writer: BufWriter<File>, receiver: Receiver<Command>) {
+    let mut count: u64 = 0;
+    let mut result = writer.write_all(b"[\n");
+
+    loop {
+        match receiver.recv() {
+            Ok(Command::Event(event)) => {
+                if result.is_ok() {
+                    result = write_event(&mut writer, &event, count);
+                    count += 1;
+                }
+            }
+            Ok(Command::Flush(ack)) => {
+                let flushed = match &result {
+                    Ok(()) => writer.flush(),
+                    Err(e) => Err(std::io::Error::new(e.kind(), e.to_string())),
+                };
+                let _ = ack.send(flushed);
+            }
+            Ok(Command::Shutdown) | Err(_) => break,
+        }
+    }
+
+    let result = result.and_then(|_| writer.write_all(b"\n]\n")).and_then(|_| writer.flush());
+    if let Err(e) = result {
+        // 不能写入 tracing，否则错误日志会再次产生事件
+        eprintln!("rlog: failed to write chrome trace: {}", e);
+    }
+}
+
+fn write_event(writer: &mut BufWriter<File>, event: &Value, index: u64) -> std::io::Result<()> {
+    if index > 0 {
+        writer.write_all(b",\n")?;
+    }
+    serde_json::to_writer(&mut *writer, event)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    #[test]
+    fn test_chrome_trace_output() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("trace").join("trace.json");
+
+        let (layer, guard) = ChromeTraceLayer::new(&path)?;
+        let subscriber = Registry::default().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!("outer");
+            let _outer = outer.enter();
+            let inner = tracing::info_span!("inner");
+            let _inner = inner.enter();
+        });
+        drop(guard);
+
+        let events: Vec<Value> = serde_json::from_slice(&std::fs::read(&path)?)?;
+        let phases: Vec<(String, String)> = events
+            .iter()
+            .map(|e| (e["name"].as_str().unwrap().to_string(), e["ph"].as_str().unwrap().to_string()))
+            .collect();
+
+        assert_eq!(
+            phases,
+            vec![
+                ("outer".to_string(), "B".to_string()),
+                ("inner".to_string(), "B".to_string()),
+                ("inner".to_string(), "E".to_string()),
+                ("outer".to_string(), "E".to_string()),
+            ]
+        );
+        assert!(events.iter().all(|e| e["ts"].is_u64() && e["tid"].is_u64()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_events_streamed_before_shutdown() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("trace.json");
+
+        let (layer, guard) = ChromeTraceLayer::with_capacity(&path, 16)?;
+        let subscriber = Registry::default().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            for _ in 0..4 {
+                let _span = tracing::info_span!("request").entered();
+            }
+        });
+
+        // 未结束的文件缺少数组结尾，已入队的事件在刷新后即可读取
+        guard.flush()?;
+        let content = std::fs::read_to_string(&path)?;
+        assert!(!content.trim_end().ends_with(']'));
+        let events: Vec<Value> = serde_json::from_str(&format!("{}]", content))?;
+        assert_eq!(events.len(), 8);
+        assert_eq!(guard.dropped(), 0);
+
+        drop(guard);
+        let events: Vec<Value> = serde_json::from_slice(&std::fs::read(&path)?)?;
+        assert_eq!(events.len(), 8);
+        Ok(())
+    }
+}
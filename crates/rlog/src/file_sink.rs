@@ -0,0 +1,145 @@
+//! 按级别/目标分流的文件输出
+//!
+//! 每个 [`FileSink`] 生成一个带过滤的 JSON 文件层，例如：
+//!
+//! ```toml
+//! [[log.files]]
+//! path = "logs/app.log"
+//!
+//! [[log.files]]
+//! path = "logs/error.log"
+//! min_level = "error"
+//!
+//! [[log.files]]
+//! path = "logs/audit.log"
+//! target_filter = "audit"
+//! ```
+
+use crate::flush::{non_blocking, FlushGuard};
+use crate::redact::{Redacting, Redactor};
+use crate::{CustomTime, FileSink};
+use std::path::Path;
+use std::str::FromStr;
+use tracing::Subscriber;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{filter_fn, LevelFilter};
+use tracing_subscriber::fmt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+type BoxedLayer<S> = Box<dyn Layer<S> + Send + Sync + 'static>;
+
+/// 为单个文件输出创建日志层，返回的 guard 需要保持存活直到进程退出，也可以用于按需刷新
+///
+/// 传入 `redactor` 时写入前先脱敏
+pub fn file_sink_layer<S>(sink: &FileSink, redactor: Option<&Redactor>) -> Result<(BoxedLayer<S>, FlushGuard), String>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let min_level = LevelFilter::from_str(&sink.min_level)
+        .map_err(|_| format!("Invalid log level for {}: {}", sink.path.display(), sink.min_level))?;
+
+    let dir = sink.path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or_else(|| Path::new("."));
+    let file_name = sink.path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid log file path: {}", sink.path.display()))?;
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    // 不轮转，直接写入配置的文件名
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::NEVER)
+        .filename_prefix(file_name)
+        .build(dir)
+        .map_err(|e| format!("Failed to create log file appender: {}", e))?;
+    let (writer, guard) = non_blocking(appender);
+
+    let target_filter = sink.target_filter.clone();
+    let filter = filter_fn(move |metadata| {
+        *metadata.level() <= min_level
+            && target_filter.as_deref().is_none_or(|target| metadata.target().starts_with(target))
+    });
+
+    let format = fmt::format().json().with_timer(CustomTime).with_current_span(true);
+    let layer = fmt::layer()
+        .json()
+        .with_writer(writer)
+        .with_ansi(false)
+        .event_format(Redacting::new(format, redactor.cloned()))
+        .with_filter(filter)
+        .boxed();
+
+    Ok((layer, guard))
+}
+
+/// 为所有文件输出创建日志层
+pub fn file_sink_layers<S>(
+    sinks: &[FileSink],
+    redactor: Option<&Redactor>,
+) -> Result<(Vec<BoxedLayer<S>>, Vec<FlushGuard>), String>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let mut layers = Vec::with_capacity(sinks.len());
+    let mut guards = Vec::with_capacity(sinks.len());
+    for sink in sinks {
+        let (layer, guard) = file_sink_layer(sink, redactor)?;
+        layers.push(layer);
+        guards.push(guard);
+    }
+    Ok((layers, guards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    #[test]
+    fn test_events_routed_by_level_and_target() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let app = temp.path().join("app.log");
+        let error = temp.path().join("error.log");
+        let audit = temp.path().join("audit.log");
+
+        let sinks = vec![
+            FileSink::new(&app),
+            FileSink::new(&error).with_min_level("error"),
+            FileSink::new(&audit).with_target_filter("audit"),
+        ];
+        let (layers, guards) = file_sink_layers(&sinks, None)?;
+        let subscriber = Registry::default().with(layers);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!("payment failed");
+            tracing::info!("payment created");
+            tracing::info!(target: "audit", "refund approved");
+        });
+        // 释放 guard 时写出缓冲的日志
+        drop(guards);
+
+        let app = std::fs::read_to_string(&app)?;
+        let error = std::fs::read_to_string(&error)?;
+        let audit = std::fs::read_to_string(&audit)?;
+
+        assert!(app.contains("payment failed"));
+        assert!(app.contains("payment created"));
+        assert!(app.contains("refund approved"));
+
+        assert!(error.contains("payment failed"));
+        assert!(!error.contains("payment created"));
+
+        assert!(audit.contains("refund approved"));
+        assert!(!audit.contains("payment"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_sink_level() {
+        let sink = FileSink::new("logs/app.log").with_min_level("verbose");
+        assert!(file_sink_layer::<Registry>(&sink, None).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! 按需刷新非阻塞文件输出
+//!
+//! 非阻塞写入的日志由后台线程落盘，进程异常退出时队列中的日志会丢失。
+//! 刷新时向队列发送一个带序号的标记，后台线程处理到标记说明之前的日志都已写入文件，
+//! 不需要像释放 [`WorkerGuard`] 那样结束后台线程。
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::Duration;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+
+/// 刷新标记前缀，后面紧跟 8 字节小端序号，不会写入文件
+const FLUSH_MARKER: &[u8] = b"\0rlog-flush\0";
+
+#[derive(Default)]
+struct FlushSignal {
+    next_id: AtomicU64,
+    /// 后台线程已处理的最大标记序号
+    completed: Mutex<u64>,
+    cond: Condvar,
+}
+
+impl FlushSignal {
+    fn complete(&self, id: u64) {
+        let mut completed = self.completed.lock().unwrap_or_else(PoisonError::into_inner);
+        *completed = (*completed).max(id);
+        self.cond.notify_all();
+    }
+}
+
+/// 包装实际的文件写入器，识别刷新标记
+struct MarkerWriter<W> {
+    inner: W,
+    signal: Arc<FlushSignal>,
+}
+
+impl<W: Write> Write for MarkerWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(id) = buf.strip_prefix(FLUSH_MARKER).and_then(|id| <[u8; 8]>::try_from(id).ok()) {
+            self.inner.flush()?;
+            self.signal.complete(u64::from_le_bytes(id));
+            return Ok(buf.len());
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 非阻塞输出的刷新句柄，可以在不持有 guard 的地方使用
+#[derive(Clone)]
+pub struct FlushHandle {
+    writer: NonBlocking,
+    signal: Arc<FlushSignal>,
+}
+
+impl FlushHandle {
+    /// 等待调用前写入的日志落盘，超时返回 `false`
+    ///
+    /// 有损模式下队列已满时标记会被丢弃，此时只能等到超时
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let id = self.signal.next_id.fetch_add(1, Ordering::AcqRel) + 1;
+        let mut marker = FLUSH_MARKER.to_vec();
+        marker.extend_from_slice(&id.to_le_bytes());
+        if self.writer.clone().write_all(&marker).is_err() {
+            return false;
+        }
+
+        let completed = self.signal.completed.lock().unwrap_or_else(PoisonError::into_inner);
+        let (completed, _) = self
+            .signal
+            .cond
+            .wait_timeout_while(completed, timeout, |completed| *completed < id)
+            .unwrap_or_else(PoisonError::into_inner);
+        *completed >= id
+    }
+}
+
+/// 非阻塞输出的 guard，释放时写出剩余日志并结束后台线程
+pub struct FlushGuard {
+    handle: FlushHandle,
+    _guard: WorkerGuard,
+}
+
+impl FlushGuard {
+    /// 等待调用前写入的日志落盘，超时返回 `false`
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.handle.flush(timeout)
+    }
+
+    pub fn handle(&self) -> FlushHandle {
+        self.handle.clone()
+    }
+}
+
+/// 创建支持按需刷新的非阻塞写入器
+pub(crate) fn non_blocking<W: Write + Send + 'static>(writer: W) -> (NonBlocking, FlushGuard) {
+    let signal = Arc::new(FlushSignal::default());
+    let (writer, guard) = NonBlocking::new(MarkerWriter { inner: writer, signal: signal.clone() });
+    let handle = FlushHandle { writer: writer.clone(), signal };
+    (writer, FlushGuard { handle, _guard: guard })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_flush_without_dropping_guard() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = tempdir()?;
+        let path = temp.path().join("app.log");
+        let (mut writer, guard) = non_blocking(std::fs::File::create(&path)?);
+
+        writer.write_all(b"before flush\n")?;
+        assert!(guard.flush(Duration::from_secs(5)));
+
+        // guard 仍然存活，内容已经在磁盘上，标记不会写入文件
+        assert_eq!(std::fs::read_to_string(&path)?, "before flush\n");
+
+        writer.write_all(b"after flush\n")?;
+        assert!(guard.handle().flush(Duration::from_secs(5)));
+        assert_eq!(std::fs::read_to_string(&path)?, "before flush\nafter flush\n");
+        Ok(())
+    }
+}
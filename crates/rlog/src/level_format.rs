@@ -0,0 +1,113 @@
+//! 按级别输出详细字段
+//!
+//! 配置 `verbose_from_level` 后，只有不低于该级别的日志附带源码位置和线程信息，
+//! 例如设为 `warn` 时 INFO 日志保持紧凑，WARN/ERROR 日志带上文件、行号和线程，便于排查问题。
+
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::{Format, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 按事件级别在紧凑格式和详细格式之间切换的格式化器
+#[derive(Debug, Clone)]
+pub struct LevelFormat<F> {
+    compact: F,
+    verbose: F,
+    verbose_from: Option<Level>,
+}
+
+impl<L: Clone, T: Clone> LevelFormat<Format<L, T>> {
+    /// `verbose_from` 为空时所有日志都使用 `format` 原有的设置
+    pub fn new(format: Format<L, T>, verbose_from: Option<Level>) -> Self {
+        match verbose_from {
+            Some(_) => Self {
+                compact: format.clone().with_file(false).with_line_number(false).with_thread_ids(false).with_thread_names(false),
+                verbose: format.with_file(true).with_line_number(true).with_thread_ids(true).with_thread_names(true),
+                verbose_from,
+            },
+            None => Self {
+                compact: format.clone(),
+                verbose: format,
+                verbose_from,
+            },
+        }
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for LevelFormat<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        // tracing 中越严重的级别越小
+        let verbose = self.verbose_from.is_some_and(|level| *event.metadata().level() <= level);
+        if verbose {
+            self.verbose.format_event(ctx, writer, event)
+        } else {
+            self.compact.format_event(ctx, writer, event)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capture(verbose_from: Option<Level>) -> Vec<String> {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let format = fmt::format().compact().without_time().with_ansi(false);
+        let subscriber = fmt()
+            .with_writer(move || writer.clone())
+            .event_format(LevelFormat::new(format, verbose_from))
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("order created");
+            tracing::warn!("order payment slow");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        output.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_verbose_from_warn() {
+        let lines = capture(Some(Level::WARN));
+        assert_eq!(lines.len(), 2);
+
+        assert!(lines[0].contains("order created"));
+        assert!(!lines[0].contains("level_format.rs"));
+        assert!(!lines[0].contains("ThreadId"));
+
+        assert!(lines[1].contains("order payment slow"));
+        assert!(lines[1].contains("src/level_format.rs:"));
+        assert!(lines[1].contains("ThreadId"));
+    }
+
+    #[test]
+    fn test_uniform_by_default() {
+        let lines = capture(None);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| !line.contains("level_format.rs") && !line.contains("ThreadId")));
+    }
+}
@@ -1,21 +1,54 @@
 //! rlog - 基于 tracing 的日志组件
 
+mod chrome_trace;
+mod file_sink;
+mod flush;
+mod level_format;
+mod redact;
+mod ring_buffer;
+mod scope;
+mod span_fields;
+mod span_metrics;
+#[cfg(feature = "ship")]
+mod ship;
+
+pub use chrome_trace::{ChromeTraceGuard, ChromeTraceLayer};
+pub use file_sink::{file_sink_layer, file_sink_layers};
+pub use flush::{FlushGuard, FlushHandle};
+pub use level_format::LevelFormat;
+pub use redact::{Redacting, Redactor};
+pub use ring_buffer::{LogRecord, RingBuffer, RingBufferLayer};
+pub use scope::{request_span, scope, ScopeFields, SpanGuard};
+pub use span_fields::{record, FieldValue};
+pub use span_metrics::SpanMetricsLayer;
+#[cfg(feature = "admin-http")]
+pub use ring_buffer::{logs_handler, LogsQuery};
+#[cfg(feature = "ship")]
+pub use ship::{ShipGuard, ShipHandle, ShipLayer};
+
+/// 未启用 `ship` 特性时没有推送线程
+#[cfg(not(feature = "ship"))]
+type ShipGuard = ();
+
 use once_cell::sync::OnceCell;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use std::time::Duration;
 use tracing_log::LogTracer;
 use tracing_subscriber::{fmt::{self}, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry};
 
 // 使用预设的 LogConfig
-pub use rconfig::presets::logging::LogConfig;
+pub use rconfig::presets::logging::{FileSink, LogConfig, RedactionRule, ShipConfig, ShipKind};
 
 // 全局日志状态
 struct LogState {
     config: LogConfig,
-    _guards: Vec<WorkerGuard>, // 保持 guards 存活，确保日志正确写入
+    guards: Vec<FlushGuard>, // 保持 guards 存活，确保日志正确写入，也用于按需刷新
+    chrome_guard: Option<ChromeTraceGuard>, // 释放时写入剩余事件并结束 Chrome Trace 文件
+    ring_buffer: Option<RingBuffer>,
+    ship_guard: Option<ShipGuard>, // 释放时推送剩余日志
 }
 
 static LOGGER: OnceCell<Arc<Mutex<LogState>>> = OnceCell::new();
@@ -49,8 +82,8 @@ pub fn init(config: &LogConfig) -> Result<(), String> {
         return Err("Logger already initialized".to_string());
     }
 
-    // 将 log crate 的日志转发到 tracing
-    if let Err(e) = LogTracer::init() {
+    // 将 log crate 的日志转发到 tracing，超过上限级别的日志不转发
+    if let Err(e) = LogTracer::builder().with_max_level(config.parse_log_crate_max_level()).init() {
         return Err(format!("Failed to initialize LogTracer: {}", e));
     }
 
@@ -77,26 +110,55 @@ pub fn init(config: &LogConfig) -> Result<(), String> {
     
     // 构建订阅器
     let registry = Registry::default().with(filter);
+    let redactor = Redactor::from_rules(&config.redactions)?;
 
     // 自定义时间格式化器
     let timer = CustomTime;
-    
-    let console_layer = fmt::layer()
+
+    let console_format = fmt::format()
         .compact()
-        .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
-        .with_writer(std::io::stdout)
         .with_timer(timer)
-        .with_ansi(config.use_ansi_colors)
         .with_file(config.show_source_location)
         .with_line_number(config.show_source_location)
         .with_target(config.show_target)
         .with_thread_ids(config.show_thread_id);
+    let console_layer = fmt::layer()
+        .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+        .with_writer(std::io::stdout)
+        .with_ansi(config.use_ansi_colors)
+        .event_format(Redacting::new(
+            LevelFormat::new(console_format, verbose_from_level(config)?),
+            redactor.clone(),
+        ));
     
 
+    // Chrome Trace 导出（可选）
+    let (chrome_layer, chrome_guard) = match &config.chrome_trace_path {
+        Some(path) => {
+            let (layer, guard) = ChromeTraceLayer::new(path)?;
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
     // 设置全局订阅器
     // registry.with(console_layer).init();
  
-    let subscriber = registry.with(console_layer);
+    // 按级别/目标分流的文件输出
+    let (sink_layers, guards) = file_sink_layers(&config.files, redactor.as_ref())?;
+    // 空的层列表会给出 OFF 级别提示，压低全局最大级别，没有文件输出时不挂载
+    let sink_layers = (!sink_layers.is_empty()).then_some(sink_layers);
+
+    let (ring_layer, ring_buffer) = ring_buffer_layer(config, redactor.clone());
+    let (ship_layer, ship_guard) = ship_layer(config, redactor.clone())?;
+
+    let subscriber = registry
+        .with(console_layer)
+        .with(sink_layers)
+        .with(chrome_layer)
+        .with(ring_layer)
+        .with(ship_layer)
+        .with(config.span_metrics.then(SpanMetricsLayer::new));
     if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
         return Err(format!("Failed to set global subscriber: {}", e));
     }
@@ -105,7 +167,10 @@ pub fn init(config: &LogConfig) -> Result<(), String> {
 
     let log_state = LogState {
         config: config.clone(),
-        _guards: Vec::new(),
+        guards,
+        chrome_guard,
+        ring_buffer,
+        ship_guard,
     };
 
     LOGGER.set(Arc::new(Mutex::new(log_state)))
@@ -122,8 +187,8 @@ pub fn init_file_log(config: LogConfig) -> Result<(), String> {
         return Err("Logger already initialized".to_string());
     }
 
-    // 将 log crate 的日志转发到 tracing
-    if let Err(e) = LogTracer::init() {
+    // 将 log crate 的日志转发到 tracing，超过上限级别的日志不转发
+    if let Err(e) = LogTracer::builder().with_max_level(config.parse_log_crate_max_level()).init() {
         return Err(format!("Failed to initialize LogTracer: {}", e));
     }
 
@@ -154,79 +219,161 @@ pub fn init_file_log(config: LogConfig) -> Result<(), String> {
 
     // 构建订阅器
     let mut registry = Registry::default().with(filter);
+    let redactor = Redactor::from_rules(&config.redactions)?;
 
     // 自定义时间格式化器
     let timer = CustomTime;
     
 
+    if config.file_path.is_none() && config.files.is_empty() {
+        return Err("File path not specified for file logging".to_string());
+    }
+
     // 同时配置文件输出
-    if let Some(file_path) = &config.file_path {
-        let dir = file_path.parent().unwrap_or_else(|| Path::new("."));
-        let file_name = file_path.file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| "app.log".to_string());
-
-        // 确保目录存在
-        if !dir.exists() {
-            std::fs::create_dir_all(dir)
-                .map_err(|e| format!("Failed to create log directory: {}", e))?;
+    let file_layer = match &config.file_path {
+        Some(file_path) => {
+            let dir = file_path.parent().unwrap_or_else(|| Path::new("."));
+            let file_name = file_path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "app.log".to_string());
+
+            // 确保目录存在
+            if !dir.exists() {
+                std::fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create log directory: {}", e))?;
+            }
+
+            // 解析轮转策略
+            let rotation = match config.rotation.to_lowercase().as_str() {
+                "hourly" => Rotation::HOURLY,
+                "minutely" => Rotation::MINUTELY,
+                "daily" => Rotation::DAILY,
+                _ => Rotation::DAILY, // 默认每日轮转
+            };
+
+            // 创建文件附加器
+            let file_appender = match RollingFileAppender::builder()
+                .rotation(rotation)
+                .filename_prefix(file_name)
+                .max_log_files(config.max_files as usize)
+                .build(dir) {
+                Ok(appender) => appender,
+                Err(e) => return Err(format!("Failed to create log file appender: {}", e)),
+            };
+
+            // 非阻塞写入
+            let (non_blocking, guard) = flush::non_blocking(file_appender);
+            guards.push(guard);
+
+            // 创建文件层
+            let file_format = fmt::format()
+                .json()
+                .with_timer(timer)
+                .with_current_span(true)
+                .with_file(config.show_source_location)
+                .with_line_number(config.show_source_location)
+                .with_target(config.show_target)
+                .with_thread_ids(config.show_thread_id);
+            Some(fmt::layer()
+                .json()
+                .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+                .with_writer(non_blocking)
+                .with_ansi(config.use_ansi_colors)
+                .event_format(Redacting::new(
+                    LevelFormat::new(file_format, verbose_from_level(&config)?),
+                    redactor.clone(),
+                )))
         }
+        None => None,
+    };
 
-        // 解析轮转策略
-        let rotation = match config.rotation.to_lowercase().as_str() {
-            "hourly" => Rotation::HOURLY,
-            "minutely" => Rotation::MINUTELY,
-            "daily" => Rotation::DAILY,
-            _ => Rotation::DAILY, // 默认每日轮转
-        };
+    // 按级别/目标分流的文件输出
+    let (sink_layers, sink_guards) = file_sink_layers(&config.files, redactor.as_ref())?;
+    let sink_layers = (!sink_layers.is_empty()).then_some(sink_layers);
+    guards.extend(sink_guards);
 
-        // 创建文件附加器
-        let file_appender = match RollingFileAppender::builder()
-            .rotation(rotation)
-            .filename_prefix(file_name)
-            .max_log_files(config.max_files as usize)
-            .build(dir) {
-            Ok(appender) => appender,
-            Err(e) => return Err(format!("Failed to create log file appender: {}", e)),
-        };
+    // Chrome Trace 导出（可选）
+    let (chrome_layer, chrome_guard) = match &config.chrome_trace_path {
+        Some(path) => {
+            let (layer, guard) = ChromeTraceLayer::new(path)?;
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
 
-        // 非阻塞写入
-        let (non_blocking, guard) = NonBlocking::new(file_appender);
-        guards.push(guard);
-
-        // 创建文件层
-        let file_layer = fmt::layer()
-            .json()
-            .with_timer(timer)
-            .with_current_span(true)
-            .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
-            .with_writer(non_blocking)
-            .with_ansi(config.use_ansi_colors)
-            .with_file(config.show_source_location)
-            .with_line_number(config.show_source_location)
-            .with_target(config.show_target)
-            .with_thread_ids(config.show_thread_id);
-
-        // 设置全局订阅器
-        registry.with(file_layer).init();
-
-        // 保存配置和 guards
-        let log_state = LogState {
-            config,
-            _guards: guards,
-        };
+    let (ring_layer, ring_buffer) = ring_buffer_layer(&config, redactor.clone());
+    let (ship_layer, ship_guard) = ship_layer(&config, redactor.clone())?;
 
-        LOGGER.set(Arc::new(Mutex::new(log_state)))
-            .map_err(|_| "Failed to set global logger state".to_string())?;
-        
-    } else {
-        return Err("File path not specified for file logging".to_string());
-    }
+    // 设置全局订阅器
+    registry
+        .with(file_layer)
+        .with(sink_layers)
+        .with(chrome_layer)
+        .with(ring_layer)
+        .with(ship_layer)
+        .with(config.span_metrics.then(SpanMetricsLayer::new))
+        .init();
+
+    // 保存配置和 guards
+    let log_state = LogState {
+        config,
+        guards,
+        chrome_guard,
+        ring_buffer,
+        ship_guard,
+    };
+
+    LOGGER.set(Arc::new(Mutex::new(log_state)))
+        .map_err(|_| "Failed to set global logger state".to_string())?;
 
     Ok(())
 }
 
 
+/// 解析输出详细字段的最低级别
+fn verbose_from_level(config: &LogConfig) -> Result<Option<Level>, String> {
+    config.verbose_from_level
+        .as_deref()
+        .map(|level| Level::from_str(level).map_err(|_| format!("Invalid verbose_from_level: {}", level)))
+        .transpose()
+}
+
+/// 按配置创建内存环形缓冲层（可选）
+fn ring_buffer_layer(config: &LogConfig, redactor: Option<Redactor>) -> (Option<RingBufferLayer>, Option<RingBuffer>) {
+    match config.ring_buffer_capacity {
+        Some(capacity) => {
+            let (layer, buffer) = RingBufferLayer::new(capacity);
+            (Some(layer.with_redactor(redactor)), Some(buffer))
+        }
+        None => (None, None),
+    }
+}
+
+/// 按配置创建日志推送层（可选）
+#[cfg(feature = "ship")]
+fn ship_layer(config: &LogConfig, redactor: Option<Redactor>) -> Result<(Option<ShipLayer>, Option<ShipGuard>), String> {
+    match &config.ship {
+        Some(ship) => {
+            let (layer, guard) = ShipLayer::new(ship.clone())?;
+            Ok((Some(layer.with_redactor(redactor)), Some(guard)))
+        }
+        None => Ok((None, None)),
+    }
+}
+
+#[cfg(not(feature = "ship"))]
+fn ship_layer(config: &LogConfig, _redactor: Option<Redactor>) -> Result<(Option<tracing_subscriber::layer::Identity>, Option<ShipGuard>), String> {
+    if config.ship.is_some() {
+        return Err("Log shipping requires the `ship` feature".to_string());
+    }
+    Ok((None, None))
+}
+
+/// 获取全局日志的内存环形缓冲，未配置 `ring_buffer_capacity` 时返回 `None`
+pub fn ring_buffer() -> Option<RingBuffer> {
+    LOGGER.get()?.lock().ok()?.ring_buffer.clone()
+}
+
 fn create_console_layer<S>() -> Box<dyn Layer<S> + Send + Sync + 'static>
 where
     S: Subscriber,
@@ -355,6 +502,43 @@ pub fn get_config() -> Option<LogConfig> {
     })
 }
 
+/// 关闭日志系统
+///
+/// 写入剩余的 Chrome Trace 事件并结束文件、推送剩余日志（如已启用），应在进程退出前调用
+pub fn shutdown() {
+    if let Some(state) = LOGGER.get() {
+        let guards = state.lock().ok().map(|mut state| (state.chrome_guard.take(), state.ship_guard.take()));
+        drop(guards);
+    }
+}
+
+/// 等待日志落盘的最长时间
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 等待所有非阻塞文件输出把已写入的日志落盘，启用日志推送时同时等待已入队的日志推送完成
+///
+/// 不需要释放 guard，适合在 panic hook、优雅停机或业务检查点调用。
+/// 日志系统未初始化或等待超时时返回错误
+pub fn flush() -> Result<(), String> {
+    let (handles, ship) = {
+        let state = LOGGER.get().ok_or("Logger not initialized")?;
+        let state = state.lock().map_err(|_| "Logger state lock poisoned")?;
+        let handles: Vec<FlushHandle> = state.guards.iter().map(FlushGuard::handle).collect();
+        #[cfg(feature = "ship")]
+        let ship = state.ship_guard.as_ref().map(ShipGuard::handle);
+        #[cfg(not(feature = "ship"))]
+        let ship: Option<FlushHandle> = None;
+        (handles, ship)
+    };
+
+    // 释放锁后再等待，避免阻塞其他日志接口
+    if handles.iter().all(|handle| handle.flush(FLUSH_TIMEOUT)) && ship.is_none_or(|ship| ship.flush(FLUSH_TIMEOUT)) {
+        Ok(())
+    } else {
+        Err(format!("Timed out flushing logs after {:?}", FLUSH_TIMEOUT))
+    }
+}
+
 /// 重新配置日志系统
 ///
 /// 注意：此方法不会改变已设置的格式和输出目标，只能调整过滤级别
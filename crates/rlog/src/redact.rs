@@ -0,0 +1,148 @@
+//! 日志脱敏
+//!
+//! 配置 `redactions` 后，每条日志先完整格式化（消息、字段和 span 上下文），再按顺序应用脱敏规则，
+//! 最后写出，避免令牌、卡号等敏感信息落盘：
+//!
+//! ```toml
+//! [[log.redactions]]
+//! pattern = "Bearer [A-Za-z0-9._~+/-]+=*"
+//! replacement = "Bearer ***"
+//! ```
+//!
+//! 未配置规则时直接写出，没有额外开销；启用后控制台输出不再带颜色，避免颜色控制字符打断匹配。
+//! 内存环形缓冲和日志推送使用同一组规则，写入缓冲或入队前脱敏。
+
+use crate::RedactionRule;
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::Arc;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 编译后的脱敏规则，克隆后共享
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Arc<Vec<(Regex, String)>>,
+}
+
+impl Redactor {
+    /// 编译规则，任一正则无效时返回错误
+    pub fn new(rules: &[RedactionRule]) -> Result<Self, String> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|regex| (regex, rule.replacement.clone()))
+                    .map_err(|e| format!("Invalid redaction pattern '{}': {}", rule.pattern, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { rules: Arc::new(rules) })
+    }
+
+    /// 按配置创建，没有规则时返回 `None`
+    pub fn from_rules(rules: &[RedactionRule]) -> Result<Option<Self>, String> {
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        Self::new(rules).map(Some)
+    }
+
+    /// 依次应用所有规则
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (regex, replacement) in self.rules.iter() {
+            if let Cow::Owned(replaced) = regex.replace_all(&text, replacement.as_str()) {
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+}
+
+/// 格式化后脱敏再写出的格式化器，`redactor` 为空时直接使用内部格式化器
+#[derive(Debug, Clone)]
+pub struct Redacting<F> {
+    inner: F,
+    redactor: Option<Redactor>,
+}
+
+impl<F> Redacting<F> {
+    pub fn new(inner: F, redactor: Option<Redactor>) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for Redacting<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let Some(redactor) = &self.redactor else {
+            return self.inner.format_event(ctx, writer, event);
+        };
+
+        let mut line = String::new();
+        self.inner.format_event(ctx, Writer::new(&mut line), event)?;
+        writer.write_str(&redactor.redact(&line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::Mutex;
+    use tracing_subscriber::fmt;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_token_masked_in_output() {
+        let rules = [
+            RedactionRule::new("Bearer [A-Za-z0-9._~+/-]+=*").with_replacement("Bearer ***"),
+            RedactionRule::new(r"\b(\d{6})\d{6,9}(\d{4})\b").with_replacement("${1}******${2}"),
+        ];
+        let redactor = Redactor::from_rules(&rules).unwrap();
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let format = fmt::format().compact().without_time().with_ansi(false);
+        let subscriber = fmt()
+            .with_writer(move || writer.clone())
+            .event_format(Redacting::new(format, redactor))
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("calling channel with Bearer eyJhbGciOiJIUzI1NiJ9.fake.token");
+            tracing::info!(card = "6222021234567890123", "card bound");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("calling channel with Bearer ***"), "{}", output);
+        assert!(!output.contains("eyJhbGciOiJIUzI1NiJ9"), "{}", output);
+        assert!(output.contains("622202******0123"), "{}", output);
+        assert!(!output.contains("6222021234567890123"), "{}", output);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(Redactor::new(&[RedactionRule::new("(unclosed")]).is_err());
+        assert!(Redactor::from_rules(&[]).unwrap().is_none());
+    }
+}
@@ -0,0 +1,267 @@
+//! 内存环形日志缓冲
+//!
+//! [`RingBufferLayer`] 与其他输出层并行运行，只保留最近 `capacity` 条日志，
+//! 不需要登录服务器就能通过管理接口查看（启用 `admin-http` 特性后使用 [`logs_handler`]）。
+//!
+//! 每条日志按原子递增的写入序号落到固定的槽位中，槽位为 [`ArcSwapOption`]，写入和读取都不加锁：
+//! 写入方用 CAS 替换槽位中的记录，多个写入方绕回到同一槽位时保留序号更大的一条；
+//! 读取方拿到的是记录的快照，不会阻塞写入。
+//! 通过 [`RingBufferLayer::with_redactor`] 设置脱敏规则后，日志先脱敏再写入缓冲，管理接口看不到原文。
+
+use crate::redact::Redactor;
+use arc_swap::ArcSwapOption;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// 缓冲中的一条日志
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    /// 写入序号，单调递增
+    pub seq: u64,
+    pub timestamp: String,
+    #[serde(serialize_with = "serialize_level")]
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+fn serialize_level<S: serde::Serializer>(level: &Level, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(level.as_str())
+}
+
+struct Inner {
+    slots: Box<[ArcSwapOption<LogRecord>]>,
+    next: AtomicU64,
+}
+
+/// 固定容量的日志缓冲，克隆后共享同一份数据
+#[derive(Clone)]
+pub struct RingBuffer {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for RingBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("capacity", &self.capacity())
+            .field("written", &self.inner.next.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl RingBuffer {
+    /// 创建指定容量的缓冲，容量至少为 1
+    pub fn new(capacity: usize) -> Self {
+        let slots = (0..capacity.max(1)).map(|_| ArcSwapOption::empty()).collect();
+        Self {
+            inner: Arc::new(Inner {
+                slots,
+                next: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.inner.slots.len()
+    }
+
+    /// 写入一条日志，缓冲已满时覆盖最旧的一条
+    pub fn push(&self, level: Level, target: &str, message: String) {
+        let seq = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        let slot = &self.inner.slots[(seq % self.capacity() as u64) as usize];
+        let record = Arc::new(LogRecord {
+            seq,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            level,
+            target: target.to_string(),
+            message,
+        });
+
+        // 并发写入同一槽位时保留序号更大的记录，CAS 失败时按最新值重新比较
+        slot.rcu(|current| {
+            let current: &Option<Arc<LogRecord>> = current;
+            match current {
+                Some(existing) if existing.seq > seq => Some(Arc::clone(existing)),
+                _ => Some(Arc::clone(&record)),
+            }
+        });
+    }
+
+    /// 按写入顺序返回缓冲中的日志，`min_level` 为最低级别，如 `WARN` 只返回警告和错误
+    pub fn records(&self, min_level: Option<Level>) -> Vec<LogRecord> {
+        let mut records: Vec<LogRecord> = self
+            .inner
+            .slots
+            .iter()
+            .filter_map(|slot| slot.load_full())
+            .filter(|record| min_level.is_none_or(|min| record.level <= min))
+            .map(|record| LogRecord::clone(&record))
+            .collect();
+        records.sort_by_key(|record| record.seq);
+        records
+    }
+}
+
+/// 把事件写入 [`RingBuffer`] 的 Layer
+pub struct RingBufferLayer {
+    buffer: RingBuffer,
+    redactor: Option<Redactor>,
+}
+
+impl RingBufferLayer {
+    /// 创建 Layer，返回的 [`RingBuffer`] 用于读取日志
+    pub fn new(capacity: usize) -> (Self, RingBuffer) {
+        let buffer = RingBuffer::new(capacity);
+        (Self { buffer: buffer.clone(), redactor: None }, buffer)
+    }
+
+    /// 写入缓冲前按 `redactor` 脱敏，为空时保留原文
+    pub fn with_redactor(mut self, redactor: Option<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RingBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = visitor.finish();
+        if let Some(redactor) = &self.redactor {
+            message = redactor.redact(&message).into_owned();
+        }
+
+        let metadata = event.metadata();
+        self.buffer.push(*metadata.level(), metadata.target(), message);
+    }
+}
+
+/// 把事件字段格式化为 `message key=value ...`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        if self.fields.is_empty() {
+            self.message
+        } else if self.message.is_empty() {
+            self.fields.trim_start().to_string()
+        } else {
+            self.message + &self.fields
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(feature = "admin-http")]
+pub use http::{logs_handler, LogsQuery};
+
+#[cfg(feature = "admin-http")]
+mod http {
+    use super::RingBuffer;
+    use actix_web::{web, HttpResponse};
+    use serde::Deserialize;
+    use std::str::FromStr;
+    use tracing::Level;
+
+    #[derive(Debug, Deserialize)]
+    pub struct LogsQuery {
+        /// 最低级别，如 `warn`
+        pub level: Option<String>,
+    }
+
+    /// `GET /admin/logs?level=warn`，返回缓冲中的日志
+    ///
+    /// ```ignore
+    /// App::new()
+    ///     .app_data(web::Data::new(buffer.clone()))
+    ///     .route("/admin/logs", web::get().to(rlog::logs_handler))
+    /// ```
+    pub async fn logs_handler(buffer: web::Data<RingBuffer>, query: web::Query<LogsQuery>) -> HttpResponse {
+        let min_level = match query.level.as_deref().map(Level::from_str).transpose() {
+            Ok(level) => level,
+            Err(_) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "success": false,
+                    "error": { "type": "BadRequest", "message": "无效的日志级别" }
+                }));
+            }
+        };
+
+        HttpResponse::Ok().json(buffer.records(min_level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RedactionRule;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    #[test]
+    fn test_keeps_most_recent_records() {
+        let (layer, buffer) = RingBufferLayer::new(3);
+        let subscriber = Registry::default().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..5 {
+                tracing::info!(attempt = i, "event {}", i);
+            }
+            tracing::warn!("slow query");
+        });
+
+        let messages: Vec<String> = buffer.records(None).into_iter().map(|r| r.message).collect();
+        assert_eq!(messages, vec!["event 3 attempt=3", "event 4 attempt=4", "slow query"]);
+
+        let warnings = buffer.records(Some(Level::WARN));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].level, Level::WARN);
+        assert_eq!(serde_json::to_value(&warnings[0]).unwrap()["level"], "WARN");
+    }
+
+    #[test]
+    fn test_records_are_redacted() {
+        let redactor = Redactor::new(&[
+            RedactionRule::new("Bearer [A-Za-z0-9._~+/-]+=*").with_replacement("Bearer ***"),
+            RedactionRule::new(r"\b(\d{6})\d{6,9}(\d{4})\b").with_replacement("${1}******${2}"),
+        ])
+        .unwrap();
+        let (layer, buffer) = RingBufferLayer::new(4);
+        let subscriber = Registry::default().with(layer.with_redactor(Some(redactor)));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(card = "6222021234567890123", "auth Bearer abc.def-123");
+        });
+
+        let messages: Vec<String> = buffer.records(None).into_iter().map(|r| r.message).collect();
+        assert_eq!(messages, vec!["auth Bearer *** card=622202******0123"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! 请求范围内的关联字段
+//!
+//! 中间件为每个请求创建带请求ID、用户、租户字段的 span，请求内的所有日志都会带上这些字段，
+//! 业务代码无需逐条补充：
+//!
+//! ```ignore
+//! let _guard = rlog::scope(ScopeFields::new().request_id(&ctx.trace_id).uid(uid));
+//! rlog::info!("order created"); // request{request_id=.. uid=..}: order created
+//! ```
+//!
+//! 异步中间件不能跨 `.await` 持有 [`SpanGuard`]，改用 [`request_span`] 配合 `Instrument::instrument`。
+
+use tracing::span::EnteredSpan;
+use tracing::Span;
+
+/// 请求关联字段，未设置的字段不输出
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeFields {
+    pub request_id: Option<String>,
+    pub uid: Option<String>,
+    pub tenant: Option<String>,
+}
+
+impl ScopeFields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    pub fn uid(mut self, uid: impl Into<String>) -> Self {
+        self.uid = Some(uid.into());
+        self
+    }
+
+    pub fn tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+}
+
+/// 创建携带关联字段的请求 span，由调用方决定何时进入
+pub fn request_span(fields: &ScopeFields) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        request_id = tracing::field::Empty,
+        uid = tracing::field::Empty,
+        tenant = tracing::field::Empty,
+    );
+    if let Some(request_id) = &fields.request_id {
+        span.record("request_id", request_id.as_str());
+    }
+    if let Some(uid) = &fields.uid {
+        span.record("uid", uid.as_str());
+    }
+    if let Some(tenant) = &fields.tenant {
+        span.record("tenant", tenant.as_str());
+    }
+    span
+}
+
+/// 进入携带关联字段的请求 span，守卫释放时退出
+pub fn scope(fields: ScopeFields) -> SpanGuard {
+    SpanGuard { entered: request_span(&fields).entered() }
+}
+
+/// [`scope`] 返回的守卫
+#[must_use = "释放守卫会立即退出请求 span"]
+pub struct SpanGuard {
+    entered: EnteredSpan,
+}
+
+impl SpanGuard {
+    /// 请求 span，可用于派生子任务
+    pub fn span(&self) -> &Span {
+        &self.entered
+    }
+
+    /// 退出请求 span 并返回它
+    pub fn exit(self) -> Span {
+        self.entered.exit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_scope_fields_on_nested_events() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            {
+                let _guard = scope(ScopeFields::new().request_id("req-1").uid("42").tenant("t1"));
+                tracing::info_span!("load_order").in_scope(|| tracing::info!("nested event"));
+            }
+
+            let _guard = scope(ScopeFields::new().request_id("req-2"));
+            tracing::info!("partial fields");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let nested = output.lines().find(|line| line.contains("nested event")).unwrap();
+        assert!(nested.contains("request_id=\"req-1\""), "{}", nested);
+        assert!(nested.contains("uid=\"42\""), "{}", nested);
+        assert!(nested.contains("tenant=\"t1\""), "{}", nested);
+
+        // 未设置的字段不输出
+        let partial = output.lines().find(|line| line.contains("partial fields")).unwrap();
+        assert!(partial.contains("request_id=\"req-2\""), "{}", partial);
+        assert!(!partial.contains("uid"), "{}", partial);
+        assert!(!partial.contains("tenant"), "{}", partial);
+    }
+}
@@ -0,0 +1,436 @@
+//! 日志推送
+//!
+//! [`ShipLayer`] 把事件放入有界队列后立即返回，后台线程按 `batch_size` 或 `flush_interval_ms`
+//! 攒批推送到 Loki push API 或 Elasticsearch bulk API。队列已满时丢弃新日志并计数，
+//! 日志平台不可用时不会拖慢业务线程。
+//!
+//! 推送本身使用的 HTTP 客户端（reqwest、hyper 等）产生的日志不会再被推送，避免循环。
+//! 通过 [`ShipLayer::with_redactor`] 设置脱敏规则后，消息和字符串字段在入队前脱敏。
+
+use crate::redact::Redactor;
+use rconfig::presets::logging::{ShipConfig, ShipKind};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// 单次推送请求的超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 不推送的日志目标前缀，推送请求自身产生的日志
+const IGNORED_TARGETS: &[&str] = &["reqwest", "hyper", "h2", "rustls", "native_tls"];
+
+/// 待推送的一条日志
+#[derive(Debug, Clone)]
+struct ShipEvent {
+    time: SystemTime,
+    level: &'static str,
+    target: String,
+    message: String,
+    fields: Map<String, Value>,
+}
+
+enum Command {
+    Event(ShipEvent),
+    /// 推送已入队的日志，完成后回复
+    Flush(mpsc::Sender<()>),
+    Shutdown,
+}
+
+/// 推送队列的句柄，可以克隆后在其他线程刷新
+#[derive(Clone)]
+pub struct ShipHandle {
+    sender: SyncSender<Command>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl ShipHandle {
+    /// 等待已入队的日志推送完成，超时返回 `false`
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (ack, done) = mpsc::channel();
+        if self.sender.send(Command::Flush(ack)).is_err() {
+            return false;
+        }
+        done.recv_timeout(timeout).is_ok()
+    }
+
+    /// 因队列已满或推送失败丢弃的日志条数
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// 持有推送线程，释放时推送剩余日志并等待线程退出
+pub struct ShipGuard {
+    handle: ShipHandle,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ShipGuard {
+    pub fn handle(&self) -> ShipHandle {
+        self.handle.clone()
+    }
+}
+
+impl Drop for ShipGuard {
+    fn drop(&mut self) {
+        if self.handle.sender.send(Command::Shutdown).is_ok()
+            && let Some(worker) = self.worker.take()
+        {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// 把事件放入推送队列的 Layer
+pub struct ShipLayer {
+    handle: ShipHandle,
+    redactor: Option<Redactor>,
+}
+
+impl ShipLayer {
+    /// 创建 Layer 并启动推送线程
+    pub fn new(config: ShipConfig) -> Result<(Self, ShipGuard), String> {
+        let (sender, receiver) = mpsc::sync_channel(config.queue_capacity.max(1));
+        let handle = ShipHandle {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+
+        let dropped = handle.dropped.clone();
+        let worker = std::thread::Builder::new()
+            .name("rlog-ship".to_string())
+            .spawn(move || Shipper::new(config, dropped).run(receiver))
+            .map_err(|e| format!("Failed to spawn log shipping thread: {}", e))?;
+
+        let guard = ShipGuard {
+            handle: handle.clone(),
+            worker: Some(worker),
+        };
+        Ok((Self { handle, redactor: None }, guard))
+    }
+
+    /// 推送前按 `redactor` 脱敏消息和字符串字段，为空时推送原文
+    pub fn with_redactor(mut self, redactor: Option<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ShipLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let target = metadata.target();
+        if IGNORED_TARGETS.iter().any(|prefix| target.starts_with(prefix)) {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        if let Some(redactor) = &self.redactor {
+            visitor.redact(redactor);
+        }
+
+        let event = ShipEvent {
+            time: SystemTime::now(),
+            level: metadata.level().as_str(),
+            target: target.to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+
+        // 队列已满时丢弃，不阻塞写日志的线程
+        if let Err(TrySendError::Full(_)) = self.handle.sender.try_send(Command::Event(event)) {
+            self.handle.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Map<String, Value>,
+}
+
+impl FieldVisitor {
+    fn redact(&mut self, redactor: &Redactor) {
+        self.message = redactor.redact(&self.message).into_owned();
+        for value in self.fields.values_mut() {
+            if let Value::String(text) = value {
+                *text = redactor.redact(text).into_owned();
+            }
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), json!(value));
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.insert(field.name().to_string(), json!(format!("{:?}", value)));
+        }
+    }
+}
+
+/// 后台推送线程
+struct Shipper {
+    config: ShipConfig,
+    /// 按键排序，保证 Loki 流标签稳定
+    labels: BTreeMap<String, String>,
+    client: Option<reqwest::blocking::Client>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Shipper {
+    fn new(config: ShipConfig, dropped: Arc<AtomicU64>) -> Self {
+        let labels = config.labels.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        // 阻塞客户端不能在异步运行时中创建，在推送线程中创建
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| eprintln!("rlog: failed to create log shipping client: {}", e))
+            .ok();
+        Self {
+            config,
+            labels,
+            client,
+            dropped,
+        }
+    }
+
+    fn run(self, receiver: Receiver<Command>) {
+        let interval = Duration::from_millis(self.config.flush_interval_ms.max(1));
+        let batch_size = self.config.batch_size.max(1);
+        let mut batch: Vec<ShipEvent> = Vec::with_capacity(batch_size);
+        let mut deadline = Instant::now() + interval;
+
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(Command::Event(event)) => {
+                    batch.push(event);
+                    if batch.len() >= batch_size {
+                        self.send(&mut batch);
+                        deadline = Instant::now() + interval;
+                    }
+                }
+                Ok(Command::Flush(ack)) => {
+                    // 队列有序，Flush 之前入队的日志都已在当前批次中
+                    self.send(&mut batch);
+                    let _ = ack.send(());
+                }
+                Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                    self.send(&mut batch);
+                    return;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.send(&mut batch);
+                    deadline = Instant::now() + interval;
+                }
+            }
+        }
+    }
+
+    /// 推送并清空当前批次，失败时计入丢弃数量
+    fn send(&self, batch: &mut Vec<ShipEvent>) {
+        if batch.is_empty() {
+            return;
+        }
+        let events = std::mem::take(batch);
+
+        let (body, content_type) = match self.config.kind {
+            ShipKind::Loki => (loki_body(&events, &self.labels), "application/json"),
+            ShipKind::Elasticsearch => (bulk_body(&events, &self.labels), "application/x-ndjson"),
+        };
+
+        let result = match &self.client {
+            Some(client) => client
+                .post(&self.config.endpoint)
+                .header("Content-Type", content_type)
+                .body(body)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            None => Err("client unavailable".to_string()),
+        };
+
+        if let Err(e) = result {
+            // 不能写入 tracing，否则失败日志会再次进入推送队列
+            eprintln!("rlog: failed to ship {} log events to {}: {}", events.len(), self.config.endpoint, e);
+            self.dropped.fetch_add(events.len() as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 日志正文，包含级别、目标、消息和事件字段
+fn event_line(event: &ShipEvent) -> Value {
+    let mut line = event.fields.clone();
+    line.insert("level".to_string(), json!(event.level));
+    line.insert("target".to_string(), json!(event.target));
+    line.insert("message".to_string(), json!(event.message));
+    Value::Object(line)
+}
+
+/// Loki push API 请求体，配置的标签作为唯一的流标签
+fn loki_body(events: &[ShipEvent], labels: &BTreeMap<String, String>) -> String {
+    let values: Vec<Value> = events
+        .iter()
+        .map(|event| {
+            let nanos = event.time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+            json!([nanos.to_string(), event_line(event).to_string()])
+        })
+        .collect();
+
+    json!({ "streams": [{ "stream": labels, "values": values }] }).to_string()
+}
+
+/// Elasticsearch bulk API 请求体，索引由推送地址指定
+fn bulk_body(events: &[ShipEvent], labels: &BTreeMap<String, String>) -> String {
+    let mut body = String::new();
+    for event in events {
+        let mut doc = event_line(event);
+        if let Value::Object(doc) = &mut doc {
+            for (key, value) in labels {
+                doc.insert(key.clone(), json!(value));
+            }
+            let time: chrono::DateTime<chrono::Utc> = event.time.into();
+            doc.insert("@timestamp".to_string(), json!(time.to_rfc3339()));
+        }
+        body.push_str("{\"index\":{}}\n");
+        body.push_str(&doc.to_string());
+        body.push('\n');
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RedactionRule;
+    use httpmock::prelude::*;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    #[test]
+    fn test_loki_batches_with_labels() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/loki/api/v1/push")
+                .header("Content-Type", "application/json")
+                .body_contains(r#""stream":{"app":"payment","env":"test"}"#);
+            then.status(204);
+        });
+
+        let config = ShipConfig::new(ShipKind::Loki, server.url("/loki/api/v1/push"))
+            .with_label("app", "payment")
+            .with_label("env", "test")
+            .with_batch_size(2);
+        let (layer, guard) = ShipLayer::new(config).unwrap();
+
+        tracing::subscriber::with_default(Registry::default().with(layer), || {
+            tracing::info!(order_id = "o-1", "created");
+            tracing::warn!("slow channel");
+            tracing::error!("failed");
+        });
+
+        // 前两条攒满一批，剩余一条在刷新时推送
+        assert!(guard.handle().flush(Duration::from_secs(5)));
+        mock.assert_hits(2);
+        assert_eq!(guard.handle().dropped(), 0);
+    }
+
+    #[test]
+    fn test_shipped_events_are_redacted() {
+        let server = MockServer::start();
+        let leaked = server.mock(|when, then| {
+            when.method(POST).body_contains("abc.def-123");
+            then.status(204);
+        });
+        let redacted = server.mock(|when, then| {
+            when.method(POST)
+                .body_contains("auth Bearer ***")
+                .body_contains(r#"\"token\":\"Bearer ***\""#);
+            then.status(204);
+        });
+
+        let redactor = Redactor::new(&[
+            RedactionRule::new("Bearer [A-Za-z0-9._~+/-]+=*").with_replacement("Bearer ***"),
+        ])
+        .unwrap();
+        let config = ShipConfig::new(ShipKind::Loki, server.url("/loki/api/v1/push"));
+        let (layer, guard) = ShipLayer::new(config).unwrap();
+
+        tracing::subscriber::with_default(Registry::default().with(layer.with_redactor(Some(redactor))), || {
+            tracing::info!(token = "Bearer abc.def-123", "auth Bearer abc.def-123");
+        });
+
+        assert!(guard.handle().flush(Duration::from_secs(5)));
+        redacted.assert_hits(1);
+        leaked.assert_hits(0);
+    }
+
+    #[test]
+    fn test_bulk_body() {
+        let event = ShipEvent {
+            time: UNIX_EPOCH,
+            level: "INFO",
+            target: "payment".to_string(),
+            message: "created".to_string(),
+            fields: Map::from_iter([("order_id".to_string(), json!("o-1"))]),
+        };
+        let labels = BTreeMap::from([("app".to_string(), "payment".to_string())]);
+
+        let body = bulk_body(&[event], &labels);
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines[0], r#"{"index":{}}"#);
+
+        let doc: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(doc["app"], "payment");
+        assert_eq!(doc["message"], "created");
+        assert_eq!(doc["order_id"], "o-1");
+        assert_eq!(doc["@timestamp"], "1970-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_unreachable_endpoint_counts_dropped() {
+        let config = ShipConfig::new(ShipKind::Loki, "http://127.0.0.1:1/loki/api/v1/push");
+        let (layer, guard) = ShipLayer::new(config).unwrap();
+
+        tracing::subscriber::with_default(Registry::default().with(layer), || {
+            tracing::info!("lost");
+        });
+
+        assert!(guard.handle().flush(Duration::from_secs(10)));
+        assert_eq!(guard.handle().dropped(), 1);
+    }
+}
@@ -0,0 +1,165 @@
+//! 在函数执行过程中为当前 span 补充字段
+//!
+//! 租户、订单号等上下文常在函数中途才能确定，`#[instrument]` 时先声明为空字段，确定后再记录：
+//!
+//! ```ignore
+//! #[instrument(skip_all, fields(tenant_id = tracing::field::Empty))]
+//! async fn handle(req: Request) {
+//!     let tenant = resolve_tenant(&req).await;
+//!     rlog::record("tenant_id", tenant.id);
+//! }
+//! ```
+//!
+//! tracing 只允许记录创建 span 时已声明的字段，未声明的字段会被忽略。
+
+use std::fmt;
+
+/// 可记录到 span 上的字段值
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(value) => f.write_str(value),
+            Self::I64(value) => write!(f, "{}", value),
+            Self::U64(value) => write!(f, "{}", value),
+            Self::F64(value) => write!(f, "{}", value),
+            Self::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<&String> for FieldValue {
+    fn from(value: &String) -> Self {
+        Self::Str(value.clone())
+    }
+}
+
+impl From<i32> for FieldValue {
+    fn from(value: i32) -> Self {
+        Self::I64(value.into())
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(value: i64) -> Self {
+        Self::I64(value)
+    }
+}
+
+impl From<u32> for FieldValue {
+    fn from(value: u32) -> Self {
+        Self::U64(value.into())
+    }
+}
+
+impl From<u64> for FieldValue {
+    fn from(value: u64) -> Self {
+        Self::U64(value)
+    }
+}
+
+impl From<usize> for FieldValue {
+    fn from(value: usize) -> Self {
+        Self::U64(value as u64)
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// 在当前 span 上记录字段，当前没有 span 或 span 未声明该字段时返回 `false`
+pub fn record(key: &str, value: impl Into<FieldValue>) -> bool {
+    let span = tracing::Span::current();
+    if !span.has_field(key) {
+        return false;
+    }
+
+    match value.into() {
+        FieldValue::Str(value) => span.record(key, value.as_str()),
+        FieldValue::I64(value) => span.record(key, value),
+        FieldValue::U64(value) => span.record(key, value),
+        FieldValue::F64(value) => span.record(key, value),
+        FieldValue::Bool(value) => span.record(key, value),
+    };
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tracing::instrument(fields(tenant_id = tracing::field::Empty, vip = tracing::field::Empty))]
+    fn handle_order(order_id: &str) -> bool {
+        assert!(record("tenant_id", 42));
+        assert!(record("vip", true));
+        record("undeclared", "ignored")
+    }
+
+    #[test]
+    fn test_recorded_field_on_close_event() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .with_span_events(FmtSpan::CLOSE)
+            .finish();
+
+        let recorded = tracing::subscriber::with_default(subscriber, || handle_order("o1"));
+        assert!(!recorded);
+        // 没有 span 时不记录
+        assert!(!record("tenant_id", 1));
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let close = output.lines().find(|line| line.contains("close")).unwrap();
+        assert!(close.contains("tenant_id=42"), "{}", close);
+        assert!(close.contains("vip=true"), "{}", close);
+        assert!(!close.contains("undeclared"), "{}", close);
+    }
+}
@@ -0,0 +1,122 @@
+//! span 耗时指标
+//!
+//! span 关闭时通过 `metrics` 记录其忙碌（进入 span 执行的时间）和空闲（已创建但未在执行的时间，
+//! 如 `.await` 等待）耗时，单位秒，指标名为 `<target>::<span 名称>`，标签 `time` 区分 `busy`/`idle`。
+//! 已有的 `#[instrument]` 不需要修改即可得到延迟直方图，需要应用自行安装 `metrics` 的导出器。
+
+use std::time::{Duration, Instant};
+use tracing::span;
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// span 关闭时记录耗时直方图的 Layer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpanMetricsLayer;
+
+impl SpanMetricsLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// 保存在 span 扩展中的计时
+struct Timings {
+    busy: Duration,
+    idle: Duration,
+    last: Instant,
+}
+
+impl<S> Layer<S> for SpanMetricsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Timings {
+                busy: Duration::ZERO,
+                idle: Duration::ZERO,
+                last: Instant::now(),
+            });
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(timings) = span.extensions_mut().get_mut::<Timings>()
+        {
+            let now = Instant::now();
+            timings.idle += now - timings.last;
+            timings.last = now;
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(timings) = span.extensions_mut().get_mut::<Timings>()
+        {
+            let now = Instant::now();
+            timings.busy += now - timings.last;
+            timings.last = now;
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timings) = span.extensions_mut().remove::<Timings>() else {
+            return;
+        };
+
+        // 最后一次退出到关闭之间也计入空闲
+        let idle = timings.idle + timings.last.elapsed();
+        let name = format!("{}::{}", span.metadata().target(), span.name());
+        metrics::histogram!(name.clone(), "time" => "busy").record(timings.busy.as_secs_f64());
+        metrics::histogram!(name, "time" => "idle").record(idle.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use metrics_util::MetricKind;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    #[tracing::instrument]
+    fn load_order() {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_span_close_records_histogram() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let subscriber = Registry::default().with(SpanMetricsLayer::new());
+
+        metrics::with_local_recorder(&recorder, || {
+            tracing::subscriber::with_default(subscriber, load_order);
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let busy = snapshot
+            .iter()
+            .find(|(key, ..)| {
+                key.kind() == MetricKind::Histogram
+                    && key.key().name() == "rlog::span_metrics::tests::load_order"
+                    && key.key().labels().any(|label| label.key() == "time" && label.value() == "busy")
+            })
+            .map(|(.., value)| value);
+
+        match busy {
+            Some(DebugValue::Histogram(samples)) => {
+                assert_eq!(samples.len(), 1);
+                assert!(samples[0].into_inner() >= 0.02);
+            }
+            other => panic!("expected busy histogram, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! 按需刷新文件日志（独立进程，避免与其他测试争用全局日志）
+
+use rlog::{FileSink, LogConfig};
+
+#[test]
+fn test_flush_writes_to_disk() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = tempfile::tempdir()?;
+    let path = temp.path().join("app.log");
+
+    let config = LogConfig {
+        level: "info".to_string(),
+        to_console: false,
+        files: vec![FileSink::new(&path)],
+        ..Default::default()
+    };
+    rlog::init(&config)?;
+
+    rlog::info!("checkpoint reached");
+    rlog::flush()?;
+
+    // guard 仍由日志系统持有，内容已经落盘
+    assert!(std::fs::read_to_string(&path)?.contains("checkpoint reached"));
+    Ok(())
+}
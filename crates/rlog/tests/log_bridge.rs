@@ -0,0 +1,23 @@
+//! log crate 转发级别上限（独立进程，避免与其他测试争用全局日志）
+
+use rlog::LogConfig;
+
+#[test]
+fn test_log_crate_max_level() {
+    let config = LogConfig {
+        level: "trace".to_string(),
+        to_console: true,
+        log_crate_max_level: Some("info".to_string()),
+        ..Default::default()
+    };
+    rlog::init(&config).unwrap();
+
+    // 低于上限的 log 记录不会被转发
+    assert!(!log::log_enabled!(log::Level::Trace));
+    assert!(!log::log_enabled!(log::Level::Debug));
+    assert!(log::log_enabled!(log::Level::Info));
+    log::trace!("dropped by log_crate_max_level");
+
+    // tracing 自身的过滤不受影响
+    assert!(tracing::enabled!(tracing::Level::TRACE));
+}
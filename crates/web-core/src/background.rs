@@ -0,0 +1,139 @@
+//! **后台定时任务**
+//!
+//! 服务通过 [`WebService::background_tasks`] 声明需要周期执行的任务（缓存刷新、数据清理等），
+//! 由 [`BackgroundScheduler`] 统一调度，服务器停止时一并取消。
+
+use crate::web_service::WebService;
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::info;
+
+type TaskFn = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// **周期执行的后台任务**
+#[derive(Clone)]
+pub struct BackgroundTask {
+    /// 任务名称，用于日志
+    pub name: String,
+    /// 执行间隔，启动后立即执行一次
+    pub interval: Duration,
+    /// 间隔的随机抖动，每次在 `interval ± jitter` 内取值，避免多个实例同时执行
+    pub jitter: Duration,
+    /// 任务内容，每次执行时调用一次
+    pub task: TaskFn,
+}
+
+impl BackgroundTask {
+    pub fn new<F, Fut>(name: impl Into<String>, interval: Duration, task: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            interval,
+            jitter: Duration::ZERO,
+            task: Arc::new(move || Box::pin(task())),
+        }
+    }
+
+    /// 设置间隔的随机抖动
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+}
+
+impl std::fmt::Debug for BackgroundTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackgroundTask")
+            .field("name", &self.name)
+            .field("interval", &self.interval)
+            .field("jitter", &self.jitter)
+            .finish()
+    }
+}
+
+/// **后台任务调度器**
+///
+/// 每个任务运行在独立的 tokio 任务中，调度器被 drop 或调用 [`shutdown`](Self::shutdown) 时全部取消
+#[derive(Debug, Default)]
+pub struct BackgroundScheduler {
+    tasks: JoinSet<()>,
+}
+
+impl BackgroundScheduler {
+    /// 启动给定的后台任务，必须在 tokio 运行时中调用
+    pub fn spawn(tasks: impl IntoIterator<Item = BackgroundTask>) -> Self {
+        let mut scheduler = Self::default();
+        for task in tasks {
+            info!("Starting background task: {} (every {:?})", task.name, task.interval);
+            scheduler.tasks.spawn(async move {
+                (task.task)().await;
+                // 任务执行时间超过间隔时不补偿错过的次数
+                let mut ticks = std::pin::pin!(common::sched::jittered_interval(task.interval, task.jitter));
+                while ticks.next().await.is_some() {
+                    (task.task)().await;
+                }
+            });
+        }
+        scheduler
+    }
+
+    /// 启动所有通过 #[service] 注册的服务声明的后台任务
+    pub fn spawn_registered() -> Self {
+        Self::spawn(
+            inventory::iter::<&dyn WebService>
+                .into_iter()
+                .flat_map(|service| service.background_tasks()),
+        )
+    }
+
+    /// 正在运行的任务数量
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// 取消所有后台任务并等待其退出
+    pub async fn shutdown(mut self) {
+        self.tasks.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[actix_web::test]
+    async fn test_background_task_runs() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let task = BackgroundTask::new("counter", Duration::from_millis(50), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let scheduler = BackgroundScheduler::spawn(vec![task]);
+        assert_eq!(scheduler.len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(runs.load(Ordering::SeqCst) >= 1);
+
+        // 取消后不再执行
+        scheduler.shutdown().await;
+        let stopped_at = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), stopped_at);
+    }
+}
@@ -0,0 +1,160 @@
+//! **服务错误类型**
+//! - 处理函数返回 [`WebResult`]，出错时由 [`WebError`] 统一转换为 [`ApiResponse`] 格式的响应，
+//!   不需要各服务自行映射 HTTP 状态码。
+//! - 错误码取自 [`common::ErrorCode`]，响应中附带当前请求的追踪ID。
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use common::ErrorCode;
+use serde::Serialize;
+use thiserror::Error;
+
+/// 服务处理结果
+pub type WebResult<T> = Result<T, WebError>;
+
+/// **统一响应格式**
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T: Serialize> {
+    /// 业务码，成功为 0，失败为 [`ErrorCode`] 或自定义错误码
+    pub code: i32,
+    pub message: String,
+    pub data: Option<T>,
+    /// 当前请求的追踪ID，不在请求上下文中时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    pub fn success(data: T) -> Self {
+        Self {
+            code: 0,
+            message: "success".to_string(),
+            data: Some(data),
+            trace_id: common::trace::current_trace_id(),
+        }
+    }
+
+    pub fn error(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+            trace_id: common::trace::current_trace_id(),
+        }
+    }
+}
+
+/// **服务错误**
+#[derive(Debug, Error)]
+pub enum WebError {
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    Unauthorized(String),
+
+    /// 内部错误，详细信息只记录日志，不返回给调用方
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+
+    /// 自定义状态码和错误码
+    #[error("{message}")]
+    Custom {
+        status: StatusCode,
+        code: i32,
+        message: String,
+    },
+}
+
+impl WebError {
+    /// 响应中的业务码
+    pub fn code(&self) -> i32 {
+        match self {
+            WebError::BadRequest(_) => ErrorCode::InvalidParameter.code(),
+            WebError::NotFound(_) => ErrorCode::NotFound.code(),
+            WebError::Unauthorized(_) => ErrorCode::Unauthorized.code(),
+            WebError::Internal(_) => ErrorCode::InternalError.code(),
+            WebError::Custom { code, .. } => *code,
+        }
+    }
+}
+
+impl From<ErrorCode> for WebError {
+    fn from(code: ErrorCode) -> Self {
+        WebError::Custom {
+            status: StatusCode::from_u16(code.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            code: code.code(),
+            message: code.message().to_string(),
+        }
+    }
+}
+
+impl ResponseError for WebError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            WebError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            WebError::NotFound(_) => StatusCode::NOT_FOUND,
+            WebError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            WebError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            WebError::Custom { status, .. } => *status,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let message = match self {
+            WebError::Internal(e) => {
+                tracing::error!("内部错误: {:?}", e);
+                ErrorCode::InternalError.message().to_string()
+            }
+            _ => self.to_string(),
+        };
+
+        HttpResponse::build(self.status_code()).json(ApiResponse::<()>::error(self.code(), message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use serde_json::{json, Value};
+
+    async fn render(error: WebError) -> (StatusCode, Value) {
+        let response = error.error_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[actix_web::test]
+    async fn test_variant_responses() {
+        let cases = vec![
+            (WebError::BadRequest("缺少 id".into()), 400, 40001, "缺少 id"),
+            (WebError::NotFound("用户不存在".into()), 404, 40401, "用户不存在"),
+            (WebError::Unauthorized("令牌无效".into()), 401, 40101, "令牌无效"),
+            (WebError::Internal(anyhow::anyhow!("connection refused")), 500, 50001, "服务内部错误"),
+            (
+                WebError::Custom { status: StatusCode::CONFLICT, code: 40901, message: "订单已支付".into() },
+                409,
+                40901,
+                "订单已支付",
+            ),
+            (ErrorCode::TooManyRequests.into(), 429, 42901, "请求过于频繁"),
+        ];
+
+        for (error, status, code, message) in cases {
+            let (actual_status, body) = render(error).await;
+            assert_eq!(actual_status.as_u16(), status);
+            assert_eq!(body, json!({ "code": code, "message": message, "data": null }));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_trace_id_included() {
+        let (_, body) = common::trace::scope("trace-1".to_string(), render(WebError::NotFound("x".into()))).await;
+        assert_eq!(body["trace_id"], "trace-1");
+    }
+}
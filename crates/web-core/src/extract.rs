@@ -0,0 +1,203 @@
+//! **类型化请求提取**
+//! - `FromWebRequest` 参照 axum 提取器的设计，让服务的处理函数直接声明所需的输入类型
+//!   （JSON 请求体、路径参数、查询参数），由 [`typed`] 统一完成提取与反序列化。
+//! - 任意一个输入提取失败都会直接返回 400，处理函数只需关心业务逻辑。
+
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse, Responder, ResponseError};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::future::Future;
+use thiserror::Error;
+
+/// **请求提取错误**
+#[derive(Error, Debug)]
+pub enum ExtractError {
+    #[error("请求体解析失败: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("路径参数解析失败: {0}")]
+    Path(String),
+
+    #[error("查询参数解析失败: {0}")]
+    Query(#[from] serde_urlencoded::de::Error),
+}
+
+impl ResponseError for ExtractError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(json!({
+            "success": false,
+            "error": {
+                "type": "BadRequest",
+                "message": self.to_string()
+            }
+        }))
+    }
+}
+
+/// **可从请求中提取的类型**
+///
+/// 请求体在调用前已被完整读取，所以实现者可以同时访问请求头/路径和请求体。
+pub trait FromWebRequest: Sized {
+    fn from_web_request(req: &HttpRequest, body: &web::Bytes) -> Result<Self, ExtractError>;
+}
+
+impl FromWebRequest for () {
+    fn from_web_request(_req: &HttpRequest, _body: &web::Bytes) -> Result<Self, ExtractError> {
+        Ok(())
+    }
+}
+
+impl FromWebRequest for HttpRequest {
+    fn from_web_request(req: &HttpRequest, _body: &web::Bytes) -> Result<Self, ExtractError> {
+        Ok(req.clone())
+    }
+}
+
+impl FromWebRequest for web::Bytes {
+    fn from_web_request(_req: &HttpRequest, body: &web::Bytes) -> Result<Self, ExtractError> {
+        Ok(body.clone())
+    }
+}
+
+/// JSON 请求体
+impl<T: DeserializeOwned> FromWebRequest for web::Json<T> {
+    fn from_web_request(_req: &HttpRequest, body: &web::Bytes) -> Result<Self, ExtractError> {
+        Ok(web::Json(serde_json::from_slice(body)?))
+    }
+}
+
+/// 路径参数，例如 `/users/{id}`
+impl<T: DeserializeOwned> FromWebRequest for web::Path<T> {
+    fn from_web_request(req: &HttpRequest, _body: &web::Bytes) -> Result<Self, ExtractError> {
+        // Path 的提取是同步完成的，可以直接取出结果
+        web::Path::<T>::extract(req)
+            .into_inner()
+            .map_err(|e| ExtractError::Path(e.to_string()))
+    }
+}
+
+/// 查询参数，例如 `?page=1&size=20`
+impl<T: DeserializeOwned> FromWebRequest for web::Query<T> {
+    fn from_web_request(req: &HttpRequest, _body: &web::Bytes) -> Result<Self, ExtractError> {
+        Ok(web::Query(serde_urlencoded::from_str(req.query_string())?))
+    }
+}
+
+macro_rules! impl_from_web_request_tuple {
+    ($($ty:ident),+) => {
+        impl<$($ty: FromWebRequest),+> FromWebRequest for ($($ty,)+) {
+            fn from_web_request(req: &HttpRequest, body: &web::Bytes) -> Result<Self, ExtractError> {
+                Ok(($($ty::from_web_request(req, body)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_web_request_tuple!(A);
+impl_from_web_request_tuple!(A, B);
+impl_from_web_request_tuple!(A, B, C);
+impl_from_web_request_tuple!(A, B, C, D);
+
+/// **将类型化处理函数包装为 actix 路由处理函数**
+///
+/// ```ignore
+/// cfg.route("/users/{id}", web::post().to(typed(Self::update_user)));
+///
+/// async fn update_user((path, body): (web::Path<u64>, web::Json<UpdateUser>)) -> impl Responder { ... }
+/// ```
+pub fn typed<H, I, Fut, R>(
+    handler: H,
+) -> impl Fn(HttpRequest, web::Bytes) -> LocalBoxFuture<'static, Result<R, ExtractError>> + Clone + 'static
+where
+    H: Fn(I) -> Fut + Clone + 'static,
+    I: FromWebRequest + 'static,
+    Fut: Future<Output = R> + 'static,
+    R: Responder + 'static,
+{
+    move |req: HttpRequest, body: web::Bytes| {
+        let handler = handler.clone();
+        Box::pin(async move {
+            let input = I::from_web_request(&req, &body)?;
+            Ok(handler(input).await)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web_service::WebService;
+    use actix_web::{test, App};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct CreateUser {
+        name: String,
+        age: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Paging {
+        page: u32,
+    }
+
+    struct UserService;
+
+    impl WebService for UserService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/users", web::post().to(typed(Self::create_user)))
+                .route("/users/{id}", web::get().to(typed(Self::get_user)));
+        }
+    }
+
+    impl UserService {
+        async fn create_user(web::Json(user): web::Json<CreateUser>) -> impl Responder {
+            HttpResponse::Ok().json(json!({ "name": user.name, "age": user.age }))
+        }
+
+        async fn get_user((path, query): (web::Path<u64>, web::Query<Paging>)) -> impl Responder {
+            HttpResponse::Ok().body(format!("{}:{}", path.into_inner(), query.page))
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_typed_json_body() {
+        let app = test::init_service(App::new().configure(|cfg| UserService.configure(cfg))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/users")
+            .set_json(CreateUser { name: "sakura".to_string(), age: 18 })
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["name"], "sakura");
+        assert_eq!(body["age"], 18);
+
+        // 请求体无法反序列化时返回 400
+        let req = test::TestRequest::post()
+            .uri("/users")
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(r#"{"name": "sakura"}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_typed_path_and_query() {
+        let app = test::init_service(App::new().configure(|cfg| UserService.configure(cfg))).await;
+
+        let req = test::TestRequest::get().uri("/users/42?page=3").to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, web::Bytes::from_static(b"42:3"));
+
+        let req = test::TestRequest::get().uri("/users/abc?page=3").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -13,6 +13,15 @@
 
 pub mod web_service;
 pub mod third_party;
+pub mod extract;
+pub mod background;
+pub mod requirements;
+pub mod limits;
+pub mod state;
+pub mod error;
+
+pub use error::{ApiResponse, WebError, WebResult};
+pub use state::State;
 
 
 // 使用 #[service] 代替
@@ -0,0 +1,488 @@
+//! **服务级限流与并发控制**
+//! - 服务通过 [`WebService::limits`](crate::web_service::WebService::limits) 声明资源限制，
+//!   [`mount_all`](crate::web_service::mount_all) 挂载时统一套上 [`ServiceGuard`]，服务本身不需要接入中间件。
+//! - 超过每秒请求数时返回 429；并发数已满时排队等待，超过 `acquire_timeout` 仍未轮到则返回 503。
+//! - 限制作用于 `path_prefix` 下的请求，同一服务在所有 worker 间共享并发名额。
+//! - 服务声明了 [`WebService::request_timeout`](crate::web_service::WebService::request_timeout) 时，
+//!   处理超时的请求被取消并返回 504，等待并发名额的时间不计入处理时间；只声明超时的服务同样生效。
+//! - 默认的 [`LocalRateLimiter`] 只在当前进程内计数，多实例部署时通过 [`ServiceLimits::with_rate_limiter`]
+//!   换成基于 Redis 的实现，保证集群整体的请求速率。
+
+use crate::error::WebError;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpMessage, ResponseError};
+use common::ErrorCode;
+use futures_util::future::{ready, BoxFuture, LocalBoxFuture, Ready};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// 并发名额默认的最长等待时间
+const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// **请求速率限制器**
+pub trait RateLimiter: Send + Sync {
+    /// 在当前一秒的窗口内为 `key` 占用一个名额，超过 `rate_per_sec` 时返回 `false`
+    fn acquire<'a>(&'a self, key: &'a str, rate_per_sec: u32) -> BoxFuture<'a, bool>;
+}
+
+/// **进程内的固定窗口限流器**
+#[derive(Debug, Default)]
+pub struct LocalRateLimiter {
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl LocalRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RateLimiter for LocalRateLimiter {
+    fn acquire<'a>(&'a self, key: &'a str, rate_per_sec: u32) -> BoxFuture<'a, bool> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let (start, count) = windows.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(*start) >= Duration::from_secs(1) {
+            *start = now;
+            *count = 0;
+        }
+
+        let allowed = *count < rate_per_sec;
+        if allowed {
+            *count += 1;
+        }
+        Box::pin(ready(allowed))
+    }
+}
+
+/// **服务资源限制**
+#[derive(Clone)]
+pub struct ServiceLimits {
+    /// 限制作用的路径前缀，按路径段匹配
+    pub path_prefix: String,
+    /// 同时处理的最大请求数
+    pub max_concurrent: Option<usize>,
+    /// 每秒最多接受的请求数
+    pub rate_per_sec: Option<u32>,
+    /// 等待并发名额的最长时间
+    pub acquire_timeout: Duration,
+    rate_limiter: Arc<dyn RateLimiter>,
+}
+
+impl ServiceLimits {
+    pub fn new(path_prefix: impl Into<String>) -> Self {
+        Self {
+            path_prefix: path_prefix.into(),
+            max_concurrent: None,
+            rate_per_sec: None,
+            acquire_timeout: DEFAULT_ACQUIRE_TIMEOUT,
+            rate_limiter: Arc::new(LocalRateLimiter::new()),
+        }
+    }
+
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = Some(max_concurrent);
+        self
+    }
+
+    pub fn with_rate_per_sec(mut self, rate_per_sec: u32) -> Self {
+        self.rate_per_sec = Some(rate_per_sec);
+        self
+    }
+
+    pub fn with_acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = timeout;
+        self
+    }
+
+    /// 替换默认的进程内限流器
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<dyn RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// 请求路径是否受此限制约束
+    pub fn matches(&self, path: &str) -> bool {
+        let prefix = self.path_prefix.trim_end_matches('/');
+        prefix.is_empty()
+            || path == prefix
+            || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+impl std::fmt::Debug for ServiceLimits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceLimits")
+            .field("path_prefix", &self.path_prefix)
+            .field("max_concurrent", &self.max_concurrent)
+            .field("rate_per_sec", &self.rate_per_sec)
+            .field("acquire_timeout", &self.acquire_timeout)
+            .finish()
+    }
+}
+
+/// 请求已经过的超时守卫层数
+///
+/// 只声明超时的服务嵌套挂载，内层服务的请求也会经过外层的守卫，外层超时后发现请求已被内层守卫接手时
+/// 不再计时，由内层守卫按实际处理请求的服务的超时执行
+#[derive(Clone, Default)]
+struct TimeoutDepth(Rc<Cell<usize>>);
+
+/// **执行服务资源限制的中间件**
+#[derive(Clone)]
+pub struct ServiceGuard {
+    name: &'static str,
+    limits: ServiceLimits,
+    semaphore: Option<Arc<Semaphore>>,
+    request_timeout: Option<Duration>,
+}
+
+impl ServiceGuard {
+    pub fn new(name: &'static str, limits: ServiceLimits) -> Self {
+        let semaphore = limits.max_concurrent.map(|n| Arc::new(Semaphore::new(n)));
+        Self { name, limits, semaphore, request_timeout: None }
+    }
+
+    /// 请求处理的最长时间
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ServiceGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ServiceGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ServiceGuardMiddleware {
+            service: Rc::new(service),
+            guard: self.clone(),
+        }))
+    }
+}
+
+pub struct ServiceGuardMiddleware<S> {
+    service: Rc<S>,
+    guard: ServiceGuard,
+}
+
+impl<S, B> Service<ServiceRequest> for ServiceGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let guard = self.guard.clone();
+
+        Box::pin(async move {
+            let reject = |req: ServiceRequest, code: ErrorCode| {
+                let response = WebError::from(code).error_response();
+                Ok(req.into_response(response).map_into_right_body())
+            };
+
+            if let Some(rate) = guard.limits.rate_per_sec
+                && !guard.limits.rate_limiter.acquire(guard.name, rate).await
+            {
+                tracing::warn!("Service {} rate limited: {}", guard.name, req.path());
+                return reject(req, ErrorCode::TooManyRequests);
+            }
+
+            // 名额在响应返回后释放
+            let _permit = match &guard.semaphore {
+                Some(semaphore) => {
+                    match tokio::time::timeout(guard.limits.acquire_timeout, semaphore.clone().acquire_owned()).await {
+                        Ok(Ok(permit)) => Some(permit),
+                        _ => {
+                            tracing::warn!("Service {} concurrency saturated: {}", guard.name, req.path());
+                            return reject(req, ErrorCode::ServiceUnavailable);
+                        }
+                    }
+                }
+                None => None,
+            };
+
+            let Some(timeout) = guard.request_timeout else {
+                return service.call(req).await.map(ServiceResponse::map_into_left_body);
+            };
+
+            let depth = {
+                let mut extensions = req.extensions_mut();
+                let depth = extensions.get::<TimeoutDepth>().cloned().unwrap_or_default();
+                extensions.insert(depth.clone());
+                depth
+            };
+            let level = depth.0.get() + 1;
+            depth.0.set(level);
+
+            // 超时后丢弃处理函数的 future，处理随之取消；请求已交给处理函数，
+            // 以错误返回，由 actix 通过 `ResponseError` 生成 504 响应
+            let path = req.path().to_string();
+            let call = service.call(req);
+            futures_util::pin_mut!(call);
+            match tokio::time::timeout(timeout, &mut call).await {
+                Ok(result) => result.map(ServiceResponse::map_into_left_body),
+                // 内层服务接手了请求，超时由内层守卫执行
+                Err(_) if depth.0.get() > level => call.await.map(ServiceResponse::map_into_left_body),
+                Err(_) => {
+                    tracing::warn!("Service {} request timed out after {:?}: {}", guard.name, timeout, path);
+                    Err(WebError::from(ErrorCode::Timeout).into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web_service::{mount, mount_timeout_chain, WebService};
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, try_call_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+    static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+    struct ReportService;
+
+    impl WebService for ReportService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/reports/daily", web::get().to(Self::daily));
+        }
+
+        fn limits(&self) -> Option<ServiceLimits> {
+            Some(ServiceLimits::new("/reports").with_max_concurrent(1))
+        }
+    }
+
+    impl ReportService {
+        async fn daily() -> HttpResponse {
+            let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+            HttpResponse::Ok().finish()
+        }
+    }
+
+    struct PingService;
+
+    impl WebService for PingService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/ping", web::get().to(HttpResponse::Ok));
+        }
+    }
+
+    static SLOW_FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    struct ExportService;
+
+    impl WebService for ExportService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/exports/slow", web::get().to(Self::slow))
+                .route("/exports/fast", web::get().to(HttpResponse::Ok));
+        }
+
+        fn limits(&self) -> Option<ServiceLimits> {
+            Some(ServiceLimits::new("/exports"))
+        }
+
+        fn request_timeout(&self) -> Option<Duration> {
+            Some(Duration::from_millis(50))
+        }
+    }
+
+    impl ExportService {
+        async fn slow() -> HttpResponse {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            SLOW_FINISHED.fetch_add(1, Ordering::SeqCst);
+            HttpResponse::Ok().finish()
+        }
+    }
+
+    /// 只声明超时，不声明限制
+    struct SearchService;
+
+    impl WebService for SearchService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/search/slow", web::get().to(Self::slow));
+        }
+
+        fn request_timeout(&self) -> Option<Duration> {
+            Some(Duration::from_millis(50))
+        }
+    }
+
+    impl SearchService {
+        async fn slow() -> HttpResponse {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            HttpResponse::Ok().finish()
+        }
+    }
+
+    /// 只声明超时，超时比 [`SearchService`] 长
+    struct ArchiveService;
+
+    impl WebService for ArchiveService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/archive/slow", web::get().to(Self::slow));
+        }
+
+        fn request_timeout(&self) -> Option<Duration> {
+            Some(Duration::from_millis(500))
+        }
+    }
+
+    impl ArchiveService {
+        async fn slow() -> HttpResponse {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            HttpResponse::Ok().finish()
+        }
+    }
+
+    static REPORT_SERVICE: ReportService = ReportService;
+    static PING_SERVICE: PingService = PingService;
+    static EXPORT_SERVICE: ExportService = ExportService;
+    static SEARCH_SERVICE: SearchService = SearchService;
+    static ARCHIVE_SERVICE: ArchiveService = ArchiveService;
+
+    #[actix_web::test]
+    async fn test_max_concurrent_serializes_requests() {
+        let app = init_service(App::new().configure(|cfg| {
+            mount(cfg, &REPORT_SERVICE);
+            mount(cfg, &PING_SERVICE);
+        }))
+        .await;
+
+        let started = Instant::now();
+        let (first, second) = futures_util::join!(
+            call_service(&app, TestRequest::get().uri("/reports/daily").to_request()),
+            call_service(&app, TestRequest::get().uri("/reports/daily").to_request()),
+        );
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(second.status(), StatusCode::OK);
+        // 第二个请求等待第一个完成后才执行
+        assert_eq!(MAX_IN_FLIGHT.load(Ordering::SeqCst), 1);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        // 限制范围外的服务照常挂载
+        let resp = call_service(&app, TestRequest::get().uri("/ping").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_rate_limit_and_saturation() {
+        let limits = ServiceLimits::new("/orders").with_rate_per_sec(1);
+        let app = init_service(
+            App::new()
+                .wrap(ServiceGuard::new("orders", limits))
+                .route("/orders", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let resp = call_service(&app, TestRequest::get().uri("/orders").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = call_service(&app, TestRequest::get().uri("/orders").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // 等待并发名额超时
+        let limits = ServiceLimits::new("/slow")
+            .with_max_concurrent(1)
+            .with_acquire_timeout(Duration::from_millis(20));
+        let app = init_service(
+            App::new()
+                .wrap(ServiceGuard::new("slow", limits))
+                .route("/slow", web::get().to(|| async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    HttpResponse::Ok().finish()
+                })),
+        )
+        .await;
+        let (first, second) = futures_util::join!(
+            call_service(&app, TestRequest::get().uri("/slow").to_request()),
+            call_service(&app, TestRequest::get().uri("/slow").to_request()),
+        );
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[actix_web::test]
+    async fn test_request_timeout() {
+        let app = init_service(App::new().configure(|cfg| {
+            mount(cfg, &EXPORT_SERVICE);
+            mount(cfg, &PING_SERVICE);
+        }))
+        .await;
+
+        let Err(err) = try_call_service(&app, TestRequest::get().uri("/exports/slow").to_request()).await else {
+            panic!("slow request should time out");
+        };
+        let resp = err.error_response();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], 50401);
+
+        // 超时的处理被取消，不会在后台继续执行
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(SLOW_FINISHED.load(Ordering::SeqCst), 0);
+
+        let resp = call_service(&app, TestRequest::get().uri("/exports/fast").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = call_service(&app, TestRequest::get().uri("/ping").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_request_timeout_without_limits() {
+        let app = init_service(App::new().configure(|cfg| {
+            mount(cfg, &PING_SERVICE);
+            mount_timeout_chain(cfg, vec![&SEARCH_SERVICE, &ARCHIVE_SERVICE]);
+        }))
+        .await;
+
+        let Err(err) = try_call_service(&app, TestRequest::get().uri("/search/slow").to_request()).await else {
+            panic!("slow request should time out");
+        };
+        assert_eq!(err.error_response().status(), StatusCode::GATEWAY_TIMEOUT);
+
+        // 内层服务的请求经过外层守卫，按内层服务的超时执行
+        let resp = call_service(&app, TestRequest::get().uri("/archive/slow").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // 之前挂载的服务不受影响，都没有匹配的路径返回 404
+        let resp = call_service(&app, TestRequest::get().uri("/ping").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = call_service(&app, TestRequest::get().uri("/missing").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_matches_path_prefix() {
+        let limits = ServiceLimits::new("/reports/");
+        assert!(limits.matches("/reports"));
+        assert!(limits.matches("/reports/daily"));
+        assert!(!limits.matches("/reportsx"));
+        assert!(ServiceLimits::new("").matches("/anything"));
+    }
+}
@@ -0,0 +1,179 @@
+//! **服务配置需求**
+//!
+//! 服务通过 [`WebService::required_config`] 声明运行所需的配置键，
+//! 启动时统一校验，一次报告所有服务缺失或无效的配置，避免上线后才在请求中失败。
+
+use crate::web_service::WebService;
+use rconfig::AppConfig;
+use serde_json::Value;
+use std::sync::Arc;
+
+type ValidatorFn = Arc<dyn Fn(&Value) -> Result<(), String> + Send + Sync>;
+
+/// **单个配置需求**
+#[derive(Clone)]
+pub struct ConfigRequirement {
+    /// 点分路径，如 `redis.host`，扩展配置可省略 `extensions.` 前缀
+    pub key: String,
+    /// 说明配置用途，出现在错误信息中
+    pub description: Option<String>,
+    validator: Option<ValidatorFn>,
+}
+
+impl ConfigRequirement {
+    /// 要求配置键存在且不为空
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            description: None,
+            validator: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// 额外校验配置值，返回的错误信息会附带在报告中
+    pub fn with_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// 检查配置，不满足时返回问题描述
+    pub fn check(&self, config: &AppConfig) -> Result<(), String> {
+        let value = match config.get_value(&self.key) {
+            Some(Value::String(s)) if s.trim().is_empty() => None,
+            value => value,
+        };
+
+        let result = match (value, &self.validator) {
+            (None, _) => Err(format!("缺少配置 {}", self.key)),
+            (Some(value), Some(validator)) => {
+                validator(&value).map_err(|e| format!("配置 {} 无效: {}", self.key, e))
+            }
+            (Some(_), None) => Ok(()),
+        };
+
+        match &self.description {
+            Some(description) => result.map_err(|e| format!("{} ({})", e, description)),
+            None => result,
+        }
+    }
+}
+
+impl std::fmt::Debug for ConfigRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigRequirement")
+            .field("key", &self.key)
+            .field("description", &self.description)
+            .field("has_validator", &self.validator.is_some())
+            .finish()
+    }
+}
+
+/// **服务配置校验失败**，包含所有服务的全部问题
+#[derive(Debug, thiserror::Error)]
+#[error("服务配置校验失败: {}", .problems.join("; "))]
+pub struct ConfigRequirementError {
+    /// 每项形如 `[服务名] 缺少配置 redis.host`
+    pub problems: Vec<String>,
+}
+
+/// 按各服务声明的需求校验配置
+pub fn check_requirements<'a>(
+    services: impl IntoIterator<Item = &'a dyn WebService>,
+    config: &AppConfig,
+) -> Result<(), ConfigRequirementError> {
+    let problems: Vec<String> = services
+        .into_iter()
+        .flat_map(|service| {
+            service
+                .required_config()
+                .into_iter()
+                .filter_map(|requirement| requirement.check(config).err())
+                .map(|problem| format!("[{}] {}", service.name(), problem))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigRequirementError { problems })
+    }
+}
+
+/// 校验所有通过 #[service] 注册的服务
+pub fn check_registered(config: &AppConfig) -> Result<(), ConfigRequirementError> {
+    check_requirements(inventory::iter::<&dyn WebService>.into_iter().copied(), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::web;
+    use serde_json::json;
+
+    struct CacheService;
+
+    impl WebService for CacheService {
+        fn configure(&self, _cfg: &mut web::ServiceConfig) {}
+
+        fn required_config(&self) -> Vec<ConfigRequirement> {
+            vec![
+                ConfigRequirement::new("redis.host").with_description("缓存服务器"),
+                ConfigRequirement::new("cache.ttl_secs").with_validator(|value| match value.as_u64() {
+                    Some(ttl) if ttl > 0 => Ok(()),
+                    _ => Err("必须是正整数".to_string()),
+                }),
+            ]
+        }
+    }
+
+    struct NotifyService;
+
+    impl WebService for NotifyService {
+        fn configure(&self, _cfg: &mut web::ServiceConfig) {}
+
+        fn required_config(&self) -> Vec<ConfigRequirement> {
+            vec![ConfigRequirement::new("notify.callback_url")]
+        }
+    }
+
+    fn config(value: Value) -> AppConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_missing_keys_reported_together() {
+        let config = config(json!({
+            "extensions": { "cache": { "ttl_secs": 0 }, "notify": { "callback_url": "" } }
+        }));
+
+        let services: [&dyn WebService; 2] = [&CacheService, &NotifyService];
+        let err = check_requirements(services, &config).unwrap_err();
+
+        assert_eq!(err.problems.len(), 3);
+        assert!(err.problems[0].contains("CacheService"));
+        assert!(err.problems[0].contains("缺少配置 redis.host (缓存服务器)"));
+        assert!(err.problems[1].contains("配置 cache.ttl_secs 无效: 必须是正整数"));
+        assert!(err.problems[2].contains("NotifyService"));
+        assert!(err.problems[2].contains("缺少配置 notify.callback_url"));
+    }
+
+    #[test]
+    fn test_requirements_satisfied() {
+        let config = config(json!({
+            "redis": { "host": "127.0.0.1" },
+            "extensions": { "cache": { "ttl_secs": 60 }, "notify": { "callback_url": "https://example.com" } }
+        }));
+
+        let services: [&dyn WebService; 2] = [&CacheService, &NotifyService];
+        assert!(check_requirements(services, &config).is_ok());
+    }
+}
@@ -0,0 +1,154 @@
+//! **服务共享状态**
+//! - [`State`] 按类型存放各服务共用的资源（数据库连接池、Redis、配置等），
+//!   由 [`WebServer::with_state`](crate::web_service::WebServer::with_state) 传入，
+//!   [`mount_all`](crate::web_service::mount_all) 注册到每个服务所在的应用中。
+//! - 处理函数直接以 [`State`] 作为参数，按类型读取需要的资源，不需要每个应用定义自己的 `AppState`：
+//!
+//! ```ignore
+//! let state = State::new().with(pool).with(redis).with(config);
+//! WebServer::new(8080).with_state(state).start().await?;
+//!
+//! async fn list_orders(state: State) -> WebResult<HttpResponse> {
+//!     let pool = state.require::<MySqlPool>()?;
+//!     ...
+//! }
+//! ```
+//!
+//! 每种类型只保存一个值，重复插入时覆盖；需要多个同类型资源时用新类型包装区分。
+
+use crate::error::WebError;
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpRequest};
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+type Entries = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// **按类型存放的共享资源**，克隆开销很小，克隆后共享同一份资源
+#[derive(Clone, Default)]
+pub struct State {
+    entries: Arc<Entries>,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 存入 `value`，同类型的旧值被替换
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        Arc::make_mut(&mut self.entries).insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// 链式存入
+    pub fn with<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.insert(value);
+        self
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.entries.get(&TypeId::of::<T>()).and_then(|value| value.downcast_ref())
+    }
+
+    /// 读取必需的资源，未注册时返回内部错误
+    pub fn require<T: Send + Sync + 'static>(&self) -> Result<&T, WebError> {
+        self.get()
+            .ok_or_else(|| WebError::Internal(anyhow::anyhow!("共享状态中没有 {}", type_name::<T>())))
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.entries.contains_key(&TypeId::of::<T>())
+    }
+}
+
+impl std::fmt::Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("State").field("entries", &self.entries.len()).finish()
+    }
+}
+
+impl FromRequest for State {
+    type Error = WebError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    /// 读取 [`mount_all`](crate::web_service::mount_all) 注册的状态，未注册时返回内部错误
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.app_data::<State>()
+                .cloned()
+                .ok_or_else(|| WebError::Internal(anyhow::anyhow!("应用未注册共享状态"))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web_service::{mount, mount_all, WebService};
+    use actix_web::test::{call_service, init_service, read_body, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 两个服务共用的计数器
+    #[derive(Default)]
+    struct Visits(AtomicUsize);
+
+    struct Greeting(&'static str);
+
+    struct OrderService;
+
+    impl WebService for OrderService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/orders", web::get().to(|state: State| async move {
+                let visits = state.require::<Visits>()?;
+                visits.0.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, WebError>(HttpResponse::Ok().body(state.require::<Greeting>()?.0))
+            }));
+        }
+    }
+
+    struct UserService;
+
+    impl WebService for UserService {
+        fn configure(&self, cfg: &mut web::ServiceConfig) {
+            cfg.route("/users", web::get().to(|state: State| async move {
+                let visits = state.require::<Visits>()?.0.fetch_add(1, Ordering::SeqCst) + 1;
+                // 未注册的类型
+                assert!(state.get::<String>().is_none());
+                Ok::<_, WebError>(HttpResponse::Ok().body(visits.to_string()))
+            }));
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_state_shared_across_services() {
+        let state = State::new().with(Visits::default()).with(Greeting("hello"));
+        let app = init_service(App::new().configure(|cfg| {
+            mount_all(cfg, &state);
+            mount(cfg, &OrderService);
+            mount(cfg, &UserService);
+        }))
+        .await;
+
+        let resp = call_service(&app, TestRequest::get().uri("/orders").to_request()).await;
+        assert_eq!(read_body(resp).await, "hello");
+        let resp = call_service(&app, TestRequest::get().uri("/users").to_request()).await;
+        assert_eq!(read_body(resp).await, "2");
+
+        // 应用外持有的状态与服务中读到的是同一份
+        assert_eq!(state.require::<Visits>().unwrap().0.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_insert_replaces_same_type() {
+        let mut state = State::new().with(1u32);
+        let shared = state.clone();
+        state.insert(2u32);
+        assert_eq!(state.get::<u32>(), Some(&2));
+        // 已克隆的状态不受之后的插入影响
+        assert_eq!(shared.get::<u32>(), Some(&1));
+        assert!(state.require::<u64>().is_err());
+    }
+}
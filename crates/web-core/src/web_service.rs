@@ -16,12 +16,52 @@ use std::sync::{Arc, RwLock};
 use std::task::{Context, Poll};
 use lazy_static::lazy_static;
 use sakura_macros::service;
+use crate::background::{BackgroundScheduler, BackgroundTask};
+use crate::error::WebError;
+use crate::limits::{ServiceGuard, ServiceLimits};
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::requirements::{check_registered, ConfigRequirement};
+use crate::state::State;
+use rconfig::AppConfig;
 
 
 /** **WebService Trait** */
 pub trait WebService: Send + Sync {
     fn configure(&self, cfg: &mut web::ServiceConfig);
 
+    /// 服务需要周期执行的后台任务，由 [`WebServer`] 启动时统一调度
+    fn background_tasks(&self) -> Vec<BackgroundTask> {
+        Vec::new()
+    }
+
+    /// 服务运行所需的配置键，[`WebServer`] 启动时统一校验
+    fn required_config(&self) -> Vec<ConfigRequirement> {
+        Vec::new()
+    }
+
+    /// 服务名称，用于日志和配置校验报告
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// 服务的并发数和请求速率限制，由 [`mount_all`] 统一执行
+    fn limits(&self) -> Option<ServiceLimits> {
+        None
+    }
+
+    /// 单个请求的最长处理时间，超时后取消处理并返回 504，由 [`mount_all`] 统一执行
+    ///
+    /// 声明了 [`limits`](WebService::limits) 时作用于其路径前缀，否则作用于服务自己的所有路由
+    fn request_timeout(&self) -> Option<Duration> {
+        None
+    }
+}
+
+lazy_static! {
+    // mount_all 在每个 worker 中执行，同一服务的并发名额需要在 worker 之间共享
+    static ref SERVICE_GUARDS: std::sync::Mutex<HashMap<&'static str, ServiceGuard>> =
+        std::sync::Mutex::new(HashMap::new());
 }
 
 
@@ -35,10 +75,98 @@ pub trait WebService: Send + Sync {
 //     SERVICES.write().unwrap().push(service);
 // }
 
+/// **挂载所有通过 #[service] 注册的服务**
+///
+/// 框架提取 JSON、路径和查询参数失败时同样转换为 [`WebError::BadRequest`]，与服务返回的错误格式一致；
+/// `state` 注册为应用数据，所有服务的处理函数都可以提取 [`State`]
+pub fn mount_all(cfg: &mut web::ServiceConfig, state: &State) {
+    let service_count = inventory::iter::<&dyn WebService>().count();
+    // 每个 worker 都会调用一次，只在 debug 级别输出
+    tracing::debug!("Mounting {} web services", service_count);
+
+    cfg.app_data(web::JsonConfig::default().error_handler(|err, _| WebError::BadRequest(err.to_string()).into()))
+        .app_data(web::PathConfig::default().error_handler(|err, _| WebError::BadRequest(err.to_string()).into()))
+        .app_data(web::QueryConfig::default().error_handler(|err, _| WebError::BadRequest(err.to_string()).into()))
+        .app_data(state.clone());
+
+    // 只声明了超时的服务在其他服务之后挂载，见 [`mount_timeout_chain`]
+    let mut timeout_only = Vec::new();
+    for service in inventory::iter::<&dyn WebService>.into_iter() {
+        if service.limits().is_none() && service.request_timeout().is_some() {
+            timeout_only.push(*service);
+        } else {
+            mount(cfg, *service);
+        }
+    }
+    mount_timeout_chain(cfg, timeout_only);
+}
+
+/// 挂载单个服务，声明了资源限制的服务挂载在只匹配其路径前缀的 scope 中，由 [`ServiceGuard`] 执行限制和超时
+///
+/// 只声明了超时的服务没有路径前缀，挂载在空前缀的 scope 中，会接住之后挂载的服务的路径，需要最后挂载
+pub fn mount(cfg: &mut web::ServiceConfig, service: &'static dyn WebService) {
+    let limits = match service.limits() {
+        Some(limits) => limits,
+        None if service.request_timeout().is_some() => {
+            mount_timeout_chain(cfg, vec![service]);
+            return;
+        }
+        None => {
+            service.configure(cfg);
+            return;
+        }
+    };
+
+    let guard = service_guard(service, &limits);
+
+    // scope 前缀为空，服务的路由保持原样；守卫保证其他路径不会进入该 scope
+    cfg.service(
+        web::scope("")
+            .guard(actix_web::guard::fn_guard(move |ctx| limits.matches(ctx.head().uri.path())))
+            .wrap(guard)
+            .configure(|cfg| service.configure(cfg)),
+    );
+}
+
+/// 挂载只声明了超时、没有声明限制的服务
+///
+/// 没有路径前缀就无法用守卫限定 scope 的范围，这些服务依次嵌套在空前缀的 scope 中：外层服务没有匹配的请求
+/// 交给内层服务，都没有匹配时返回 404。经过多层超时守卫的请求按最内层（实际处理请求的服务）的超时执行
+pub(crate) fn mount_timeout_chain(cfg: &mut web::ServiceConfig, services: Vec<&'static dyn WebService>) {
+    let Some((&service, rest)) = services.split_first() else {
+        return;
+    };
+    let rest = rest.to_vec();
+    let guard = service_guard(service, &ServiceLimits::new(""));
+
+    cfg.service(
+        web::scope("")
+            .wrap(guard)
+            .configure(move |cfg| {
+                service.configure(cfg);
+                mount_timeout_chain(cfg, rest);
+            }),
+    );
+}
+
+/// 服务的 [`ServiceGuard`]，同一服务在所有 worker 间共享
+fn service_guard(service: &'static dyn WebService, limits: &ServiceLimits) -> ServiceGuard {
+    SERVICE_GUARDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(service.name())
+        .or_insert_with(|| ServiceGuard::new(service.name(), limits.clone()).with_request_timeout(service.request_timeout()))
+        .clone()
+}
+
 /// **通用 Web 服务器**
 pub struct WebServer {
     // services: Vec<Arc<dyn WebService>>,
     port: u16,
+    /// 设置后启动前校验各服务声明的配置需求
+    config: Option<AppConfig>,
+    /// 所有服务共享的资源
+    state: State,
     stop_signal: Arc<Mutex<Option<oneshot::Sender<()>>>>,
 }
 
@@ -49,28 +177,49 @@ impl WebServer {
         Self {
             // services,
             port,
+            config: None,
+            state: State::new(),
             stop_signal: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// **设置应用配置**，启动时据此校验所有服务的配置需求
+    pub fn with_config(mut self, config: AppConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// **设置共享状态**，各服务通过 [`State`] 提取
+    pub fn with_state(mut self, state: State) -> Self {
+        self.state = state;
+        self
+    }
+
     /// **启动服务器**
     pub async fn start(&self) -> std::io::Result<()> {
+        // 任一服务缺少配置时不启动，一次报告全部问题
+        if let Some(config) = &self.config {
+            check_registered(config).map_err(|e| {
+                error!("{}", e);
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, e)
+            })?;
+        }
+
         // let services = self.services.clone();
         let port = self.port;
+        let state = self.state.clone();
         let (tx, rx) = oneshot::channel();
         *self.stop_signal.lock().await = Some(tx);
 
+        // mount_all 会在每个 worker 中执行，后台任务只在这里启动一份
+        let scheduler = BackgroundScheduler::spawn_registered();
+
         HttpServer::new(move || {
             let mut app = App::new()
                 .wrap(Logger::default())  // 请求日志
                 .wrap(NormalizePath::trim()); // 处理 URL 末尾斜杠
 
-            let service_count = inventory::iter::<&dyn WebService>().count();
-            println!("service_count:{}", service_count);
-
-            for service in inventory::iter::<&dyn WebService>.into_iter() {
-                app = app.configure(|cfg| service.configure(cfg));
-            }
+            app = app.configure(|cfg| mount_all(cfg, &state));
 
             // app.wrap(AuthMiddleware) // JWT 认证
             app
@@ -84,6 +233,7 @@ impl WebServer {
         // 等待 stop 信号
         let _ = rx.await;
         info!("🛑 Server is shutting down...");
+        scheduler.shutdown().await;
         Ok(())
     }
 
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+#[cfg(test)]
+use chrono::Duration;
+#[cfg(test)]
+use std::sync::Mutex;
+
+/// 时间来源
+///
+/// 服务中与时间相关的逻辑（订单过期、回调时间戳等）统一从这里取当前时间，便于测试中控制时间
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// 系统时钟
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// 可手动拨动的时钟，用于测试
+#[cfg(test)]
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    /// 设置当前时间
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// 时间向前推进
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_advance() {
+        let start = Utc::now();
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::minutes(30));
+        assert_eq!(clock.now(), start + Duration::minutes(30));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
@@ -0,0 +1,387 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+use crate::error::PaymentError;
+use crate::models::enums::PaymentType;
+
+/// 支付渠道展示配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    pub display_name: String,
+    pub logo_url: Option<String>,
+    /// 支持的货币代码，如 `CNY`
+    pub currencies: Vec<String>,
+    /// 渠道下可用的支付方式
+    pub methods: Vec<PaymentType>,
+    pub enabled: bool,
+    /// 允许发送回调通知的地址段，为空时不限制
+    #[serde(default)]
+    pub callback_ips: Vec<IpCidr>,
+}
+
+/// 配置文件中的渠道配置，未填写的字段沿用默认值
+#[derive(Debug, Default, Deserialize)]
+struct ChannelOverride {
+    display_name: Option<String>,
+    logo_url: Option<String>,
+    currencies: Option<Vec<String>>,
+    methods: Option<Vec<PaymentType>>,
+    enabled: Option<bool>,
+    callback_ips: Option<Vec<IpCidr>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChannelsFile {
+    #[serde(default)]
+    payment: PaymentSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PaymentSection {
+    #[serde(default)]
+    channels: BTreeMap<String, ChannelOverride>,
+    #[serde(default)]
+    trusted_proxies: Vec<IpCidr>,
+}
+
+/// 地址段，如 `10.0.0.0/8`、`2001:db8::/32`，不带前缀长度时表示单个地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    /// 是否包含该地址，IPv4 映射的 IPv6 地址按 IPv4 比较
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = match s.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (s, None),
+        };
+        let network = address
+            .trim()
+            .parse::<IpAddr>()
+            .map_err(|e| format!("无效的地址段 {}: {}", s, e))?
+            .to_canonical();
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max_prefix)
+                .ok_or_else(|| format!("无效的地址段 {}: 前缀长度应在 0-{} 之间", s, max_prefix))?,
+            None => max_prefix,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+impl TryFrom<String> for IpCidr {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<IpCidr> for String {
+    fn from(value: IpCidr) -> Self {
+        value.to_string()
+    }
+}
+
+/// 可用的支付渠道
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AvailableChannel {
+    pub name: String,
+    pub display_name: String,
+    pub logo_url: Option<String>,
+    pub methods: Vec<PaymentType>,
+}
+
+/// 支付渠道配置，对应配置文件中的 `payment.channels.<name>`
+///
+/// 新增或停用渠道只需修改配置，不需要改代码
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelsConfig {
+    channels: BTreeMap<String, ChannelConfig>,
+    /// 可信的反向代理地址段，对应 `payment.trusted_proxies`
+    ///
+    /// 只有直连地址属于这些地址段时才读取 `X-Forwarded-For`，为空时始终使用连接地址
+    trusted_proxies: Vec<IpCidr>,
+}
+
+impl Default for ChannelsConfig {
+    fn default() -> Self {
+        let channel = |display_name: &str, currencies: &[&str], methods: &[PaymentType]| ChannelConfig {
+            display_name: display_name.to_string(),
+            logo_url: None,
+            currencies: currencies.iter().map(|c| c.to_string()).collect(),
+            methods: methods.to_vec(),
+            enabled: true,
+            callback_ips: Vec::new(),
+        };
+
+        let mut channels = BTreeMap::new();
+        channels.insert("wechat".to_string(), channel("微信支付", &["CNY"], &[PaymentType::WxH5, PaymentType::WxSdk]));
+        channels.insert("alipay".to_string(), channel("支付宝", &["CNY"], &[PaymentType::ZfbH5, PaymentType::ZfbSdk]));
+        channels.insert(
+            "unionpay".to_string(),
+            channel("银联", &["CNY"], &[PaymentType::UnionPayH5, PaymentType::UnionPaySdk, PaymentType::ScanPayUnion]),
+        );
+        channels.insert(
+            "apple".to_string(),
+            channel("Apple Pay", &["CNY", "USD", "EUR", "GBP", "JPY"], &[PaymentType::AppleIap]),
+        );
+
+        Self { channels, trusted_proxies: Vec::new() }
+    }
+}
+
+impl ChannelsConfig {
+    /// 解析 JSON 配置并叠加到默认渠道上
+    pub fn from_json(content: &str) -> Result<Self, PaymentError> {
+        let file: ChannelsFile = serde_json::from_str(content)
+            .map_err(|e| PaymentError::Configuration(format!("支付渠道配置解析失败: {}", e)))?;
+
+        let mut config = Self { trusted_proxies: file.payment.trusted_proxies, ..Self::default() };
+        for (name, overrides) in file.payment.channels {
+            match config.channels.get_mut(&name) {
+                Some(channel) => {
+                    if let Some(display_name) = overrides.display_name {
+                        channel.display_name = display_name;
+                    }
+                    if overrides.logo_url.is_some() {
+                        channel.logo_url = overrides.logo_url;
+                    }
+                    if let Some(currencies) = overrides.currencies {
+                        channel.currencies = currencies;
+                    }
+                    if let Some(methods) = overrides.methods {
+                        channel.methods = methods;
+                    }
+                    if let Some(enabled) = overrides.enabled {
+                        channel.enabled = enabled;
+                    }
+                    if let Some(callback_ips) = overrides.callback_ips {
+                        channel.callback_ips = callback_ips;
+                    }
+                }
+                None => {
+                    // 新渠道需要完整配置
+                    let channel = ChannelConfig {
+                        display_name: overrides.display_name.unwrap_or_else(|| name.clone()),
+                        logo_url: overrides.logo_url,
+                        currencies: overrides.currencies.unwrap_or_default(),
+                        methods: overrides.methods.unwrap_or_default(),
+                        enabled: overrides.enabled.unwrap_or(true),
+                        callback_ips: overrides.callback_ips.unwrap_or_default(),
+                    };
+                    config.channels.insert(name, channel);
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// 从 JSON 文件加载
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, PaymentError> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| PaymentError::Configuration(format!("读取支付渠道配置失败: {} ({})", path.as_ref().display(), e)))?;
+        Self::from_json(&content)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChannelConfig> {
+        self.channels.get(name)
+    }
+
+    /// 支付方式所属的渠道是否已启用，不属于任何渠道的支付方式视为未启用
+    pub fn is_method_enabled(&self, payment_type: PaymentType) -> bool {
+        self.channels
+            .values()
+            .any(|channel| channel.enabled && channel.methods.contains(&payment_type))
+    }
+
+    /// 回调请求的客户端地址
+    ///
+    /// 直连地址不是可信代理时，`X-Forwarded-For` 可被任意伪造，直接使用连接地址；
+    /// 否则从右向左跳过可信代理追加的地址，第一个不属于可信代理的地址即为客户端
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        let is_trusted = |ip: IpAddr| self.trusted_proxies.iter().any(|cidr| cidr.contains(ip));
+        let mut client = peer;
+        let Some(forwarded_for) = forwarded_for else {
+            return client;
+        };
+
+        for hop in forwarded_for.rsplit(',') {
+            if !is_trusted(client) {
+                break;
+            }
+            // 无法解析的地址之后的内容不可信，停在最后一个可信代理上
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => client = ip,
+                Err(_) => break,
+            }
+        }
+        client
+    }
+
+    /// 支付方式所属的渠道是否允许该地址发送回调，渠道未配置 `callback_ips` 时不限制
+    ///
+    /// 没有渠道包含该支付方式时拒绝，未配置的支付方式不接受回调
+    pub fn is_callback_ip_allowed(&self, payment_type: PaymentType, ip: IpAddr) -> bool {
+        let mut channels = self
+            .channels
+            .values()
+            .filter(|channel| channel.methods.contains(&payment_type))
+            .peekable();
+        channels.peek().is_some()
+            && channels.all(|channel| channel.callback_ips.is_empty() || channel.callback_ips.iter().any(|cidr| cidr.contains(ip)))
+    }
+
+    /// 指定货币下已启用的渠道
+    pub fn available_channels(&self, currency: &str) -> Vec<AvailableChannel> {
+        self.channels
+            .iter()
+            .filter(|(_, channel)| {
+                channel.enabled && channel.currencies.iter().any(|c| c.eq_ignore_ascii_case(currency))
+            })
+            .map(|(name, channel)| AvailableChannel {
+                name: name.clone(),
+                display_name: channel.display_name.clone(),
+                logo_url: channel.logo_url.clone(),
+                methods: channel.methods.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(channels: &[AvailableChannel]) -> Vec<&str> {
+        channels.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_default_channels() {
+        let config = ChannelsConfig::default();
+        assert_eq!(names(&config.available_channels("CNY")), vec!["alipay", "apple", "unionpay", "wechat"]);
+        assert_eq!(names(&config.available_channels("USD")), vec!["apple"]);
+    }
+
+    #[test]
+    fn test_disabled_channel_not_available() {
+        let config = ChannelsConfig::from_json(r#"{
+            "payment": {
+                "channels": {
+                    "alipay": { "enabled": false },
+                    "wechat": { "logo_url": "https://cdn.example.com/wechat.png" },
+                    "paypal": { "display_name": "PayPal", "currencies": ["USD"], "methods": ["PAYPAL_H5"] }
+                }
+            }
+        }"#).unwrap();
+
+        let available = config.available_channels("CNY");
+        assert_eq!(names(&available), vec!["apple", "unionpay", "wechat"]);
+
+        // 只覆盖填写的字段
+        let wechat = config.get("wechat").unwrap();
+        assert_eq!(wechat.logo_url.as_deref(), Some("https://cdn.example.com/wechat.png"));
+        assert_eq!(wechat.methods, vec![PaymentType::WxH5, PaymentType::WxSdk]);
+
+        // 配置新增的渠道
+        assert_eq!(names(&config.available_channels("USD")), vec!["apple", "paypal"]);
+
+        assert!(!config.is_method_enabled(PaymentType::ZfbH5));
+        assert!(config.is_method_enabled(PaymentType::WxH5));
+    }
+
+    fn callback_config() -> ChannelsConfig {
+        ChannelsConfig::from_json(r#"{
+            "payment": {
+                "trusted_proxies": ["10.0.0.0/8"],
+                "channels": {
+                    "wechat": { "callback_ips": ["101.226.103.0/25", "2408:8000::/32"] }
+                }
+            }
+        }"#).unwrap()
+    }
+
+    #[test]
+    fn test_callback_ip_allowlist() {
+        let config = callback_config();
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+        assert!(config.is_callback_ip_allowed(PaymentType::WxH5, ip("101.226.103.15")));
+        assert!(config.is_callback_ip_allowed(PaymentType::WxSdk, ip("::ffff:101.226.103.15")));
+        assert!(config.is_callback_ip_allowed(PaymentType::WxH5, ip("2408:8000:1::1")));
+        assert!(!config.is_callback_ip_allowed(PaymentType::WxH5, ip("101.226.103.200")));
+        assert!(!config.is_callback_ip_allowed(PaymentType::WxH5, ip("203.0.113.9")));
+
+        // 未配置白名单的渠道不限制
+        assert!(config.is_callback_ip_allowed(PaymentType::ZfbH5, ip("203.0.113.9")));
+
+        // 没有渠道包含的支付方式拒绝回调
+        assert!(!config.is_callback_ip_allowed(PaymentType::ShoufaWxH5, ip("101.226.103.15")));
+        assert!(!config.is_callback_ip_allowed(PaymentType::ShoufaWxH5, ip("203.0.113.9")));
+    }
+
+    #[test]
+    fn test_client_ip_from_trusted_proxy_only() {
+        let config = callback_config();
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+        // 经可信代理转发时取代理前的客户端地址
+        let client = config.client_ip(ip("10.1.2.3"), Some("203.0.113.9, 101.226.103.15, 10.0.0.5"));
+        assert_eq!(client, ip("101.226.103.15"));
+        assert!(config.is_callback_ip_allowed(PaymentType::WxH5, client));
+
+        // 直连请求伪造的请求头被忽略
+        let client = config.client_ip(ip("203.0.113.9"), Some("101.226.103.15"));
+        assert_eq!(client, ip("203.0.113.9"));
+        assert!(!config.is_callback_ip_allowed(PaymentType::WxH5, client));
+
+        // 无法解析的地址之后不再信任
+        assert_eq!(config.client_ip(ip("10.1.2.3"), Some("101.226.103.15, unknown")), ip("10.1.2.3"));
+    }
+
+    #[test]
+    fn test_invalid_cidr() {
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("not-an-ip".parse::<IpCidr>().is_err());
+        assert_eq!("10.1.2.3/8".parse::<IpCidr>().unwrap().to_string(), "10.1.2.3/8");
+        assert!(ChannelsConfig::from_json(r#"{"payment": {"channels": {"wechat": {"callback_ips": ["1.2.3.4/40"]}}}}"#).is_err());
+    }
+}
@@ -7,6 +7,16 @@ pub struct AppSettings {
     pub server_port: u16,
     pub cache_ttl_seconds: u64,
     pub rate_limits: RateLimits,
+    /// 商户限流使用的 Redis，为空时使用进程内限流
+    pub redis_url: Option<String>,
+    /// 支付渠道配置文件（JSON），为空时使用内置的默认渠道
+    pub payment_channels_file: Option<String>,
+    /// 订单事件投递使用的 RabbitMQ，为空时不启动 outbox 分发
+    pub rabbitmq_url: Option<String>,
+    /// 订单事件发布的交换机
+    pub outbox_exchange: String,
+    /// 允许跨域调用的来源，逗号分隔；为空时不允许跨域（商户接口由服务端调用）
+    pub cors_allowed_origins: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -40,6 +50,20 @@ impl AppSettings {
                     .parse()
                     .unwrap_or(300),
             },
+            redis_url: std::env::var("REDIS_URL").ok(),
+            payment_channels_file: std::env::var("PAYMENT_CHANNELS_FILE").ok(),
+            rabbitmq_url: std::env::var("RABBITMQ_URL").ok(),
+            outbox_exchange: std::env::var("OUTBOX_EXCHANGE")
+                .unwrap_or_else(|_| "payment.events".to_string()),
+            cors_allowed_origins: std::env::var("CORS_ALLOWED_ORIGINS")
+                .map(|origins| {
+                    origins.split(',')
+                        .map(str::trim)
+                        .filter(|origin| !origin.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
         }
     }
 }
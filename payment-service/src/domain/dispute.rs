@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use common::int_enum;
+use serde::{Deserialize, Serialize};
+
+use super::money::Money;
+use crate::models::enums::PaymentType;
+
+int_enum! {
+    /// 争议（拒付）状态，名称即数据库中保存的状态
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub enum DisputeStatus {
+        /// 持卡人发起争议，等待商户应诉
+        Open = 0 => "OPEN",
+        /// 已提交材料，等待发卡行裁决
+        UnderReview = 1 => "UNDER_REVIEW",
+        /// 商户胜诉，资金退回商户
+        Won = 2 => "WON",
+        /// 商户败诉，资金已退还持卡人
+        Lost = 3 => "LOST",
+    }
+}
+
+impl DisputeStatus {
+    pub fn from_db(status: &str) -> Option<Self> {
+        status.parse().ok()
+    }
+
+    /// 争议未结束或商户败诉时冻结退款，避免资金被重复退还
+    pub fn blocks_refund(&self) -> bool {
+        !matches!(self, Self::Won)
+    }
+}
+
+/// 渠道争议通知解析后的内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisputeNotification {
+    /// 渠道侧争议ID，同一争议的后续通知使用相同ID
+    pub dispute_id: String,
+    /// 本系统订单号
+    pub order_id: String,
+    pub amount: Money,
+    pub reason: Option<String>,
+    pub status: DisputeStatus,
+}
+
+/// 订单上的争议记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dispute {
+    pub dispute_id: String,
+    pub order_id: String,
+    pub payment_type: PaymentType,
+    pub amount: Money,
+    pub reason: Option<String>,
+    pub status: DisputeStatus,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Dispute {
+    pub fn from_notification(payment_type: PaymentType, notification: DisputeNotification, now: DateTime<Utc>) -> Self {
+        Self {
+            dispute_id: notification.dispute_id,
+            order_id: notification.order_id,
+            payment_type,
+            amount: notification.amount,
+            reason: notification.reason,
+            status: notification.status,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispute_status_db_round_trip() {
+        for &status in DisputeStatus::all() {
+            assert_eq!(DisputeStatus::from_db(status.as_str()), Some(status));
+        }
+        assert_eq!(DisputeStatus::from_db("CLOSED"), None);
+
+        assert!(DisputeStatus::Open.blocks_refund());
+        assert!(DisputeStatus::Lost.blocks_refund());
+        assert!(!DisputeStatus::Won.blocks_refund());
+    }
+}
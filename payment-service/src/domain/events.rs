@@ -23,28 +23,124 @@ pub enum PaymentEvent {
         error_reason: String,
         failed_at: DateTime<Utc>,
     },
+    /// 预授权成功，额度已冻结
+    PaymentAuthorized {
+        order_id: String,
+        third_party_order_id: String,
+        authorized_at: DateTime<Utc>,
+    },
+    /// 开始捕获预授权，请求渠道前记录捕获金额
+    CaptureRequested {
+        order_id: String,
+        capture_amount: i64,
+        requested_at: DateTime<Utc>,
+    },
+    /// 捕获预授权，按捕获金额扣款
+    PaymentCaptured {
+        order_id: String,
+        capture_amount: i64,
+        captured_at: DateTime<Utc>,
+    },
+    /// 撤销预授权，释放冻结的额度
+    AuthorizationVoided {
+        order_id: String,
+        voided_at: DateTime<Utc>,
+    },
     RefundRequested {
         order_id: String,
         refund_id: String,
         refund_amount: i64,
         requested_at: DateTime<Utc>,
     },
+    /// 部分退款，退款后订单仍有可退金额
+    PartialRefundRequested {
+        order_id: String,
+        refund_id: String,
+        refund_amount: i64,
+        requested_at: DateTime<Utc>,
+    },
     RefundCompleted {
         order_id: String,
         refund_id: String,
         completed_at: DateTime<Utc>,
     },
+    OrderClosed {
+        order_id: String,
+        closed_at: DateTime<Utc>,
+    },
+}
+
+/// 订单事件写入 outbox 后的记录，由分发器投递给下游系统（账务、分析等）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub order_id: String,
+    /// 事件类型，同时作为消息的路由键，如 `PaymentCompleted`
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 待投递给商户的支付结果通知，与订单变更在同一事务中写入，由后台任务投递并按退避间隔重试
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerchantNotification {
+    pub id: i64,
+    pub order_id: String,
+    /// 已失败的投递次数
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
 }
 
 impl PaymentEvent {
+    /// 事件类型名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OrderCreated { .. } => "OrderCreated",
+            Self::PaymentInitiated { .. } => "PaymentInitiated",
+            Self::PaymentCompleted { .. } => "PaymentCompleted",
+            Self::PaymentFailed { .. } => "PaymentFailed",
+            Self::PaymentAuthorized { .. } => "PaymentAuthorized",
+            Self::CaptureRequested { .. } => "CaptureRequested",
+            Self::PaymentCaptured { .. } => "PaymentCaptured",
+            Self::AuthorizationVoided { .. } => "AuthorizationVoided",
+            Self::RefundRequested { .. } => "RefundRequested",
+            Self::PartialRefundRequested { .. } => "PartialRefundRequested",
+            Self::RefundCompleted { .. } => "RefundCompleted",
+            Self::OrderClosed { .. } => "OrderClosed",
+        }
+    }
+
+    /// 事件是否改变了商户关心的支付结果，需要通知商户
+    pub fn notifies_merchant(&self) -> bool {
+        matches!(
+            self,
+            Self::PaymentCompleted { .. }
+                | Self::PaymentFailed { .. }
+                | Self::PaymentAuthorized { .. }
+                | Self::PaymentCaptured { .. }
+                | Self::AuthorizationVoided { .. }
+        )
+    }
+
+    /// 事件是否表示扣款成功，需要为商户入账
+    pub fn settles_payment(&self) -> bool {
+        matches!(self, Self::PaymentCompleted { .. } | Self::PaymentCaptured { .. })
+    }
+
     pub fn order_id(&self) -> &str {
         match self {
             Self::OrderCreated { order_id, .. } => order_id,
             Self::PaymentInitiated { order_id, .. } => order_id,
             Self::PaymentCompleted { order_id, .. } => order_id,
             Self::PaymentFailed { order_id, .. } => order_id,
+            Self::PaymentAuthorized { order_id, .. } => order_id,
+            Self::CaptureRequested { order_id, .. } => order_id,
+            Self::PaymentCaptured { order_id, .. } => order_id,
+            Self::AuthorizationVoided { order_id, .. } => order_id,
             Self::RefundRequested { order_id, .. } => order_id,
+            Self::PartialRefundRequested { order_id, .. } => order_id,
             Self::RefundCompleted { order_id, .. } => order_id,
+            Self::OrderClosed { order_id, .. } => order_id,
         }
     }
 
@@ -54,8 +150,14 @@ impl PaymentEvent {
             Self::PaymentInitiated { initiated_at, .. } => *initiated_at,
             Self::PaymentCompleted { completed_at, .. } => *completed_at,
             Self::PaymentFailed { failed_at, .. } => *failed_at,
+            Self::PaymentAuthorized { authorized_at, .. } => *authorized_at,
+            Self::CaptureRequested { requested_at, .. } => *requested_at,
+            Self::PaymentCaptured { captured_at, .. } => *captured_at,
+            Self::AuthorizationVoided { voided_at, .. } => *voided_at,
             Self::RefundRequested { requested_at, .. } => *requested_at,
+            Self::PartialRefundRequested { requested_at, .. } => *requested_at,
             Self::RefundCompleted { completed_at, .. } => *completed_at,
+            Self::OrderClosed { closed_at, .. } => *closed_at,
         }
     }
 }
@@ -66,8 +168,17 @@ pub fn apply_event(current_status: OrderStatus, event: &PaymentEvent) -> Result<
         (OrderStatus::Pending, PaymentEvent::PaymentInitiated { .. }) => Ok(OrderStatus::Processing),
         (OrderStatus::Processing, PaymentEvent::PaymentCompleted { .. }) => Ok(OrderStatus::Success),
         (OrderStatus::Processing, PaymentEvent::PaymentFailed { .. }) => Ok(OrderStatus::Failed),
-        (OrderStatus::Success, PaymentEvent::RefundRequested { .. }) => Ok(OrderStatus::Refunded),
+        (OrderStatus::Processing, PaymentEvent::PaymentAuthorized { .. }) => Ok(OrderStatus::Authorized),
+        (OrderStatus::Authorized, PaymentEvent::CaptureRequested { .. }) => Ok(OrderStatus::Capturing),
+        (OrderStatus::Capturing, PaymentEvent::PaymentCaptured { .. }) => Ok(OrderStatus::Success),
+        (OrderStatus::Authorized, PaymentEvent::AuthorizationVoided { .. }) => Ok(OrderStatus::Closed),
+        (OrderStatus::Success | OrderStatus::PartialRefunded, PaymentEvent::RefundRequested { .. }) => Ok(OrderStatus::Refunded),
+        (OrderStatus::Success | OrderStatus::PartialRefunded, PaymentEvent::PartialRefundRequested { .. }) => {
+            Ok(OrderStatus::PartialRefunded)
+        }
         (OrderStatus::Refunded, PaymentEvent::RefundCompleted { .. }) => Ok(OrderStatus::Refunded),
+        (OrderStatus::PartialRefunded, PaymentEvent::RefundCompleted { .. }) => Ok(OrderStatus::PartialRefunded),
+        (OrderStatus::Pending | OrderStatus::Processing, PaymentEvent::OrderClosed { .. }) => Ok(OrderStatus::Closed),
         _ => Err("Invalid state transition"),
     }
 }
@@ -1,3 +1,5 @@
 pub mod payment;
 pub mod money;
-pub mod events;
\ No newline at end of file
+pub mod events;
+pub mod dispute;
+pub mod refund;
\ No newline at end of file
@@ -1,4 +1,6 @@
-use serde::{Serialize, Deserialize};
+use common::int_enum;
+use serde::{Serialize, Deserialize, Deserializer, Serializer};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Money {
@@ -7,15 +9,54 @@ pub struct Money {
     pub currency: Currency,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum Currency {
-    CNY,
-    USD,
-    EUR,
-    GBP,
-    JPY,
-    // 其他货币...
+int_enum! {
+    /// 货币，整数值为 ISO 4217 数字代码，名称为字母代码
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "UPPERCASE")]
+    pub enum Currency {
+        CNY = 156 => "CNY",
+        USD = 840 => "USD",
+        EUR = 978 => "EUR",
+        GBP = 826 => "GBP",
+        JPY = 392 => "JPY",
+        MYR = 458 => "MYR",
+        // 其他货币...
+    }
+}
+
+impl Currency {
+    /// ISO 4217 货币代码
+    pub fn code(&self) -> &'static str {
+        self.as_str()
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        code.parse().ok()
+    }
+
+    /// ISO 4217 数字代码，固定三位，如人民币为 `"156"`
+    pub fn numeric_code(&self) -> String {
+        format!("{:03}", self.value())
+    }
+
+    /// 最小单位的小数位数，例如人民币为2（分），日元为0
+    pub fn minor_units(&self) -> u32 {
+        match self {
+            Currency::JPY => 0,
+            _ => 2,
+        }
+    }
+
+    /// 展示用的货币符号
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::CNY | Currency::JPY => "¥",
+            Currency::USD => "$",
+            Currency::EUR => "€",
+            Currency::GBP => "£",
+            Currency::MYR => "RM",
+        }
+    }
 }
 
 impl Money {
@@ -31,6 +72,52 @@ impl Money {
         Self { amount, currency: Currency::USD }
     }
 
+    /// 解析以主单位表示的十进制金额，如 `"10.01"` 元
+    ///
+    /// 小数位数不能超过货币的最小单位（人民币 2 位、日元 0 位），超出时返回错误而不是截断
+    pub fn from_decimal(text: &str, currency: Currency) -> Result<Self, String> {
+        let invalid = || format!("金额格式无效: {}", text);
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+        let (units, fraction) = text.split_once('.').unwrap_or((text, ""));
+        if !is_digits(units) || (text.contains('.') && !is_digits(fraction)) {
+            return Err(invalid());
+        }
+        let minor_units = currency.minor_units() as usize;
+        if fraction.len() > minor_units {
+            return Err(format!(
+                "金额 {} 的小数位数超过 {} 的最小单位（{} 位）",
+                text, currency.code(), minor_units
+            ));
+        }
+
+        let fraction: i64 = format!("{:0<width$}", fraction, width = minor_units)
+            .parse()
+            .unwrap_or(0);
+        let amount = units.parse::<i64>().ok()
+            .and_then(|units| units.checked_mul(10i64.pow(minor_units as u32)))
+            .and_then(|amount| amount.checked_add(fraction))
+            .ok_or_else(invalid)?;
+        Ok(Self { amount, currency })
+    }
+
+    /// 按汇率换算为另一种货币，使用整数运算，结果四舍五入到目标货币的最小单位，溢出时返回 `None`
+    ///
+    /// `rate` 表示 1 单位当前货币可兑换的目标货币数量
+    pub fn convert(&self, to: Currency, rate: ExchangeRate) -> Option<Self> {
+        let numerator = i128::from(self.amount)
+            .checked_mul(i128::from(rate.scaled()))?
+            .checked_mul(10i128.pow(to.minor_units()))?;
+        let denominator = 10i128.pow(self.currency.minor_units() + ExchangeRate::SCALE);
+
+        // 四舍五入，远离零
+        let mut amount = numerator / denominator;
+        if (numerator % denominator).abs() * 2 >= denominator {
+            amount += numerator.signum();
+        }
+        Some(Self { amount: i64::try_from(amount).ok()?, currency: to })
+    }
+
     // 简单货币操作
     pub fn add(&self, other: &Self) -> Result<Self, &'static str> {
         if self.currency != other.currency {
@@ -53,6 +140,143 @@ impl Money {
             currency: self.currency,
         })
     }
+
+    /// 按地区习惯格式化，供前端直接展示，见 [`format_for_display`]
+    pub fn format_for_display(&self, locale: &str) -> String {
+        format_for_display(self.amount, self.currency, locale)
+    }
+}
+
+/// 汇率，按 [`SCALE`](ExchangeRate::SCALE) 位小数的定点整数保存，换算和存储都不经过浮点数
+///
+/// 序列化为十进制字符串，如 `"7.1"`；反序列化同时接受字符串和 JSON 数字，数字按其最短十进制表示解析
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExchangeRate(i64);
+
+impl ExchangeRate {
+    /// 小数位数
+    pub const SCALE: u32 = 8;
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(10i64.pow(Self::SCALE));
+
+    /// 以 10^-[`SCALE`](Self::SCALE) 为单位的整数值
+    pub fn from_scaled(scaled: i64) -> Self {
+        Self(scaled)
+    }
+
+    pub fn scaled(&self) -> i64 {
+        self.0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0 > 0
+    }
+}
+
+impl FromStr for ExchangeRate {
+    type Err = String;
+
+    /// 解析十进制汇率，如 `"7.1"`、`"0.0067"`，小数位数不能超过 [`SCALE`](Self::SCALE)
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("无效的汇率: {}", text);
+        let (units, fraction) = text.trim().split_once('.').unwrap_or((text.trim(), ""));
+        let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if units.is_empty() || !is_digits(units) || !is_digits(fraction) || fraction.len() > Self::SCALE as usize {
+            return Err(invalid());
+        }
+
+        let fraction: i64 = format!("{:0<width$}", fraction, width = Self::SCALE as usize)
+            .parse()
+            .map_err(|_| invalid())?;
+        units.parse::<i64>().ok()
+            .and_then(|units| units.checked_mul(10i64.pow(Self::SCALE)))
+            .and_then(|scaled| scaled.checked_add(fraction))
+            .map(Self)
+            .ok_or_else(invalid)
+    }
+}
+
+impl std::fmt::Display for ExchangeRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scale = 10i64.pow(Self::SCALE);
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let units = (self.0 / scale).unsigned_abs();
+        let fraction = format!("{:0width$}", (self.0 % scale).unsigned_abs(), width = Self::SCALE as usize);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, units)
+        } else {
+            write!(f, "{}{}.{}", sign, units, fraction)
+        }
+    }
+}
+
+impl Serialize for ExchangeRate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExchangeRate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Number(f64),
+        }
+
+        let text = match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => text,
+            Raw::Number(number) => number.to_string(),
+        };
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// 按地区习惯格式化金额（最小单位），如 `¥99.99`、`RM 10.00`、`$1,234.50`、`1.234,50 €`
+///
+/// 小数位数由货币决定；千分位、小数点和货币符号的位置由 `locale`（如 `zh-CN`、`de_DE`）的语言决定，
+/// 未识别的地区使用 `1,234.50` 的格式并把符号放在前面
+pub fn format_for_display(amount: i64, currency: Currency, locale: &str) -> String {
+    let (group, decimal, symbol_after) = locale_separators(locale);
+    let minor_units = currency.minor_units();
+    let scale = 10u64.pow(minor_units);
+    let abs = amount.unsigned_abs();
+
+    let digits = (abs / scale).to_string();
+    let mut number = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            number.push_str(group);
+        }
+        number.push(c);
+    }
+    if minor_units > 0 {
+        number.push_str(decimal);
+        number.push_str(&format!("{:0width$}", abs % scale, width = minor_units as usize));
+    }
+
+    let sign = if amount < 0 { "-" } else { "" };
+    let symbol = currency.symbol();
+    if symbol_after {
+        format!("{}{}\u{a0}{}", sign, number, symbol)
+    } else if symbol.chars().all(char::is_alphabetic) {
+        // 字母符号与数字之间留空格，如 `RM 10.00`
+        format!("{}{} {}", sign, symbol, number)
+    } else {
+        format!("{}{}{}", sign, symbol, number)
+    }
+}
+
+/// 地区的千分位分隔符、小数点，以及货币符号是否放在数字后面
+fn locale_separators(locale: &str) -> (&'static str, &'static str, bool) {
+    let language = locale.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    match language.as_str() {
+        "de" | "es" | "it" | "nl" | "pt" | "id" => (".", ",", true),
+        "fr" | "ru" | "pl" | "sv" | "fi" => ("\u{a0}", ",", true),
+        _ => (",", ".", false),
+    }
 }
 
 impl std::fmt::Display for Money {
@@ -63,6 +287,7 @@ impl std::fmt::Display for Money {
             Currency::EUR => write!(f, "€{:.2}", self.amount as f64 / 100.0),
             Currency::GBP => write!(f, "£{:.2}", self.amount as f64 / 100.0),
             Currency::JPY => write!(f, "¥{}", self.amount), // JPY没有小数点
+            Currency::MYR => write!(f, "RM {:.2}", self.amount as f64 / 100.0),
         }
     }
 }
@@ -82,6 +307,22 @@ mod tests {
         assert_eq!(m2.currency, Currency::USD);
     }
 
+    #[test]
+    fn test_money_from_decimal() {
+        assert_eq!(Money::from_decimal("10.01", Currency::CNY), Ok(Money::cny(1001)));
+        assert_eq!(Money::from_decimal("10.5", Currency::USD), Ok(Money::usd(1050)));
+        assert_eq!(Money::from_decimal("10", Currency::CNY), Ok(Money::cny(1000)));
+        assert_eq!(Money::from_decimal("1000", Currency::JPY), Ok(Money::new(1000, Currency::JPY)));
+
+        // 小数位数超过币种精度
+        assert!(Money::from_decimal("10.001", Currency::CNY).is_err());
+        assert!(Money::from_decimal("1000.5", Currency::JPY).is_err());
+
+        for text in ["", ".5", "10.", "-1", "1e3", "abc", "99999999999999999999"] {
+            assert!(Money::from_decimal(text, Currency::CNY).is_err(), "{}", text);
+        }
+    }
+
     #[test]
     fn test_money_add() {
         let m1 = Money::cny(1000);
@@ -109,6 +350,64 @@ mod tests {
         assert!(m1.subtract(&m2).is_err());
     }
 
+    #[test]
+    fn test_convert() {
+        let rate = |text: &str| text.parse::<ExchangeRate>().unwrap();
+
+        // 100.00 USD -> CNY @ 7.1
+        let cny = Money::usd(10000).convert(Currency::CNY, rate("7.1"));
+        assert_eq!(cny, Some(Money::cny(71000)));
+
+        // 日元没有小数位: 1000 JPY -> USD @ 0.0067 = 6.70 USD
+        let usd = Money::new(1000, Currency::JPY).convert(Currency::USD, rate("0.0067"));
+        assert_eq!(usd, Some(Money::usd(670)));
+
+        // 0.29 USD -> CNY @ 7.25 = 2.1025，半分向上舍入
+        assert_eq!(Money::usd(29).convert(Currency::CNY, rate("7.25")), Some(Money::cny(210)));
+        assert_eq!(Money::usd(30).convert(Currency::CNY, rate("7.15")), Some(Money::cny(215)));
+        assert_eq!(Money::usd(-30).convert(Currency::CNY, rate("7.15")), Some(Money::cny(-215)));
+
+        // 溢出时不换算
+        assert_eq!(Money::usd(i64::MAX).convert(Currency::CNY, rate("7.1")), None);
+    }
+
+    #[test]
+    fn test_exchange_rate() {
+        let rate: ExchangeRate = "7.1".parse().unwrap();
+        assert_eq!(rate.scaled(), 710_000_000);
+        assert_eq!(rate.to_string(), "7.1");
+        assert_eq!("0.00000001".parse::<ExchangeRate>().unwrap().scaled(), 1);
+        assert_eq!("1".parse::<ExchangeRate>().unwrap(), ExchangeRate::ONE);
+        assert_eq!(ExchangeRate::ONE.to_string(), "1");
+        assert!("0.000000001".parse::<ExchangeRate>().is_err());
+        assert!("-1".parse::<ExchangeRate>().is_err());
+        assert!("abc".parse::<ExchangeRate>().is_err());
+
+        // JSON 数字和字符串都可以反序列化，序列化为字符串
+        assert_eq!(serde_json::from_str::<ExchangeRate>("7.1").unwrap(), rate);
+        assert_eq!(serde_json::from_str::<ExchangeRate>(r#""7.10""#).unwrap(), rate);
+        assert_eq!(serde_json::to_string(&rate).unwrap(), r#""7.1""#);
+    }
+
+    #[test]
+    fn test_numeric_code() {
+        assert_eq!(Currency::CNY.numeric_code(), "156");
+        assert_eq!(Currency::USD.numeric_code(), "840");
+        assert_eq!(Currency::JPY.numeric_code(), "392");
+    }
+
+    #[test]
+    fn test_format_for_display() {
+        assert_eq!(format_for_display(9999, Currency::CNY, "zh-CN"), "¥99.99");
+        assert_eq!(format_for_display(1000, Currency::MYR, "ms-MY"), "RM 10.00");
+        assert_eq!(format_for_display(123450, Currency::USD, "en-US"), "$1,234.50");
+        assert_eq!(format_for_display(-5, Currency::USD, "en-US"), "-$0.05");
+        // 日元没有小数位
+        assert_eq!(format_for_display(1234567, Currency::JPY, "ja-JP"), "¥1,234,567");
+        assert_eq!(format_for_display(123450, Currency::EUR, "de_DE"), "1.234,50\u{a0}€");
+        assert_eq!(Money::cny(100).format_for_display(""), "¥1.00");
+    }
+
     #[test]
     fn test_display_format() {
         let m1 = Money::cny(1050);
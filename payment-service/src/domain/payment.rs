@@ -2,17 +2,31 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 use crate::models::enums::{PaymentType, OrderStatus};
-use crate::domain::{money::Money, events::{PaymentEvent, apply_event}};
+use crate::models::payment::TransactionType;
+use crate::domain::{money::{Money, Currency, ExchangeRate}, events::{PaymentEvent, apply_event}};
 use crate::error::PaymentError;
 
+/// 订单未支付的过期时间
+pub const ORDER_EXPIRE_MINUTES: i64 = 30;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentOrder {
     pub id: Option<i64>,
     pub order_id: String,
+    /// 商户侧订单号，同一商户内唯一
+    pub merchant_order_id: Option<String>,
     pub tenant_id: i64,
     pub user_id: i64,
     pub payment_type: PaymentType,
+    pub transaction_type: TransactionType,
+    // 下单金额（用户看到并支付的货币）
     pub amount: Money,
+    // 结算金额（商户实际入账的货币）
+    pub settlement_amount: Money,
+    // 下单货币到结算货币的汇率，在创建订单时锁定
+    pub exchange_rate: ExchangeRate,
+    /// 预授权订单实际捕获的金额，捕获前为空
+    pub captured_amount: Option<i64>,
     pub status: OrderStatus,
     pub third_party_order_id: Option<String>,
     pub callback_url: Option<String>,
@@ -20,6 +34,8 @@ pub struct PaymentOrder {
     pub extra_data: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// 支付成功（预授权订单为捕获成功）的时间，对账按此归入账单日
+    pub paid_at: Option<DateTime<Utc>>,
 
     // 用于跟踪事件
     #[serde(skip)]
@@ -35,36 +51,78 @@ impl PaymentOrder {
         callback_url: Option<String>,
         notify_url: Option<String>,
         extra_data: Option<serde_json::Value>,
+        created_at: DateTime<Utc>,
     ) -> Self {
-        let now = Utc::now();
         let order_id = Uuid::new_v4().to_string();
 
         let mut order = Self {
             id: None,
             order_id,
+            merchant_order_id: None,
             tenant_id,
             user_id,
             payment_type,
+            transaction_type: TransactionType::Sale,
+            settlement_amount: amount.clone(),
             amount,
+            exchange_rate: ExchangeRate::ONE,
+            captured_amount: None,
             status: OrderStatus::Pending,
             third_party_order_id: None,
             callback_url,
             notify_url,
             extra_data,
-            created_at: now,
-            updated_at: now,
+            created_at,
+            updated_at: created_at,
+            paid_at: None,
             events: Vec::new(),
         };
 
         // 添加创建事件
         let _ = order.apply_event(PaymentEvent::OrderCreated {
             order_id: order.order_id.clone(),
-            created_at: now,
+            created_at,
         });
 
         order
     }
 
+    /// 设置结算货币，按汇率计算结算金额
+    ///
+    /// 结算货币与下单货币不同时，汇率必须大于0且换算结果不能溢出；相同时汇率固定为1
+    pub fn with_settlement(mut self, currency: Currency, exchange_rate: ExchangeRate) -> Result<Self, PaymentError> {
+        if currency == self.amount.currency {
+            self.settlement_amount = self.amount.clone();
+            self.exchange_rate = ExchangeRate::ONE;
+            return Ok(self);
+        }
+
+        let settlement_amount = Some(exchange_rate)
+            .filter(ExchangeRate::is_positive)
+            .and_then(|rate| self.amount.convert(currency, rate))
+            .ok_or_else(|| PaymentError::InvalidExchangeRate {
+                from: self.amount.currency.code().to_string(),
+                to: currency.code().to_string(),
+                rate: exchange_rate.to_string(),
+            })?;
+
+        self.settlement_amount = settlement_amount;
+        self.exchange_rate = exchange_rate;
+        Ok(self)
+    }
+
+    /// 设置交易类型
+    pub fn with_transaction_type(mut self, transaction_type: TransactionType) -> Self {
+        self.transaction_type = transaction_type;
+        self
+    }
+
+    /// 设置商户侧订单号
+    pub fn with_merchant_order_id(mut self, merchant_order_id: Option<String>) -> Self {
+        self.merchant_order_id = merchant_order_id;
+        self
+    }
+
     pub fn apply_event(&mut self, event: PaymentEvent) -> Result<(), PaymentError> {
         // 确保事件适用于当前订单
         if event.order_id() != self.order_id {
@@ -82,8 +140,19 @@ impl PaymentOrder {
 
                 // 处理特定事件的额外逻辑
                 match &event {
-                    PaymentEvent::PaymentCompleted { third_party_order_id, .. } => {
+                    PaymentEvent::PaymentCompleted { third_party_order_id, completed_at, .. } => {
                         self.third_party_order_id = Some(third_party_order_id.clone());
+                        self.paid_at = Some(*completed_at);
+                    }
+                    PaymentEvent::PaymentAuthorized { third_party_order_id, .. } => {
+                        self.third_party_order_id = Some(third_party_order_id.clone());
+                    }
+                    PaymentEvent::CaptureRequested { capture_amount, .. } => {
+                        self.captured_amount = Some(*capture_amount);
+                    }
+                    PaymentEvent::PaymentCaptured { capture_amount, captured_at, .. } => {
+                        self.captured_amount = Some(*capture_amount);
+                        self.paid_at = Some(*captured_at);
                     }
                     // 其他特定事件处理...
                     _ => {}
@@ -100,47 +169,151 @@ impl PaymentOrder {
         }
     }
 
-    pub fn initiate_payment(&mut self, payment_url: Option<String>) -> Result<(), PaymentError> {
+    // 状态转换的发生时间由调用方传入，服务中取自 [`Clock`](crate::clock::Clock)
+
+    pub fn initiate_payment(&mut self, payment_url: Option<String>, initiated_at: DateTime<Utc>) -> Result<(), PaymentError> {
         self.apply_event(PaymentEvent::PaymentInitiated {
             order_id: self.order_id.clone(),
             payment_url,
-            initiated_at: Utc::now(),
+            initiated_at,
         })
     }
 
-    pub fn complete_payment(&mut self, third_party_order_id: String) -> Result<(), PaymentError> {
+    pub fn complete_payment(&mut self, third_party_order_id: String, completed_at: DateTime<Utc>) -> Result<(), PaymentError> {
         self.apply_event(PaymentEvent::PaymentCompleted {
             order_id: self.order_id.clone(),
             third_party_order_id,
-            completed_at: Utc::now(),
+            completed_at,
         })
     }
 
-    pub fn fail_payment(&mut self, reason: String) -> Result<(), PaymentError> {
+    pub fn fail_payment(&mut self, reason: String, failed_at: DateTime<Utc>) -> Result<(), PaymentError> {
         self.apply_event(PaymentEvent::PaymentFailed {
             order_id: self.order_id.clone(),
             error_reason: reason,
-            failed_at: Utc::now(),
+            failed_at,
         })
     }
 
-    pub fn request_refund(&mut self, refund_id: String, refund_amount: i64) -> Result<(), PaymentError> {
+    /// 预授权成功，只有预授权订单可以进入已授权状态
+    pub fn authorize_payment(&mut self, third_party_order_id: String, authorized_at: DateTime<Utc>) -> Result<(), PaymentError> {
+        self.ensure_authorization()?;
+        self.apply_event(PaymentEvent::PaymentAuthorized {
+            order_id: self.order_id.clone(),
+            third_party_order_id,
+            authorized_at,
+        })
+    }
+
+    /// 开始捕获预授权，金额必须大于 0 且不超过授权金额，订单进入捕获中
+    pub fn start_capture(&mut self, capture_amount: i64, requested_at: DateTime<Utc>) -> Result<(), PaymentError> {
+        self.ensure_authorization()?;
+        if capture_amount <= 0 || capture_amount > self.amount.amount {
+            return Err(PaymentError::InvalidCaptureAmount(format!(
+                "捕获金额 {} 超出范围，授权金额 {}",
+                Money::new(capture_amount, self.amount.currency),
+                self.amount
+            )));
+        }
+        self.apply_event(PaymentEvent::CaptureRequested {
+            order_id: self.order_id.clone(),
+            capture_amount,
+            requested_at,
+        })
+    }
+
+    /// 渠道确认捕获，按 [`start_capture`](Self::start_capture) 记录的金额扣款
+    pub fn complete_capture(&mut self, captured_at: DateTime<Utc>) -> Result<(), PaymentError> {
+        self.ensure_authorization()?;
+        let capture_amount = self.captured_amount
+            .ok_or_else(|| PaymentError::InvalidCaptureAmount(format!("订单 {} 未开始捕获", self.order_id)))?;
+        self.apply_event(PaymentEvent::PaymentCaptured {
+            order_id: self.order_id.clone(),
+            capture_amount,
+            captured_at,
+        })
+    }
+
+    /// 撤销预授权，订单关闭
+    pub fn void_authorization(&mut self, voided_at: DateTime<Utc>) -> Result<(), PaymentError> {
+        self.ensure_authorization()?;
+        self.apply_event(PaymentEvent::AuthorizationVoided {
+            order_id: self.order_id.clone(),
+            voided_at,
+        })
+    }
+
+    fn ensure_authorization(&self) -> Result<(), PaymentError> {
+        match self.transaction_type {
+            TransactionType::Authorization => Ok(()),
+            TransactionType::Sale => Err(PaymentError::UnsupportedOperation(format!("订单 {} 不是预授权交易", self.order_id))),
+        }
+    }
+
+    /// 实际扣款金额：预授权订单为捕获金额，其他订单为下单金额
+    pub fn paid_amount(&self) -> Money {
+        match self.captured_amount {
+            Some(captured) => Money::new(captured, self.amount.currency),
+            None => self.amount.clone(),
+        }
+    }
+
+    /// 商户入账的结算金额：按下单时锁定的汇率换算实际扣款金额
+    pub fn paid_settlement_amount(&self) -> Money {
+        match self.captured_amount {
+            // 捕获金额不超过下单金额，下单时已换算过，不会溢出
+            Some(_) => self.paid_amount()
+                .convert(self.settlement_amount.currency, self.exchange_rate)
+                .unwrap_or_else(|| self.settlement_amount.clone()),
+            None => self.settlement_amount.clone(),
+        }
+    }
+
+    pub fn request_refund(&mut self, refund_id: String, refund_amount: i64, requested_at: DateTime<Utc>) -> Result<(), PaymentError> {
         self.apply_event(PaymentEvent::RefundRequested {
             order_id: self.order_id.clone(),
             refund_id,
             refund_amount,
-            requested_at: Utc::now(),
+            requested_at,
+        })
+    }
+
+    /// 部分退款，订单进入部分退款状态，可以继续退款
+    pub fn request_partial_refund(&mut self, refund_id: String, refund_amount: i64, requested_at: DateTime<Utc>) -> Result<(), PaymentError> {
+        self.apply_event(PaymentEvent::PartialRefundRequested {
+            order_id: self.order_id.clone(),
+            refund_id,
+            refund_amount,
+            requested_at,
         })
     }
 
-    pub fn complete_refund(&mut self, refund_id: String) -> Result<(), PaymentError> {
+    pub fn complete_refund(&mut self, refund_id: String, completed_at: DateTime<Utc>) -> Result<(), PaymentError> {
         self.apply_event(PaymentEvent::RefundCompleted {
             order_id: self.order_id.clone(),
             refund_id,
-            completed_at: Utc::now(),
+            completed_at,
         })
     }
 
+    /// 关闭未支付的订单，只有待支付和支付中的订单可以关闭
+    pub fn close(&mut self, closed_at: DateTime<Utc>) -> Result<(), PaymentError> {
+        self.apply_event(PaymentEvent::OrderClosed {
+            order_id: self.order_id.clone(),
+            closed_at,
+        })
+    }
+
+    /// 订单过期时间
+    pub fn expire_time(&self, ttl: chrono::Duration) -> DateTime<Utc> {
+        self.created_at + ttl
+    }
+
+    /// 按默认有效期 [`ORDER_EXPIRE_MINUTES`] 计算的过期时间，下单时传给渠道，使渠道侧的支付页面与订单同时失效
+    pub fn expire_at(&self) -> DateTime<Utc> {
+        self.expire_time(chrono::Duration::minutes(ORDER_EXPIRE_MINUTES))
+    }
+
     pub fn events(&self) -> &[PaymentEvent] {
         &self.events
     }
@@ -153,7 +326,6 @@ impl PaymentOrder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::money::{Money, Currency};
 
     #[test]
     fn test_create_payment_order() {
@@ -165,6 +337,7 @@ mod tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            Utc::now(),
         );
 
         assert_eq!(order.status, OrderStatus::Pending);
@@ -184,6 +357,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cross_currency_order() {
+        let order = PaymentOrder::new(
+            1,
+            100,
+            PaymentType::WxH5,
+            Money::usd(10000), // 100美元
+            None,
+            None,
+            None,
+            Utc::now(),
+        )
+        .with_settlement(Currency::CNY, "7.1".parse().unwrap())
+        .unwrap();
+
+        assert_eq!(order.amount, Money::usd(10000));
+        assert_eq!(order.settlement_amount, Money::cny(71000));
+        assert_eq!(order.exchange_rate.to_string(), "7.1");
+        assert_eq!(order.paid_settlement_amount(), Money::cny(71000));
+
+        // 预授权订单按捕获金额入账
+        let mut authorized = order.clone().with_transaction_type(TransactionType::Authorization);
+        authorized.captured_amount = Some(5000);
+        assert_eq!(authorized.paid_settlement_amount(), Money::cny(35500));
+
+        // 跨币种时汇率必须大于0
+        let result = PaymentOrder::new(1, 100, PaymentType::WxH5, Money::usd(10000), None, None, None, Utc::now())
+            .with_settlement(Currency::CNY, ExchangeRate::ZERO);
+        assert!(matches!(result, Err(PaymentError::InvalidExchangeRate { .. })));
+
+        // 换算溢出同样拒绝
+        let result = PaymentOrder::new(1, 100, PaymentType::WxH5, Money::usd(i64::MAX), None, None, None, Utc::now())
+            .with_settlement(Currency::CNY, "7.1".parse().unwrap());
+        assert!(matches!(result, Err(PaymentError::InvalidExchangeRate { .. })));
+
+        // 同币种忽略汇率
+        let order = PaymentOrder::new(1, 100, PaymentType::WxH5, Money::cny(10000), None, None, None, Utc::now())
+            .with_settlement(Currency::CNY, ExchangeRate::ZERO)
+            .unwrap();
+        assert_eq!(order.settlement_amount, Money::cny(10000));
+        assert_eq!(order.exchange_rate, ExchangeRate::ONE);
+    }
+
     #[test]
     fn test_payment_flow() {
         let mut order = PaymentOrder::new(
@@ -194,26 +410,29 @@ mod tests {
             None,
             None,
             None,
+            Utc::now(),
         );
 
         // Initial state
         assert_eq!(order.status, OrderStatus::Pending);
 
         // Initiate payment
-        order.initiate_payment(Some("http://pay.example.com".to_string())).unwrap();
+        order.initiate_payment(Some("http://pay.example.com".to_string()), Utc::now()).unwrap();
         assert_eq!(order.status, OrderStatus::Processing);
 
         // Complete payment
-        order.complete_payment("third_party_order_123".to_string()).unwrap();
+        let paid_at = Utc::now();
+        order.complete_payment("third_party_order_123".to_string(), paid_at).unwrap();
         assert_eq!(order.status, OrderStatus::Success);
         assert_eq!(order.third_party_order_id, Some("third_party_order_123".to_string()));
+        assert_eq!(order.paid_at, Some(paid_at));
 
         // Request refund
-        order.request_refund("refund_123".to_string(), 10000).unwrap();
+        order.request_refund("refund_123".to_string(), 10000, Utc::now()).unwrap();
         assert_eq!(order.status, OrderStatus::Refunded);
 
         // Complete refund
-        order.complete_refund("refund_123".to_string()).unwrap();
+        order.complete_refund("refund_123".to_string(), Utc::now()).unwrap();
         assert_eq!(order.status, OrderStatus::Refunded);
 
         // Check events
@@ -230,25 +449,79 @@ mod tests {
             None,
             None,
             None,
+            Utc::now(),
         );
 
         // Cannot complete payment directly from Pending
-        let result = order.complete_payment("test".to_string());
+        let result = order.complete_payment("test".to_string(), Utc::now());
         assert!(result.is_err());
 
         // Correct flow
-        order.initiate_payment(None).unwrap();
+        order.initiate_payment(None, Utc::now()).unwrap();
         assert_eq!(order.status, OrderStatus::Processing);
 
-        let result = order.complete_payment("test".to_string());
+        let result = order.complete_payment("test".to_string(), Utc::now());
         assert!(result.is_ok());
         assert_eq!(order.status, OrderStatus::Success);
 
         // Cannot initiate again after success
-        let result = order.initiate_payment(None);
+        let result = order.initiate_payment(None, Utc::now());
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_close_order() {
+        let mut order = PaymentOrder::new(1, 100, PaymentType::WxH5, Money::cny(10000), None, None, None, Utc::now());
+        let ttl = chrono::Duration::minutes(30);
+        assert_eq!(order.expire_time(ttl), order.created_at + ttl);
+
+        let closed_at = order.expire_time(ttl);
+        order.close(closed_at).unwrap();
+        assert_eq!(order.status, OrderStatus::Closed);
+        assert_eq!(order.updated_at, closed_at);
+
+        // 已支付的订单不能关闭
+        let mut paid = PaymentOrder::new(1, 100, PaymentType::WxH5, Money::cny(10000), None, None, None, Utc::now());
+        paid.initiate_payment(None, Utc::now()).unwrap();
+        paid.complete_payment("test".to_string(), Utc::now()).unwrap();
+        assert!(paid.close(Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_authorization_flow() {
+        let mut order = PaymentOrder::new(1, 100, PaymentType::PaypalH5, Money::usd(10000), None, None, None, Utc::now())
+            .with_transaction_type(TransactionType::Authorization);
+        order.initiate_payment(None, Utc::now()).unwrap();
+        order.authorize_payment("PAY-1".to_string(), Utc::now()).unwrap();
+        assert_eq!(order.status, OrderStatus::Authorized);
+        assert_eq!(order.third_party_order_id.as_deref(), Some("PAY-1"));
+        // 授权只冻结额度，捕获成功才算支付
+        assert_eq!(order.paid_at, None);
+
+        // 捕获金额不能超过授权金额，未开始捕获时不能确认
+        assert!(matches!(order.start_capture(10001, Utc::now()), Err(PaymentError::InvalidCaptureAmount(_))));
+        assert!(matches!(order.start_capture(0, Utc::now()), Err(PaymentError::InvalidCaptureAmount(_))));
+        assert!(order.complete_capture(Utc::now()).is_err());
+
+        order.start_capture(8000, Utc::now()).unwrap();
+        assert_eq!(order.status, OrderStatus::Capturing);
+        // 捕获中的订单不能撤销
+        assert!(order.void_authorization(Utc::now()).is_err());
+        let captured_at = Utc::now();
+        order.complete_capture(captured_at).unwrap();
+        assert_eq!(order.status, OrderStatus::Success);
+        assert_eq!(order.paid_amount(), Money::usd(8000));
+        assert_eq!(order.paid_at, Some(captured_at));
+        // 已捕获的订单不能撤销
+        assert!(order.void_authorization(Utc::now()).is_err());
+
+        // 普通订单不能预授权
+        let mut sale = PaymentOrder::new(1, 100, PaymentType::WxH5, Money::cny(10000), None, None, None, Utc::now());
+        sale.initiate_payment(None, Utc::now()).unwrap();
+        assert!(matches!(sale.authorize_payment("wx".to_string(), Utc::now()), Err(PaymentError::UnsupportedOperation(_))));
+        assert_eq!(sale.paid_amount(), Money::cny(10000));
+    }
+
     #[test]
     fn test_event_order_id_validation() {
         let mut order = PaymentOrder::new(
@@ -259,6 +532,7 @@ mod tests {
             None,
             None,
             None,
+            Utc::now(),
         );
 
         // Try to apply event with wrong order_id
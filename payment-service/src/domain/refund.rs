@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 订单上的退款记录
+///
+/// 同一订单的商户退款单号唯一，商户重试同一退款请求时返回已有记录，不会重复退款
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Refund {
+    pub refund_id: String,
+    pub order_id: String,
+    /// 商户退款单号，同时作为渠道的退款单号（如微信 `out_refund_no`）
+    pub merchant_refund_no: String,
+    /// 退款金额，订单币种的最小单位
+    pub refund_amount: i64,
+    pub refund_reason: Option<String>,
+    pub third_party_refund_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 向渠道查询退款的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelRefundStatus {
+    /// 渠道已退款，附渠道退款单号
+    Succeeded(String),
+    /// 渠道没有受理或退款失败，可以释放预留的额度
+    Failed,
+    /// 渠道仍在处理
+    Processing,
+}
+
+/// 订单上的退款金额，订单币种的最小单位
+///
+/// 只有渠道已确认的退款计入订单的退款状态；处理中的退款结果未知，只用来防止超额退款
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefundTotals {
+    /// 渠道已确认的退款
+    pub succeeded: i64,
+    /// 已预留额度、等待渠道确认的退款
+    pub pending: i64,
+}
+
+impl RefundTotals {
+    /// 已占用的可退额度，包括处理中的退款
+    pub fn reserved(&self) -> i64 {
+        self.succeeded + self.pending
+    }
+}
+
+/// 预留退款额度的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefundReservation {
+    /// 已写入处理中的退款
+    Reserved,
+    /// 同一订单的商户退款单号已存在
+    Duplicate(Refund),
+    /// 已占用的额度（含处理中的退款）加上本次金额超过可退金额，`reserved` 为已占用的额度
+    Exceeded { reserved: i64 },
+}
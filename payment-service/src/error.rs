@@ -1,8 +1,45 @@
-use axum::{response::{IntoResponse, Response}, http::StatusCode, Json};
+use axum::{response::{IntoResponse, Response}, http::{header, HeaderValue, StatusCode}, Json};
 use serde_json::json;
 use thiserror::Error;
 use crate::models::enums::OrderStatus;
 
+/// 支付服务错误
+///
+/// 响应体为 `{"success": false, "error": {"code", "type", "message"}}`，
+/// `code` 是对外承诺稳定的错误码，客户端应按它分支；`message` 仅用于展示，内容可能调整或本地化。
+///
+/// | 错误码 | HTTP 状态 | 变体 |
+/// |---|---|---|
+/// | `DATABASE_ERROR` | 500 | `Database` |
+/// | `UNSUPPORTED_CHANNEL` | 400 | `UnsupportedPaymentType` |
+/// | `INVALID_PAYMENT_TYPE` | 400 | `InvalidPaymentType` |
+/// | `INVALID_ORDER_STATUS` | 409 | `InvalidOrderStatus` |
+/// | `INVALID_STATE_TRANSITION` | 409 | `InvalidStateTransition` |
+/// | `INVALID_EVENT` | 400 | `InvalidEvent` |
+/// | `UNSUPPORTED_OPERATION` | 400 | `UnsupportedOperation` |
+/// | `INVALID_CALLBACK` | 400 | `InvalidCallback` |
+/// | `INTERNAL_ERROR` | 500 | `Internal` |
+/// | `CHANNEL_API_ERROR` | 502 | `ExternalApi` |
+/// | `CHANNEL_OUTCOME_UNKNOWN` | 502 | `ChannelOutcomeUnknown` |
+/// | `CONFIGURATION_ERROR` | 500 | `Configuration` |
+/// | `INCOMPLETE_CONFIG` | 500 | `IncompleteConfig` |
+/// | `INVALID_CONFIG` | 500 | `InvalidConfig` |
+/// | `ORDER_ACCESS_DENIED` | 403 | `OrderAccessDenied` |
+/// | `CALLBACK_IP_DENIED` | 403 | `CallbackIpDenied` |
+/// | `REFUND_DESTINATION_NOT_ALLOWED` | 403 | `RefundDestinationNotAllowed` |
+/// | `RATE_LIMITED` | 429 | `RateLimited` |
+/// | `MERCHANT_RATE_LIMITED` | 429 | `MerchantRateLimited` |
+/// | `ORDER_NOT_FOUND` | 404 | `OrderNotFound` |
+/// | `REFUND_FROZEN` | 409 | `RefundFrozen` |
+/// | `ORDER_ALREADY_EXISTS` | 409 | `OrderAlreadyExists` |
+/// | `INVALID_REFUND_AMOUNT` | 400 | `InvalidRefundAmount` |
+/// | `INVALID_CAPTURE_AMOUNT` | 400 | `InvalidCaptureAmount` |
+/// | `INSUFFICIENT_BALANCE` | 409 | `InsufficientBalance` |
+/// | `LEDGER_REFERENCE_CONFLICT` | 409 | `LedgerReferenceConflict` |
+/// | `INVALID_EXCHANGE_RATE` | 400 | `InvalidExchangeRate` |
+/// | `UNAUTHORIZED` | 401 | `Unauthorized` |
+///
+/// 处理器在进入业务前拒绝的请求使用相同的响应格式：回调路径中的支付类型无法解析返回 `INVALID_PAYMENT_TYPE`(400)
 #[derive(Error, Debug)]
 pub enum PaymentError {
     #[error("数据库错误: {0}")]
@@ -35,96 +72,295 @@ pub enum PaymentError {
     #[error("不支持的操作: {0}")]
     UnsupportedOperation(String),
 
+    #[error("无效的回调: {0}")]
+    InvalidCallback(String),
+
     #[error("内部错误: {0}")]
     Internal(String),
 
     #[error("第三方API错误: {code} - {message}")]
     ExternalApi { code: String, message: String },
 
+    /// 请求已发出但未收到明确结果（超时、网关错误），渠道可能已经处理
+    #[error("渠道结果未知: {0}")]
+    ChannelOutcomeUnknown(String),
+
     #[error("配置错误: {0}")]
     Configuration(String),
 
+    #[error("支付配置不完整: tenant_id={tenant_id}, payment_type={payment_type}, 缺少 {missing_field}")]
+    IncompleteConfig {
+        tenant_id: i64,
+        payment_type: String,
+        missing_field: String,
+    },
+
+    #[error("支付配置无效: tenant_id={tenant_id}, payment_type={payment_type}, {field} {reason}")]
+    InvalidConfig {
+        tenant_id: i64,
+        payment_type: String,
+        field: String,
+        reason: String,
+    },
+
+    #[error("无权访问订单: {order_id}")]
+    OrderAccessDenied { order_id: String },
+
+    #[error("回调来源地址不在白名单内: {payment_type} 来自 {ip}")]
+    CallbackIpDenied { payment_type: String, ip: std::net::IpAddr },
+
+    #[error("商户 {merchant_id} 不允许退款到非原支付账户")]
+    RefundDestinationNotAllowed { merchant_id: String },
+
     #[error("请求限流")]
     RateLimited,
 
+    #[error("商户请求限流: {merchant_id}, {retry_after:?} 后重试")]
+    MerchantRateLimited {
+        merchant_id: String,
+        retry_after: std::time::Duration,
+    },
+
     #[error("订单不存在: {0}")]
     OrderNotFound(String),
+
+    #[error("订单存在争议，退款已冻结: {order_id} (争议 {dispute_id})")]
+    RefundFrozen { order_id: String, dispute_id: String },
+
+    #[error("商户订单号已存在: {merchant_order_id}")]
+    OrderAlreadyExists { merchant_order_id: String },
+
+    #[error("无效的退款金额: {0}")]
+    InvalidRefundAmount(String),
+
+    #[error("无效的捕获金额: {0}")]
+    InvalidCaptureAmount(String),
+
+    #[error("商户余额不足: {merchant_id}, 余额 {balance}, 需要 {amount}")]
+    InsufficientBalance { merchant_id: i64, balance: i64, amount: i64 },
+
+    #[error("账本流水 {reference} 已记账，但金额不一致: 已记 {recorded}, 本次 {amount}")]
+    LedgerReferenceConflict { reference: String, recorded: String, amount: String },
+
+    #[error("无效的汇率: {from} -> {to} 汇率 {rate}")]
+    InvalidExchangeRate {
+        from: String,
+        to: String,
+        rate: String,
+    },
+
+    /// 商户请求未通过鉴权
+    #[error("未授权: {0}")]
+    Unauthorized(String),
+}
+
+impl PaymentError {
+    /// 稳定的错误码，见 [`PaymentError`] 的错误码表，新增变体时同步更新
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            PaymentError::Database(_) => "DATABASE_ERROR",
+            PaymentError::UnsupportedPaymentType(_) => "UNSUPPORTED_CHANNEL",
+            PaymentError::InvalidPaymentType(_) => "INVALID_PAYMENT_TYPE",
+            PaymentError::InvalidOrderStatus { .. } => "INVALID_ORDER_STATUS",
+            PaymentError::InvalidStateTransition { .. } => "INVALID_STATE_TRANSITION",
+            PaymentError::InvalidEvent { .. } => "INVALID_EVENT",
+            PaymentError::UnsupportedOperation(_) => "UNSUPPORTED_OPERATION",
+            PaymentError::InvalidCallback(_) => "INVALID_CALLBACK",
+            PaymentError::Internal(_) => "INTERNAL_ERROR",
+            PaymentError::ExternalApi { .. } => "CHANNEL_API_ERROR",
+            PaymentError::ChannelOutcomeUnknown(_) => "CHANNEL_OUTCOME_UNKNOWN",
+            PaymentError::Configuration(_) => "CONFIGURATION_ERROR",
+            PaymentError::IncompleteConfig { .. } => "INCOMPLETE_CONFIG",
+            PaymentError::InvalidConfig { .. } => "INVALID_CONFIG",
+            PaymentError::OrderAccessDenied { .. } => "ORDER_ACCESS_DENIED",
+            PaymentError::CallbackIpDenied { .. } => "CALLBACK_IP_DENIED",
+            PaymentError::RefundDestinationNotAllowed { .. } => "REFUND_DESTINATION_NOT_ALLOWED",
+            PaymentError::RateLimited => "RATE_LIMITED",
+            PaymentError::MerchantRateLimited { .. } => "MERCHANT_RATE_LIMITED",
+            PaymentError::OrderNotFound(_) => "ORDER_NOT_FOUND",
+            PaymentError::RefundFrozen { .. } => "REFUND_FROZEN",
+            PaymentError::OrderAlreadyExists { .. } => "ORDER_ALREADY_EXISTS",
+            PaymentError::InvalidRefundAmount(_) => "INVALID_REFUND_AMOUNT",
+            PaymentError::InvalidCaptureAmount(_) => "INVALID_CAPTURE_AMOUNT",
+            PaymentError::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
+            PaymentError::LedgerReferenceConflict { .. } => "LEDGER_REFERENCE_CONFLICT",
+            PaymentError::InvalidExchangeRate { .. } => "INVALID_EXCHANGE_RATE",
+            PaymentError::Unauthorized(_) => "UNAUTHORIZED",
+        }
+    }
+
+    /// 错误对应的 HTTP 状态码
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            PaymentError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            PaymentError::UnsupportedPaymentType(_) => StatusCode::BAD_REQUEST,
+            PaymentError::InvalidPaymentType(_) => StatusCode::BAD_REQUEST,
+            PaymentError::InvalidOrderStatus { .. } => StatusCode::CONFLICT,
+            PaymentError::InvalidStateTransition { .. } => StatusCode::CONFLICT,
+            PaymentError::InvalidEvent { .. } => StatusCode::BAD_REQUEST,
+            PaymentError::UnsupportedOperation(_) => StatusCode::BAD_REQUEST,
+            PaymentError::InvalidCallback(_) => StatusCode::BAD_REQUEST,
+            PaymentError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            PaymentError::ExternalApi { .. } => StatusCode::BAD_GATEWAY,
+            PaymentError::ChannelOutcomeUnknown(_) => StatusCode::BAD_GATEWAY,
+            PaymentError::Configuration(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            PaymentError::IncompleteConfig { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            PaymentError::InvalidConfig { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            PaymentError::OrderAccessDenied { .. } => StatusCode::FORBIDDEN,
+            PaymentError::CallbackIpDenied { .. } => StatusCode::FORBIDDEN,
+            PaymentError::RefundDestinationNotAllowed { .. } => StatusCode::FORBIDDEN,
+            PaymentError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            PaymentError::MerchantRateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            PaymentError::OrderNotFound(_) => StatusCode::NOT_FOUND,
+            PaymentError::RefundFrozen { .. } => StatusCode::CONFLICT,
+            PaymentError::OrderAlreadyExists { .. } => StatusCode::CONFLICT,
+            PaymentError::InvalidRefundAmount(_) => StatusCode::BAD_REQUEST,
+            PaymentError::InvalidCaptureAmount(_) => StatusCode::BAD_REQUEST,
+            PaymentError::InsufficientBalance { .. } => StatusCode::CONFLICT,
+            PaymentError::LedgerReferenceConflict { .. } => StatusCode::CONFLICT,
+            PaymentError::InvalidExchangeRate { .. } => StatusCode::BAD_REQUEST,
+            PaymentError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        }
+    }
 }
 
 impl IntoResponse for PaymentError {
     fn into_response(self) -> Response {
-        let (status, error_type, error_message) = match &self {
+        let (error_type, error_message) = match &self {
             PaymentError::Database(e) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
                 "DatabaseError",
                 format!("数据库操作失败: {}", e)
             ),
             PaymentError::UnsupportedPaymentType(pt) => (
-                StatusCode::BAD_REQUEST,
                 "UnsupportedPaymentType",
                 format!("不支持的支付类型: {}", pt)
             ),
             PaymentError::InvalidPaymentType(code) => (
-                StatusCode::BAD_REQUEST,
                 "InvalidPaymentType",
                 format!("无效的支付类型代码: {}", code)
             ),
             PaymentError::InvalidOrderStatus { current, expected } => (
-                StatusCode::CONFLICT,
                 "InvalidOrderStatus",
                 format!("订单状态错误: 当前 {}, 需要 {:?}", current, expected)
             ),
             PaymentError::InvalidStateTransition { from, event } => (
-                StatusCode::CONFLICT,
                 "InvalidStateTransition",
                 format!("状态转换错误: 从 {:?} 不能应用 {}", from, event)
             ),
             PaymentError::InvalidEvent { order_id, event_order_id } => (
-                StatusCode::BAD_REQUEST,
                 "InvalidEvent",
                 format!("无效的事件: 订单ID {} 与事件订单ID {} 不匹配", order_id, event_order_id)
             ),
             PaymentError::UnsupportedOperation(msg) => (
-                StatusCode::BAD_REQUEST,
                 "UnsupportedOperation",
                 format!("不支持的操作: {}", msg)
             ),
+            PaymentError::InvalidCallback(msg) => (
+                "InvalidCallback",
+                format!("无效的回调: {}", msg)
+            ),
             PaymentError::Internal(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
                 "InternalError",
                 format!("内部错误: {}", msg)
             ),
             PaymentError::ExternalApi { code, message } => (
-                StatusCode::BAD_GATEWAY,
                 "ExternalApiError",
                 format!("第三方API错误 {}: {}", code, message)
             ),
+            PaymentError::ChannelOutcomeUnknown(msg) => (
+                "ChannelOutcomeUnknown",
+                format!("渠道结果未知，请稍后查询: {}", msg)
+            ),
             PaymentError::Configuration(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
                 "ConfigurationError",
                 format!("配置错误: {}", msg)
             ),
+            PaymentError::IncompleteConfig { .. } => (
+                "IncompleteConfig",
+                self.to_string()
+            ),
+            PaymentError::InvalidConfig { .. } => (
+                "InvalidConfig",
+                self.to_string()
+            ),
+            PaymentError::OrderAccessDenied { .. } => (
+                "OrderAccessDenied",
+                self.to_string()
+            ),
+            PaymentError::CallbackIpDenied { .. } => (
+                "CallbackIpDenied",
+                "回调来源地址不被允许".to_string()
+            ),
+            PaymentError::RefundDestinationNotAllowed { .. } => (
+                "RefundDestinationNotAllowed",
+                self.to_string()
+            ),
             PaymentError::RateLimited => (
-                StatusCode::TOO_MANY_REQUESTS,
                 "RateLimited",
                 "请求被限流，请稍后重试".to_string()
             ),
+            PaymentError::MerchantRateLimited { merchant_id, retry_after } => (
+                "MerchantRateLimited",
+                format!("商户 {} 请求过于频繁，请 {}ms 后重试", merchant_id, retry_after.as_millis())
+            ),
             PaymentError::OrderNotFound(order_id) => (
-                StatusCode::NOT_FOUND,
                 "OrderNotFound",
                 format!("订单不存在: {}", order_id)
             ),
+            PaymentError::RefundFrozen { .. } => (
+                "RefundFrozen",
+                self.to_string()
+            ),
+            PaymentError::OrderAlreadyExists { .. } => (
+                "OrderAlreadyExists",
+                self.to_string()
+            ),
+            PaymentError::InvalidRefundAmount(msg) => (
+                "InvalidRefundAmount",
+                format!("无效的退款金额: {}", msg)
+            ),
+            PaymentError::InvalidCaptureAmount(msg) => (
+                "InvalidCaptureAmount",
+                format!("无效的捕获金额: {}", msg)
+            ),
+            PaymentError::InsufficientBalance { .. } => (
+                "InsufficientBalance",
+                self.to_string()
+            ),
+            PaymentError::LedgerReferenceConflict { .. } => (
+                "LedgerReferenceConflict",
+                self.to_string()
+            ),
+            PaymentError::InvalidExchangeRate { from, to, rate } => (
+                "InvalidExchangeRate",
+                format!("无效的汇率: {} -> {} 汇率 {}", from, to, rate)
+            ),
+            PaymentError::Unauthorized(msg) => (
+                "Unauthorized",
+                msg.clone()
+            ),
         };
 
         let body = Json(json!({
             "success": false,
             "error": {
+                "code": self.error_code(),
                 "type": error_type,
                 "message": error_message
             }
         }));
 
-        (status, body).into_response()
+        let mut response = (self.status_code(), body).into_response();
+
+        // 限流时告知客户端重试时间（秒，向上取整）
+        if let PaymentError::MerchantRateLimited { retry_after, .. } = &self {
+            let secs = retry_after.as_millis().div_ceil(1000).max(1);
+            if let Ok(value) = HeaderValue::from_str(&secs.to_string()) {
+                response.headers_mut().insert(header::RETRY_AFTER, value);
+            }
+        }
+
+        response
     }
 }
 
@@ -150,9 +386,74 @@ mod tests {
         let response = order_not_found.into_response();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
 
+        // 测试商户订单号重复错误响应
+        let duplicate = PaymentError::OrderAlreadyExists { merchant_order_id: "M-1".to_string() };
+        let response = duplicate.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
         // 测试限流错误响应
         let rate_limited = PaymentError::RateLimited;
         let response = rate_limited.into_response();
         assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // 测试商户限流携带重试时间
+        let merchant_limited = PaymentError::MerchantRateLimited {
+            merchant_id: "m1".to_string(),
+            retry_after: std::time::Duration::from_millis(1500),
+        };
+        let response = merchant_limited.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "2");
+    }
+
+    #[test]
+    fn test_error_code_catalog() {
+        let cases = [
+            (PaymentError::Database(sqlx::Error::PoolClosed), "DATABASE_ERROR", StatusCode::INTERNAL_SERVER_ERROR),
+            (PaymentError::UnsupportedPaymentType("paypal".to_string()), "UNSUPPORTED_CHANNEL", StatusCode::BAD_REQUEST),
+            (PaymentError::InvalidPaymentType(999), "INVALID_PAYMENT_TYPE", StatusCode::BAD_REQUEST),
+            (PaymentError::InvalidOrderStatus { current: "Paid".to_string(), expected: vec!["Pending".to_string()] }, "INVALID_ORDER_STATUS", StatusCode::CONFLICT),
+            (PaymentError::InvalidStateTransition { from: OrderStatus::Pending, event: "Refund".to_string() }, "INVALID_STATE_TRANSITION", StatusCode::CONFLICT),
+            (PaymentError::InvalidEvent { order_id: "o1".to_string(), event_order_id: "o2".to_string() }, "INVALID_EVENT", StatusCode::BAD_REQUEST),
+            (PaymentError::UnsupportedOperation("close".to_string()), "UNSUPPORTED_OPERATION", StatusCode::BAD_REQUEST),
+            (PaymentError::InvalidCallback("bad sign".to_string()), "INVALID_CALLBACK", StatusCode::BAD_REQUEST),
+            (PaymentError::Internal("oops".to_string()), "INTERNAL_ERROR", StatusCode::INTERNAL_SERVER_ERROR),
+            (PaymentError::ExternalApi { code: "40004".to_string(), message: "fail".to_string() }, "CHANNEL_API_ERROR", StatusCode::BAD_GATEWAY),
+            (PaymentError::ChannelOutcomeUnknown("timeout".to_string()), "CHANNEL_OUTCOME_UNKNOWN", StatusCode::BAD_GATEWAY),
+            (PaymentError::Configuration("missing".to_string()), "CONFIGURATION_ERROR", StatusCode::INTERNAL_SERVER_ERROR),
+            (PaymentError::IncompleteConfig { tenant_id: 1, payment_type: "Alipay".to_string(), missing_field: "app_id".to_string() }, "INCOMPLETE_CONFIG", StatusCode::INTERNAL_SERVER_ERROR),
+            (PaymentError::InvalidConfig { tenant_id: 1, payment_type: "Alipay".to_string(), field: "key".to_string(), reason: "empty".to_string() }, "INVALID_CONFIG", StatusCode::INTERNAL_SERVER_ERROR),
+            (PaymentError::OrderAccessDenied { order_id: "o1".to_string() }, "ORDER_ACCESS_DENIED", StatusCode::FORBIDDEN),
+            (PaymentError::CallbackIpDenied { payment_type: "Alipay".to_string(), ip: [10, 0, 0, 1].into() }, "CALLBACK_IP_DENIED", StatusCode::FORBIDDEN),
+            (PaymentError::RefundDestinationNotAllowed { merchant_id: "m1".to_string() }, "REFUND_DESTINATION_NOT_ALLOWED", StatusCode::FORBIDDEN),
+            (PaymentError::RateLimited, "RATE_LIMITED", StatusCode::TOO_MANY_REQUESTS),
+            (PaymentError::MerchantRateLimited { merchant_id: "m1".to_string(), retry_after: std::time::Duration::from_secs(1) }, "MERCHANT_RATE_LIMITED", StatusCode::TOO_MANY_REQUESTS),
+            (PaymentError::OrderNotFound("o1".to_string()), "ORDER_NOT_FOUND", StatusCode::NOT_FOUND),
+            (PaymentError::RefundFrozen { order_id: "o1".to_string(), dispute_id: "d1".to_string() }, "REFUND_FROZEN", StatusCode::CONFLICT),
+            (PaymentError::OrderAlreadyExists { merchant_order_id: "M-1".to_string() }, "ORDER_ALREADY_EXISTS", StatusCode::CONFLICT),
+            (PaymentError::InvalidRefundAmount("0".to_string()), "INVALID_REFUND_AMOUNT", StatusCode::BAD_REQUEST),
+            (PaymentError::InvalidCaptureAmount("0".to_string()), "INVALID_CAPTURE_AMOUNT", StatusCode::BAD_REQUEST),
+            (PaymentError::InsufficientBalance { merchant_id: 1, balance: 0, amount: 100 }, "INSUFFICIENT_BALANCE", StatusCode::CONFLICT),
+            (PaymentError::LedgerReferenceConflict { reference: "s1".to_string(), recorded: "¥10.00".to_string(), amount: "¥20.00".to_string() }, "LEDGER_REFERENCE_CONFLICT", StatusCode::CONFLICT),
+            (PaymentError::InvalidExchangeRate { from: "USD".to_string(), to: "CNY".to_string(), rate: "0".to_string() }, "INVALID_EXCHANGE_RATE", StatusCode::BAD_REQUEST),
+            (PaymentError::Unauthorized("签名无效".to_string()), "UNAUTHORIZED", StatusCode::UNAUTHORIZED),
+        ];
+
+        for (error, code, status) in cases {
+            assert_eq!(error.error_code(), code, "{:?}", error);
+            assert_eq!(error.status_code(), status, "{:?}", error);
+            assert_eq!(error.into_response().status(), status);
+        }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_error_response_includes_code() {
+        let response = PaymentError::OrderNotFound("order123".to_string()).into_response();
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"]["code"], "ORDER_NOT_FOUND");
+        assert_eq!(body["error"]["message"], "订单不存在: order123");
+    }
+}
@@ -0,0 +1,202 @@
+//! 商户 API 鉴权
+//!
+//! 商户请求需携带三个请求头：
+//! - `X-Api-Key`：分配给商户的 API Key；
+//! - `X-Timestamp`：发送请求时的 Unix 时间戳（秒）；
+//! - `X-Signature`：以 API 密钥为 key，对 `"{timestamp}.{METHOD}.{path_and_query}.{body}"`
+//!   计算的 HMAC-SHA256，小写十六进制。
+//!
+//! 时间戳与服务器时间相差超过 [`MAX_CLOCK_SKEW_SECS`] 的请求拒绝，限制签名被重放的窗口。
+//! 验签通过后把凭证所属的租户写入请求扩展 [`AuthenticatedMerchant`]，处理器只信任该扩展，
+//! 不读取客户端传入的租户ID。
+
+use std::sync::Arc;
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::request::Parts,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::PaymentError;
+use crate::handlers::AuthenticatedMerchant;
+use crate::repository::credential_repository::CredentialRepository;
+use crate::services::callback_signer::{sign_callback, SIGNATURE_HEADER};
+
+/// API Key 请求头
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// 签名时间戳请求头，Unix 秒
+pub const TIMESTAMP_HEADER: &str = "X-Timestamp";
+
+/// 请求时间戳与服务器时间允许的最大偏差
+pub const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+/// 参与签名的请求体上限
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// 计算请求签名，商户侧按相同规则签名
+pub fn sign_request(secret: &str, timestamp: i64, method: &str, path_and_query: &str, body: &[u8]) -> String {
+    let mut message = format!("{}.{}.{}.", timestamp, method, path_and_query).into_bytes();
+    message.extend_from_slice(body);
+    sign_callback(secret, &message)
+}
+
+/// 商户鉴权中间件的状态
+#[derive(Clone)]
+pub struct MerchantAuth {
+    credentials: Arc<dyn CredentialRepository>,
+    clock: Arc<dyn Clock>,
+}
+
+impl MerchantAuth {
+    pub fn new(credentials: Arc<dyn CredentialRepository>) -> Self {
+        Self {
+            credentials,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// 替换时间来源，与支付服务共用同一个时钟
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 校验请求签名，返回凭证所属的商户
+    async fn verify(&self, parts: &Parts, body: &[u8]) -> Result<AuthenticatedMerchant, PaymentError> {
+        let header = |name: &str| {
+            parts.headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| PaymentError::Unauthorized(format!("缺少请求头 {}", name)))
+        };
+        let api_key = header(API_KEY_HEADER)?;
+        let signature = header(SIGNATURE_HEADER)?;
+        let timestamp: i64 = header(TIMESTAMP_HEADER)?
+            .parse()
+            .map_err(|_| PaymentError::Unauthorized("时间戳格式错误".to_string()))?;
+
+        if (self.clock.now().timestamp() - timestamp).abs() > MAX_CLOCK_SKEW_SECS {
+            return Err(PaymentError::Unauthorized("请求已过期".to_string()));
+        }
+
+        let credential = self.credentials.find_by_api_key(api_key).await?
+            .ok_or_else(|| PaymentError::Unauthorized("API Key 无效".to_string()))?;
+
+        let path_and_query = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        let expected = sign_request(&credential.secret, timestamp, parts.method.as_str(), path_and_query, body);
+        if !constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+            return Err(PaymentError::Unauthorized("签名无效".to_string()));
+        }
+
+        Ok(AuthenticatedMerchant(credential.tenant_id))
+    }
+}
+
+/// 校验商户签名，通过后写入 [`AuthenticatedMerchant`] 并放行
+pub async fn authenticate(State(auth): State<MerchantAuth>, request: Request, next: Next) -> Response {
+    let (mut parts, body) = request.into_parts();
+    let body: Bytes = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => return PaymentError::Unauthorized("请求体过大或读取失败".to_string()).into_response(),
+    };
+
+    match auth.verify(&parts, &body).await {
+        Ok(merchant) => {
+            parts.extensions.insert(merchant);
+            next.run(Request::from_parts(parts, Body::from(body))).await
+        }
+        Err(e) => {
+            tracing::warn!("商户鉴权失败: {} {} ({})", parts.method, parts.uri.path(), e);
+            e.into_response()
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use axum::{http::StatusCode, routing::post, Router};
+    use chrono::Utc;
+    use tower::ServiceExt;
+    use crate::clock::MockClock;
+    use crate::repository::credential_repository::MerchantCredential;
+
+    struct StaticCredentials;
+
+    #[async_trait]
+    impl CredentialRepository for StaticCredentials {
+        async fn find_by_api_key(&self, api_key: &str) -> Result<Option<MerchantCredential>, PaymentError> {
+            Ok((api_key == "key_1").then(|| MerchantCredential {
+                api_key: "key_1".to_string(),
+                tenant_id: 1,
+                secret: "secret_1".to_string(),
+            }))
+        }
+    }
+
+    async fn whoami(AuthenticatedMerchant(merchant_id): AuthenticatedMerchant) -> String {
+        merchant_id.to_string()
+    }
+
+    fn app(clock: Arc<MockClock>) -> Router {
+        let auth = MerchantAuth::new(Arc::new(StaticCredentials)).with_clock(clock);
+        Router::new()
+            .route("/whoami", post(whoami))
+            .layer(axum::middleware::from_fn_with_state(auth, authenticate))
+    }
+
+    fn request(api_key: &str, timestamp: i64, signature: &str, tenant_header: Option<&str>) -> Request {
+        let mut builder = axum::http::Request::builder()
+            .method("POST")
+            .uri("/whoami")
+            .header(API_KEY_HEADER, api_key)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature);
+        if let Some(tenant_id) = tenant_header {
+            builder = builder.header("X-Tenant-Id", tenant_id);
+        }
+        builder.body(Body::from(r#"{"tenant_id":2}"#)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_authenticate() -> anyhow::Result<()> {
+        let clock = Arc::new(MockClock::new(Utc::now()));
+        let now = clock.now().timestamp();
+        let body = br#"{"tenant_id":2}"#;
+        let signature = sign_request("secret_1", now, "POST", "/whoami", body);
+
+        // 租户取自凭证，忽略客户端传入的租户ID
+        let response = app(clock.clone()).oneshot(request("key_1", now, &signature, Some("2"))).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        assert_eq!(&body[..], b"1");
+
+        // 签名错误、未知的 API Key
+        let response = app(clock.clone()).oneshot(request("key_1", now, "bad", None)).await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app(clock.clone()).oneshot(request("key_2", now, &signature, None)).await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // 超出时间窗口的签名不能重放
+        clock.advance(chrono::Duration::seconds(MAX_CLOCK_SKEW_SECS + 1));
+        let response = app(clock.clone()).oneshot(request("key_1", now, &signature, None)).await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // 只有客户端传入的租户请求头时拒绝
+        let response = app(clock).oneshot(
+            axum::http::Request::builder().method("POST").uri("/whoami").header("X-Tenant-Id", "1").body(Body::empty())?,
+        ).await?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        Ok(())
+    }
+}
@@ -1,38 +1,183 @@
 use axum::{
-    extract::{Path, Json, State, Query},
-    http::StatusCode,
+    extract::{ConnectInfo, FromRequestParts, Path, Json, State, Query},
+    http::{header, request::Parts, HeaderMap, StatusCode},
     response::IntoResponse,
     Extension,
 };
+use std::net::SocketAddr;
 use std::sync::Arc;
 use axum::response::Response;
 use serde_json::json;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::models::payment::{CreatePaymentRequest, RefundRequest};
+use crate::config::channels::ChannelsConfig;
+use crate::domain::money::{Currency, Money};
+use crate::error::PaymentError;
+use crate::models::payment::{CaptureRequest, CreatePaymentRequest, Paging, PaymentQueryResponse, RefundRequest, VoidAuthorizationRequest};
 use crate::models::enums::PaymentType;
+use crate::services::metrics::render_prometheus;
 use crate::services::payment_service::PaymentService;
 
+pub mod auth;
+
 pub async fn health() -> impl IntoResponse {
     (StatusCode::OK, Json(json!({ "status": "healthy" })))
 }
 
+/// 就绪探针：所有支付渠道可达时返回 200，否则返回 503
+pub async fn readyz(
+    Extension(service): Extension<Arc<PaymentService>>,
+) -> Response {
+    let channels = service.channel_health().await;
+    let ready = channels.values().all(|healthy| *healthy);
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (status, Json(json!({ "ready": ready, "channels": channels }))).into_response()
+}
+
+/// Prometheus 抓取的支付指标
+pub async fn metrics(
+    Extension(service): Extension<Arc<PaymentService>>,
+) -> Response {
+    let body = render_prometheus(&service.metrics_snapshot());
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        body,
+    )
+        .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ChannelsQuery {
+    currency: Option<String>,
+}
+
+/// 指定货币下可用的支付渠道，默认 CNY
+pub async fn available_channels(
+    Extension(channels): Extension<Arc<ChannelsConfig>>,
+    Query(query): Query<ChannelsQuery>,
+) -> Response {
+    let currency = query.currency.as_deref().unwrap_or("CNY");
+    let data = channels.available_channels(currency);
+    (StatusCode::OK, Json(json!({ "success": true, "data": data }))).into_response()
+}
+
+/// 下单，订单归属于已鉴权的商户，忽略请求体中的 `tenant_id`
 pub async fn create_payment(
     Extension(service): Extension<Arc<PaymentService>>,
-    Json(request): Json<CreatePaymentRequest>,
+    AuthenticatedMerchant(merchant_id): AuthenticatedMerchant,
+    Query(display): Query<DisplayQuery>,
+    Json(mut request): Json<CreatePaymentRequest>,
 ) -> Response {
+    request.tenant_id = merchant_id;
+    // 货币无效时由下单返回错误，这里不需要处理
+    let amount = Currency::from_code(&request.currency).map(|currency| Money::new(request.amount, currency));
     match service.create_payment(request).await {
-        Ok(response) => (StatusCode::OK, Json(json!({ "success": true, "data": response }))).into_response(),
+        Ok(response) => {
+            let data = WithDisplayAmount {
+                display_amount: amount.and_then(|amount| display.display_amount(&amount)),
+                data: response,
+            };
+            (StatusCode::OK, Json(json!({ "success": true, "data": data }))).into_response()
+        }
         Err(e) => e.into_response(),
     }
 }
 
+/// 已鉴权的商户（租户）
+///
+/// 由 [`auth::authenticate`] 验证商户签名后写入请求扩展，路由未经过鉴权中间件时拒绝请求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatedMerchant(pub i64);
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthenticatedMerchant {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions
+            .get::<Self>()
+            .copied()
+            .ok_or_else(|| PaymentError::Unauthorized("缺少商户身份".to_string()).into_response())
+    }
+}
+
+/// 商户按自己的订单号查询，响应与按内部订单号查询相同
+pub async fn query_by_merchant_order_id(
+    Extension(service): Extension<Arc<PaymentService>>,
+    AuthenticatedMerchant(merchant_id): AuthenticatedMerchant,
+    Path(merchant_order_id): Path<String>,
+    Query(display): Query<DisplayQuery>,
+) -> Response {
+    match service.query_by_merchant_order_id(merchant_id, &merchant_order_id).await {
+        Ok(result) => query_response(result, &display),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 按内部订单号查询，只能查询本商户（租户）的订单
 pub async fn query_payment(
     Extension(service): Extension<Arc<PaymentService>>,
+    AuthenticatedMerchant(merchant_id): AuthenticatedMerchant,
     Path(order_id): Path<String>,
+    Query(display): Query<DisplayQuery>,
 ) -> Response {
-    match service.query_payment(&order_id).await {
-        Ok(status) => (StatusCode::OK, Json(json!({ "success": true, "status": status }))).into_response(),
+    match service.query_payment(merchant_id, &order_id).await {
+        Ok(result) => query_response(result, &display),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 查询接口的响应，`display_amount` 与订单状态同级
+fn query_response(result: PaymentQueryResponse, display: &DisplayQuery) -> Response {
+    let body = WithDisplayAmount {
+        display_amount: display.display_amount(&result.amount),
+        data: json!({ "success": true, "status": result.status }),
+    };
+    (StatusCode::OK, Json(body)).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct DisplayQuery {
+    /// 展示金额使用的地区，如 `zh-CN`，未指定时不返回 `display_amount`
+    locale: Option<String>,
+}
+
+impl DisplayQuery {
+    /// 按请求的地区格式化金额，未指定地区时为空
+    fn display_amount(&self, amount: &Money) -> Option<String> {
+        self.locale.as_deref().map(|locale| amount.format_for_display(locale))
+    }
+}
+
+/// 响应数据及按地区格式化的金额
+#[derive(Serialize)]
+struct WithDisplayAmount<T> {
+    #[serde(flatten)]
+    data: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_amount: Option<String>,
+}
+
+/// 用户在本商户下的支付记录
+pub async fn list_user_payments(
+    Extension(service): Extension<Arc<PaymentService>>,
+    AuthenticatedMerchant(merchant_id): AuthenticatedMerchant,
+    Path(user_id): Path<i64>,
+    Query(paging): Query<Paging>,
+    Query(display): Query<DisplayQuery>,
+) -> Response {
+    match service.list_user_payments(merchant_id, user_id, paging).await {
+        Ok(orders) => {
+            let data: Vec<_> = orders
+                .into_iter()
+                .map(|order| WithDisplayAmount {
+                    display_amount: display.display_amount(&order.amount),
+                    data: order,
+                })
+                .collect();
+            (StatusCode::OK, Json(json!({ "success": true, "data": data }))).into_response()
+        }
         Err(e) => e.into_response(),
     }
 }
@@ -44,6 +189,9 @@ pub struct CallbackQuery {
 
 pub async fn payment_callback(
     Extension(service): Extension<Arc<PaymentService>>,
+    Extension(channels): Extension<Arc<ChannelsConfig>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Path(payment_type_str): Path<String>,
     Query(query): Query<CallbackQuery>,
     Json(callback_data): Json<serde_json::Value>,
@@ -62,6 +210,7 @@ pub async fn payment_callback(
                 Json(json!({
                     "success": false,
                     "error": {
+                        "code": "INVALID_PAYMENT_TYPE",
                         "type": "InvalidPaymentType",
                         "message": format!("Invalid payment type: {}", payment_type_str)
                     }
@@ -70,17 +219,94 @@ pub async fn payment_callback(
         }
     };
 
+    if let Err(e) = check_callback_source(&channels, payment_type, peer, &headers) {
+        return e.into_response();
+    }
+
     match service.handle_callback(payment_type, tenant_id, callback_data).await {
         Ok(_) => (StatusCode::OK, Json(json!({ "success": true }))).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+/// 渠道争议（拒付）通知
+pub async fn dispute_notification(
+    Extension(service): Extension<Arc<PaymentService>>,
+    Extension(channels): Extension<Arc<ChannelsConfig>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(payment_type_str): Path<String>,
+    Query(query): Query<CallbackQuery>,
+    Json(notification): Json<serde_json::Value>,
+) -> Response {
+    let tenant_id = query.tenant_id
+        .or_else(|| notification.get("tenant_id").and_then(|v| v.as_i64()))
+        .unwrap_or(1);
+
+    let payment_type = match payment_type_str.parse::<PaymentType>() {
+        Ok(pt) => pt,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "success": false,
+                    "error": {
+                        "code": "INVALID_PAYMENT_TYPE",
+                        "type": "InvalidPaymentType",
+                        "message": format!("Invalid payment type: {}", payment_type_str)
+                    }
+                })),
+            ).into_response();
+        }
+    };
+
+    if let Err(e) = check_callback_source(&channels, payment_type, peer, &headers) {
+        return e.into_response();
+    }
+
+    match service.handle_dispute_notification(payment_type, tenant_id, notification).await {
+        Ok(dispute) => (
+            StatusCode::OK,
+            Json(json!({ "success": true, "dispute_id": dispute.dispute_id })),
+        ).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 校验渠道通知的来源地址，在交给渠道适配器处理之前拒绝白名单外的请求
+fn check_callback_source(
+    channels: &ChannelsConfig,
+    payment_type: PaymentType,
+    peer: SocketAddr,
+    headers: &HeaderMap,
+) -> Result<(), PaymentError> {
+    // 多个 X-Forwarded-For 头按出现顺序拼接
+    let forwarded_for = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+    let forwarded_for = (!forwarded_for.is_empty()).then_some(forwarded_for.as_str());
+
+    let ip = channels.client_ip(peer.ip(), forwarded_for);
+    if channels.is_callback_ip_allowed(payment_type, ip) {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        "拒绝白名单外的回调: payment_type={}, ip={}, peer={}, x-forwarded-for={:?}",
+        payment_type, ip, peer, forwarded_for
+    );
+    Err(PaymentError::CallbackIpDenied { payment_type: payment_type.to_string(), ip })
+}
+
 pub async fn refund_payment(
     Extension(service): Extension<Arc<PaymentService>>,
+    AuthenticatedMerchant(merchant_id): AuthenticatedMerchant,
     Json(request): Json<RefundRequest>,
 ) -> Response {
-    match service.refund_payment(request).await {
+    match service.refund_payment(merchant_id, request).await {
         Ok(refund_id) => (
             StatusCode::OK,
             Json(json!({ "success": true, "refund_id": refund_id })),
@@ -88,6 +314,34 @@ pub async fn refund_payment(
         Err(e) => e.into_response(),
     }
 }
+
+pub async fn capture_payment(
+    Extension(service): Extension<Arc<PaymentService>>,
+    AuthenticatedMerchant(merchant_id): AuthenticatedMerchant,
+    Json(request): Json<CaptureRequest>,
+) -> Response {
+    match service.capture(merchant_id, &request.order_id, request.amount).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(json!({ "success": true, "order_id": request.order_id })),
+        ).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn void_authorization(
+    Extension(service): Extension<Arc<PaymentService>>,
+    AuthenticatedMerchant(merchant_id): AuthenticatedMerchant,
+    Json(request): Json<VoidAuthorizationRequest>,
+) -> Response {
+    match service.void_authorization(merchant_id, &request.order_id).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(json!({ "success": true, "order_id": request.order_id })),
+        ).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,7 +355,7 @@ mod tests {
     use axum::Router;
     use sqlx::mysql::MySqlPoolOptions;
     use std::sync::Arc;
-    use chrono::Utc;
+    use crate::clock::{Clock, SystemClock};
 
     async fn setup_test_db() -> anyhow::Result<sqlx::MySqlPool> {
         let database_url = std::env::var("TEST_DATABASE_URL")
@@ -129,7 +383,7 @@ mod tests {
             .await?;
 
         // 插入测试支付配置
-        let now = Utc::now();
+        let now = SystemClock.now();
         sqlx::query(
             r#"
             INSERT INTO payment_configs 
@@ -184,14 +438,15 @@ mod tests {
             config_cache,
         ));
 
-        // 创建测试app
+        // 创建测试app，商户身份由鉴权中间件写入，这里直接注入
         let app = Router::new()
             .route("/api/v1/payment/create", post(create_payment))
+            .layer(Extension(AuthenticatedMerchant(999)))
             .layer(Extension(payment_service));
 
-        // 创建测试请求 - 使用测试 tenant_id
+        // 创建测试请求 - 请求体中的 tenant_id 不生效
         let request_body = json!({
-            "tenant_id": 999,  // 使用测试 tenant_id
+            "tenant_id": 1,
             "user_id": 100,
             "payment_type": "WX_H5",
             "amount": 10000,
@@ -262,10 +517,14 @@ mod tests {
         // 先创建一个订单
         let create_request = crate::models::payment::CreatePaymentRequest {
             tenant_id: 999,
+            merchant_order_id: None,
             user_id: 100,
             payment_type: crate::models::enums::PaymentType::WxH5,
             amount: 10000,
             currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: crate::models::payment::TransactionType::Sale,
             product_name: "测试商品".to_string(),
             product_desc: Some("商品描述".to_string()),
             callback_url: Some("http://example.com/callback".to_string()),
@@ -278,7 +537,8 @@ mod tests {
 
         // 创建测试app
         let app = Router::new()
-            .route("/api/v1/payment/query/:order_id", get(query_payment))
+            .route("/api/v1/payment/query/{order_id}", get(query_payment))
+            .layer(Extension(AuthenticatedMerchant(999)))
             .layer(Extension(payment_service));
 
         // 创建查询请求
@@ -325,14 +585,15 @@ mod tests {
             config_cache,
         ));
 
-        // 创建测试app
+        // 创建测试app - 使用不存在的 tenant_id
         let app = Router::new()
             .route("/api/v1/payment/create", post(create_payment))
+            .layer(Extension(AuthenticatedMerchant(888)))
             .layer(Extension(payment_service));
 
-        // 创建测试请求 - 使用不存在的 tenant_id
+        // 创建测试请求
         let request_body = json!({
-            "tenant_id": 888,  // 不存在的 tenant_id
+            "tenant_id": 888,
             "user_id": 100,
             "payment_type": "WX_H5",
             "amount": 10000,
@@ -399,7 +660,7 @@ mod tests {
 //         fn app(&self) -> Router {
 //             Router::new()
 //                 .route("/api/v1/payment/create", post(create_payment))
-//                 .route("/api/v1/payment/query/:order_id", get(query_payment))
+//                 .route("/api/v1/payment/query/{order_id}", get(query_payment))
 //                 .layer(Extension(self.service.clone()))
 //         }
 // 
@@ -1,3 +1,4 @@
+pub mod clock;
 pub mod config;
 pub mod db;
 pub mod error;
@@ -7,3 +8,4 @@ pub mod payment;
 pub mod services;
 pub mod domain;
 pub mod repository;
+pub mod utils;
@@ -5,12 +5,18 @@ use axum::{
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
-use axum::http::StatusCode;
+use std::time::Duration;
+use common::sched::jittered_interval;
+use common::security::{headers_layer, SecurityHeadersConfig};
+use common::shutdown::ShutdownController;
+use futures::StreamExt;
+use axum::http::{header, HeaderName, HeaderValue, Method, StatusCode};
 use axum::response::IntoResponse;
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use payment_service::{config, db, handlers, payment, services};
+use payment_service::{config, db, handlers, payment, repository, services};
+use payment_service::handlers::auth::{self, MerchantAuth};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -40,26 +46,134 @@ async fn main() -> anyhow::Result<()> {
         )
     );
 
+    // 加载支付渠道配置
+    let channels = Arc::new(match &settings.payment_channels_file {
+        Some(path) => config::channels::ChannelsConfig::from_file(path)?,
+        None => config::channels::ChannelsConfig::default(),
+    });
+
+    // 已启用渠道的商户配置有误时拒绝启动
+    let validated = config_cache.validate_enabled(&channels).await?;
+    tracing::info!("支付配置校验通过: {} 条", validated);
+
     // 初始化支付工厂
     let payment_factory = Arc::new(payment::factory::PaymentFactory::new(config_cache.clone()));
 
     // 初始化支付服务
-    let payment_service = Arc::new(services::payment_service::PaymentService::new(
+    let mut payment_service = services::payment_service::PaymentService::new(
         pool.clone(),
-        payment_factory,
+        payment_factory.clone(),
         config_cache,
+    );
+
+    // 配置了 Redis 时商户限流在多实例间共享
+    if let Some(redis_url) = &settings.redis_url {
+        let rate_limiter = services::rate_limiter::RedisRateLimiter::new(redis_url).await?;
+        payment_service = payment_service.with_rate_limiter(Arc::new(rate_limiter));
+    }
+    let payment_service = Arc::new(payment_service);
+
+    // 收到 SIGTERM / Ctrl-C 时停止定时任务，等待处理中的请求完成后退出
+    let shutdown = ShutdownController::new();
+    shutdown.trigger_on_signal();
+
+    // 定时关闭超时未支付的订单并确认结果未知的退款，多实例部署时间隔随机抖动，避免同时扫描
+    let sweeper = payment_service.clone();
+    let stopped = shutdown.subscribe();
+    tokio::spawn(async move {
+        let mut ticks = std::pin::pin!(jittered_interval(Duration::from_secs(60), Duration::from_secs(10)).take_until(stopped.wait()));
+        while ticks.next().await.is_some() {
+            if let Err(e) = sweeper.close_expired_orders().await {
+                tracing::error!("关闭过期订单失败: {}", e);
+            }
+            if let Err(e) = sweeper.resolve_pending_refunds().await {
+                tracing::error!("查询结果未知的退款失败: {}", e);
+            }
+        }
+    });
+
+    // 定时向渠道刷新待支付订单，补偿丢失的支付通知
+    let poller = payment_service.clone();
+    let payment_types: Vec<_> = payment_factory.strategies().map(|(payment_type, _)| payment_type).collect();
+    let stopped = shutdown.subscribe();
+    tokio::spawn(async move {
+        let mut ticks = std::pin::pin!(jittered_interval(Duration::from_secs(60), Duration::from_secs(10)).take_until(stopped.wait()));
+        while ticks.next().await.is_some() {
+            for payment_type in &payment_types {
+                if let Err(e) = poller.refresh_pending(*payment_type, 100).await {
+                    tracing::error!("刷新 {} 待支付订单失败: {}", payment_type, e);
+                }
+            }
+        }
+    });
+
+    // 投递支付结果的商户通知，失败的通知按退避时间重试
+    let notifier = payment_service.clone();
+    let stopped = shutdown.subscribe();
+    tokio::spawn(async move {
+        let mut ticks = std::pin::pin!(jittered_interval(Duration::from_secs(5), Duration::from_secs(1)).take_until(stopped.wait()));
+        while ticks.next().await.is_some() {
+            if let Err(e) = notifier.deliver_merchant_notifications().await {
+                tracing::error!("投递商户通知失败: {}", e);
+            }
+        }
+    });
+
+    // 配置了 RabbitMQ 时向下游投递订单事件，停机时等待正在投递的一批完成
+    let mut outbox_dispatcher = None;
+    if let Some(rabbitmq_url) = &settings.rabbitmq_url {
+        let publisher = services::outbox::RabbitMqPublisher::connect(rabbitmq_url, &settings.outbox_exchange).await?;
+        outbox_dispatcher = Some(payment_service.clone().spawn_outbox_dispatcher(
+            Arc::new(publisher),
+            Duration::from_secs(1),
+            Duration::from_millis(200),
+            shutdown.subscribe(),
+        ));
+    }
+
+    // 商户接口验证 API 签名，租户取自签名凭证
+    let merchant_auth = MerchantAuth::new(Arc::new(
+        repository::credential_repository::MySqlCredentialRepository::new(pool.clone()),
     ));
+    let merchant_api = Router::new()
+        .route("/api/v1/payment/create", post(handlers::create_payment))
+        .route("/api/v1/payment/query/{order_id}", get(handlers::query_payment))
+        .route("/api/v1/orders/by-merchant/{merchant_order_id}", get(handlers::query_by_merchant_order_id))
+        .route("/api/v1/users/{user_id}/payments", get(handlers::list_user_payments))
+        .route("/api/v1/payment/refund", post(handlers::refund_payment))
+        .route("/api/v1/payment/capture", post(handlers::capture_payment))
+        .route("/api/v1/payment/void", post(handlers::void_authorization))
+        .route_layer(axum::middleware::from_fn_with_state(merchant_auth, auth::authenticate));
 
-    // 构建路由
+    // 只允许配置的来源跨域调用
+    let allowed_origins = settings.cors_allowed_origins
+        .iter()
+        .map(|origin| HeaderValue::from_str(origin))
+        .collect::<Result<Vec<_>, _>>()?;
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::list(allowed_origins))
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            HeaderName::from_static("x-api-key"),
+            HeaderName::from_static("x-timestamp"),
+            HeaderName::from_static("x-signature"),
+        ]);
+
+    // 构建路由，渠道通知和探针不经过商户鉴权
     let app = Router::new()
         .route("/health", get(handlers::health))
-        .route("/api/v1/payment/create", post(handlers::create_payment))
-        .route("/api/v1/payment/query/:order_id", get(handlers::query_payment))
-        .route("/api/v1/payment/callback/:payment_type", post(handlers::payment_callback))
-        .route("/api/v1/payment/refund", post(handlers::refund_payment))
+        .route("/readyz", get(handlers::readyz))
+        .route("/metrics", get(handlers::metrics))
+        .route("/api/v1/payment/channels", get(handlers::available_channels))
+        .route("/api/v1/payment/callback/{payment_type}", post(handlers::payment_callback))
+        .route("/api/v1/payment/dispute/{payment_type}", post(handlers::dispute_notification))
+        .merge(merchant_api)
         .layer(Extension(payment_service))
+        .layer(Extension(channels))
         .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::permissive());
+        .layer(headers_layer(&SecurityHeadersConfig::default())?)
+        .layer(cors);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], settings.server_port));
     tracing::info!("Payment service listening on {}", addr);
@@ -70,7 +184,15 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
     
-    axum::serve::serve(listener, app.into_make_service()).await?;
+    axum::serve::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown.subscribe().wait())
+        .await?;
+    if let Some(dispatcher) = outbox_dispatcher
+        && let Err(e) = dispatcher.await
+    {
+        tracing::error!("outbox 分发任务异常退出: {}", e);
+    }
+    tracing::info!("Payment service stopped");
 
     Ok(())
 }
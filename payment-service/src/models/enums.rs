@@ -1,77 +1,49 @@
 use serde::{Deserialize, Serialize};
-use strum_macros::{Display, EnumString, EnumIter};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumString, EnumIter)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum PaymentType {
-    #[strum(serialize = "APPLE_IAP")]
-    AppleIap,
-    #[strum(serialize = "WX_SDK")]
-    WxSdk,
-    #[strum(serialize = "ZFB_SDK")]
-    ZfbSdk,
-    #[strum(serialize = "WX_H5")]
-    WxH5,
-    #[strum(serialize = "SHOUFA_WX_H5")]
-    ShoufaWxH5,
-    #[strum(serialize = "WX_H5_V1")]
-    WxH5V1,
-    #[strum(serialize = "KJ_WX_H5")]
-    KjWxH5,
-    #[strum(serialize = "ZFB_H5")]
-    ZfbH5,
-    #[strum(serialize = "ZFB_H5_V1")]
-    ZfbH5V1,
-    #[strum(serialize = "ZFB_MIN_PROGRAM")]
-    ZfbMinProgram,
-    #[strum(serialize = "SHOUFA_ZFB_H5")]
-    ShoufaZfbH5,
-    #[strum(serialize = "XIAOJU_ZFB_H5")]
-    XiaojuZfbH5,
-    #[strum(serialize = "ZHILIAN_ALI_H5")]
-    ZhilianAliH5,
-    #[strum(serialize = "FUBEI_ALI_H5")]
-    FubeiAliH5,
-    #[strum(serialize = "KUAIJIE_ZFB_H5_V1")]
-    KuaijieZfbH5V1,
-    #[strum(serialize = "SCAN_PAY_WECHAT")]
-    ScanPayWechat,
-    #[strum(serialize = "SCAN_KJ_WX")]
-    ScanKjWx,
-    #[strum(serialize = "SCAN_PAY_ZFB")]
-    ScanPayZfb,
-    #[strum(serialize = "MIFA_PAY")]
-    MifaPay,
-    #[strum(serialize = "DY_SDK")]
-    DySdk,
-    #[strum(serialize = "PAYPAL_H5")]
-    PaypalH5,
-    #[strum(serialize = "GOOGLE")]
-    Google,
-    #[strum(serialize = "SD_WX_APPLET_NEW")]
-    SdWxAppletNew,
-    #[strum(serialize = "SD_ZFB_SDK")]
-    SdZfbSdk,
-    #[strum(serialize = "QUICK")]
-    Quick,
-    #[strum(serialize = "SD_H5_APPLET")]
-    SdH5Applet,
-    #[strum(serialize = "SD_H5_APPLET_JS")]
-    SdH5AppletJs,
-    #[strum(serialize = "DIN_WX_H5_V2")]
-    DinWxH5V2,
-    #[strum(serialize = "HEE_PAY")]
-    HeePay,
-    #[strum(serialize = "HEE_ALI_WAP")]
-    HeeAliWap,
-    #[strum(serialize = "HLB_ZFB_SDK")]
-    HlbZfbSdk,
-    #[strum(serialize = "WX_JS")]
-    WxJs,
-    #[strum(serialize = "SD_WX_JS")]
-    SdWxJs,
-    #[strum(serialize = "SHOUFA_WX_JS")]
-    ShoufaWxJs,
+use common::int_enum;
+
+int_enum! {
+    /// 支付方式，整数值为子类型编码，名称与数据库、接口中使用的一致
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum PaymentType {
+        AppleIap = 1 => "APPLE_IAP",
+        WxSdk = 2 => "WX_SDK",
+        ZfbSdk = 3 => "ZFB_SDK",
+        WxH5 = 5 => "WX_H5",
+        ShoufaWxH5 = 135 => "SHOUFA_WX_H5",
+        WxH5V1 = 300 => "WX_H5_V1",
+        KjWxH5 = 335 => "KJ_WX_H5",
+        ZfbH5 = 6 => "ZFB_H5",
+        ZfbH5V1 = 301 => "ZFB_H5_V1",
+        ZfbMinProgram = 302 => "ZFB_MIN_PROGRAM",
+        ShoufaZfbH5 = 143 => "SHOUFA_ZFB_H5",
+        XiaojuZfbH5 = 161 => "XIAOJU_ZFB_H5",
+        ZhilianAliH5 = 603 => "ZHILIAN_ALI_H5",
+        FubeiAliH5 = 604 => "FUBEI_ALI_H5",
+        KuaijieZfbH5V1 = 605 => "KUAIJIE_ZFB_H5_V1",
+        ScanPayWechat = 700 => "SCAN_PAY_WECHAT",
+        ScanKjWx = 701 => "SCAN_KJ_WX",
+        ScanPayZfb = 750 => "SCAN_PAY_ZFB",
+        MifaPay = 800 => "MIFA_PAY",
+        DySdk = 901 => "DY_SDK",
+        PaypalH5 = 18 => "PAYPAL_H5",
+        Google = 136 => "GOOGLE",
+        SdWxAppletNew = 151 => "SD_WX_APPLET_NEW",
+        SdZfbSdk = 150 => "SD_ZFB_SDK",
+        Quick = 157 => "QUICK",
+        SdH5Applet = 501 => "SD_H5_APPLET",
+        SdH5AppletJs = 502 => "SD_H5_APPLET_JS",
+        DinWxH5V2 = 503 => "DIN_WX_H5_V2",
+        HeePay = 153 => "HEE_PAY",
+        HeeAliWap = 155 => "HEE_ALI_WAP",
+        HlbZfbSdk = 159 => "HLB_ZFB_SDK",
+        WxJs = 16 => "WX_JS",
+        SdWxJs = 165 => "SD_WX_JS",
+        ShoufaWxJs = 166 => "SHOUFA_WX_JS",
+        UnionPayH5 = 19 => "UNIONPAY_H5",
+        UnionPaySdk = 190 => "UNIONPAY_SDK",
+        ScanPayUnion = 751 => "SCAN_PAY_UNION",
+    }
 }
 
 impl PaymentType {
@@ -83,7 +55,7 @@ impl PaymentType {
             Self::WxH5 | Self::ShoufaWxH5 | Self::WxH5V1 | Self::KjWxH5 => 5,
             Self::ZfbH5 | Self::ZfbH5V1 | Self::ZfbMinProgram | Self::ShoufaZfbH5
             | Self::XiaojuZfbH5 | Self::ZhilianAliH5 | Self::FubeiAliH5 | Self::KuaijieZfbH5V1 => 6,
-            Self::ScanPayWechat | Self::ScanKjWx | Self::ScanPayZfb => 7,
+            Self::ScanPayWechat | Self::ScanKjWx | Self::ScanPayZfb | Self::ScanPayUnion => 7,
             Self::MifaPay => 8,
             Self::DySdk => 9,
             Self::PaypalH5 => 18,
@@ -94,46 +66,13 @@ impl PaymentType {
             | Self::HeePay | Self::HeeAliWap => 157,
             Self::HlbZfbSdk => 159,
             Self::WxJs | Self::SdWxJs | Self::ShoufaWxJs => 16,
+            Self::UnionPayH5 | Self::UnionPaySdk => 19,
         }
     }
 
+    /// 子类型编码
     pub fn sub_type_code(&self) -> i32 {
-        match self {
-            Self::AppleIap => 1,
-            Self::WxSdk => 2,
-            Self::ZfbSdk => 3,
-            Self::WxH5 => 5,
-            Self::ShoufaWxH5 => 135,
-            Self::WxH5V1 => 300,
-            Self::KjWxH5 => 335,
-            Self::ZfbH5 => 6,
-            Self::ZfbH5V1 => 301,
-            Self::ZfbMinProgram => 302,
-            Self::ShoufaZfbH5 => 143,
-            Self::XiaojuZfbH5 => 161,
-            Self::ZhilianAliH5 => 603,
-            Self::FubeiAliH5 => 604,
-            Self::KuaijieZfbH5V1 => 605,
-            Self::ScanPayWechat => 700,
-            Self::ScanKjWx => 701,
-            Self::ScanPayZfb => 750,
-            Self::MifaPay => 800,
-            Self::DySdk => 901,
-            Self::PaypalH5 => 18,
-            Self::Google => 136,
-            Self::SdWxAppletNew => 151,
-            Self::SdZfbSdk => 150,
-            Self::Quick => 157,
-            Self::SdH5Applet => 501,
-            Self::SdH5AppletJs => 502,
-            Self::DinWxH5V2 => 503,
-            Self::HeePay => 153,
-            Self::HeeAliWap => 155,
-            Self::HlbZfbSdk => 159,
-            Self::WxJs => 16,
-            Self::SdWxJs => 165,
-            Self::ShoufaWxJs => 166,
-        }
+        self.value()
     }
 
     pub fn description(&self) -> &'static str {
@@ -172,36 +111,47 @@ impl PaymentType {
             Self::WxJs => "微信公众号支付",
             Self::SdWxJs => "杉德微信公众号支付",
             Self::ShoufaWxJs => "首发(快接)微信公众号支付",
+            Self::UnionPayH5 => "银联手机网页支付",
+            Self::UnionPaySdk => "银联控件支付",
+            Self::ScanPayUnion => "扫码-银联支付",
         }
     }
 
     pub fn from_sub_type(sub_type: i32) -> Option<Self> {
-        use strum::IntoEnumIterator;
-
-        Self::iter().find(|p| p.sub_type_code() == sub_type)
+        Self::try_from(sub_type).ok()
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum OrderStatus {
-    #[serde(rename = "PENDING")]
-    Pending,
-    #[serde(rename = "PROCESSING")]
-    Processing,
-    #[serde(rename = "SUCCESS")]
-    Success,
-    #[serde(rename = "FAILED")]
-    Failed,
-    #[serde(rename = "REFUNDED")]
-    Refunded,
-    #[serde(rename = "PARTIAL_REFUNDED")]
-    PartialRefunded,
+int_enum! {
+    /// 订单状态，名称即数据库中保存的状态
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub enum OrderStatus {
+        #[serde(rename = "PENDING")]
+        Pending = 0 => "PENDING",
+        #[serde(rename = "PROCESSING")]
+        Processing = 1 => "PROCESSING",
+        /// 预授权成功，等待捕获或撤销
+        #[serde(rename = "AUTHORIZED")]
+        Authorized = 2 => "AUTHORIZED",
+        /// 已向渠道发起捕获，等待渠道确认
+        #[serde(rename = "CAPTURING")]
+        Capturing = 3 => "CAPTURING",
+        #[serde(rename = "SUCCESS")]
+        Success = 4 => "SUCCESS",
+        #[serde(rename = "FAILED")]
+        Failed = 5 => "FAILED",
+        #[serde(rename = "REFUNDED")]
+        Refunded = 6 => "REFUNDED",
+        #[serde(rename = "PARTIAL_REFUNDED")]
+        PartialRefunded = 7 => "PARTIAL_REFUNDED",
+        #[serde(rename = "CLOSED")]
+        Closed = 8 => "CLOSED",
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use strum::IntoEnumIterator;
 
     #[test]
     fn test_payment_type_codes() {
@@ -222,6 +172,21 @@ mod tests {
         assert_eq!(PaymentType::from_sub_type(999), None);
     }
 
+    #[test]
+    fn test_payment_type_names() {
+        assert_eq!(PaymentType::UnionPayH5.to_string(), "UNIONPAY_H5");
+        assert_eq!("WX_H5".parse::<PaymentType>(), Ok(PaymentType::WxH5));
+        assert!("WX_H6".parse::<PaymentType>().is_err());
+    }
+
+    #[test]
+    fn test_order_status_names() {
+        for &status in OrderStatus::all() {
+            assert_eq!(status.as_str().parse::<OrderStatus>(), Ok(status));
+        }
+        assert!("UNKNOWN".parse::<OrderStatus>().is_err());
+    }
+
     #[test]
     fn test_payment_type_description() {
         assert_eq!(PaymentType::WxH5.description(), "微信H5支付");
@@ -230,7 +195,7 @@ mod tests {
 
     #[test]
     fn test_payment_type_iteration() {
-        let types: Vec<PaymentType> = PaymentType::iter().collect();
+        let types = PaymentType::all();
 
         // 确保包含至少一些预期的支付类型
         assert!(types.contains(&PaymentType::WxH5));
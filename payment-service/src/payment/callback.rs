@@ -0,0 +1,252 @@
+//! 渠道回调报文
+//!
+//! 各渠道异步通知的字段不同，先按支付类型解析为对应的结构，再由策略处理，
+//! 避免在策略中直接按字段名读取 JSON。
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::PaymentError;
+use crate::models::enums::{OrderStatus, PaymentType};
+
+/// 按支付类型解析后的回调
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedCallback {
+    Wechat(WechatCallback),
+    Alipay(AlipayCallback),
+    UnionPay(UnionPayCallback),
+    Apple(AppleCallback),
+}
+
+impl TypedCallback {
+    /// 回调对应的本系统订单号
+    pub fn order_id(&self) -> &str {
+        match self {
+            Self::Wechat(callback) => &callback.out_trade_no,
+            Self::Alipay(callback) => &callback.out_trade_no,
+            Self::UnionPay(callback) => callback.order_id(),
+            Self::Apple(callback) => &callback.order_id,
+        }
+    }
+}
+
+impl PaymentType {
+    /// 将渠道回调解析为对应的结构，缺少必填字段或字段类型不符时返回 [`PaymentError::InvalidCallback`]
+    pub fn parse_callback(&self, value: &Value) -> Result<TypedCallback, PaymentError> {
+        match self {
+            Self::WxH5 | Self::WxSdk => WechatCallback::parse(value).map(TypedCallback::Wechat),
+            Self::ZfbH5 | Self::ZfbSdk => AlipayCallback::parse(value).map(TypedCallback::Alipay),
+            Self::UnionPayH5 | Self::UnionPaySdk | Self::ScanPayUnion => {
+                UnionPayCallback::parse(value).map(TypedCallback::UnionPay)
+            }
+            Self::AppleIap => AppleCallback::parse(value).map(TypedCallback::Apple),
+            _ => Err(PaymentError::UnsupportedPaymentType(self.to_string())),
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(channel: &str, value: &Value) -> Result<T, PaymentError> {
+    T::deserialize(value).map_err(|e| PaymentError::InvalidCallback(format!("{}回调格式错误: {}", channel, e)))
+}
+
+/// 微信支付结果通知
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WechatCallback {
+    pub out_trade_no: String,
+    pub result_code: String,
+    #[serde(default)]
+    pub transaction_id: Option<String>,
+    /// 订单金额，单位分
+    #[serde(default)]
+    pub total_fee: Option<i64>,
+}
+
+impl WechatCallback {
+    pub fn parse(value: &Value) -> Result<Self, PaymentError> {
+        parse("微信", value)
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        if self.result_code == "SUCCESS" {
+            OrderStatus::Success
+        } else {
+            OrderStatus::Failed
+        }
+    }
+}
+
+/// 支付宝异步通知
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AlipayCallback {
+    pub out_trade_no: String,
+    pub trade_status: String,
+    #[serde(default)]
+    pub trade_no: Option<String>,
+    /// 订单金额，单位元
+    #[serde(default)]
+    pub total_amount: Option<String>,
+}
+
+impl AlipayCallback {
+    pub fn parse(value: &Value) -> Result<Self, PaymentError> {
+        parse("支付宝", value)
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        match self.trade_status.as_str() {
+            "TRADE_SUCCESS" | "TRADE_FINISHED" => OrderStatus::Success,
+            "TRADE_CLOSED" => OrderStatus::Refunded,
+            _ => OrderStatus::Processing,
+        }
+    }
+}
+
+/// 银联退款时 `reqReserved` 中订单号与退款单号的分隔符
+const UNIONPAY_REFUND_SEPARATOR: char = '|';
+
+/// 银联后台通知
+///
+/// 验签需要全部字段，保留原始参数；通知字段均为字符串，其他类型的字段忽略。
+/// 支付和退款的结果通知推送到同一个 backUrl，按 `txnType` 区分
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnionPayCallback {
+    params: BTreeMap<String, String>,
+}
+
+impl UnionPayCallback {
+    /// 退款请求的 `reqReserved`：原样回传 `订单号|退款单号`，退款通知据此定位退款
+    pub fn refund_reserved(order_id: &str, refund_no: &str) -> String {
+        format!("{}{}{}", order_id, UNIONPAY_REFUND_SEPARATOR, refund_no)
+    }
+
+    pub fn parse(value: &Value) -> Result<Self, PaymentError> {
+        let params: BTreeMap<String, String> = value
+            .as_object()
+            .ok_or_else(|| PaymentError::InvalidCallback("银联通知格式错误".to_string()))?
+            .iter()
+            .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+            .collect();
+
+        if !params.contains_key("reqReserved") && !params.contains_key("orderId") {
+            return Err(PaymentError::InvalidCallback("银联通知缺少 orderId".to_string()));
+        }
+        Ok(Self { params })
+    }
+
+    /// 下单时通过 `reqReserved` 透传本系统订单号，没有时使用银联订单号
+    pub fn order_id(&self) -> &str {
+        self.params.get("reqReserved")
+            .map(|reserved| reserved.split(UNIONPAY_REFUND_SEPARATOR).next().unwrap_or_default())
+            .or_else(|| self.params.get("orderId").map(String::as_str))
+            .unwrap_or_default()
+    }
+
+    /// 是否为退款（`txnType=04`）的结果通知
+    pub fn is_refund(&self) -> bool {
+        self.params.get("txnType").map(String::as_str) == Some("04")
+    }
+
+    /// 退款通知中透传回来的退款单号
+    pub fn refund_no(&self) -> Option<&str> {
+        self.params.get("reqReserved")
+            .and_then(|reserved| reserved.split_once(UNIONPAY_REFUND_SEPARATOR))
+            .map(|(_, refund_no)| refund_no)
+    }
+
+    /// 银联交易流水号，退款等后续交易通过 `origQryId` 引用原交易
+    pub fn query_id(&self) -> Option<&str> {
+        self.params.get("queryId").map(String::as_str)
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        match self.params.get("respCode").map(String::as_str) {
+            Some("00") | Some("A6") => OrderStatus::Success,
+            _ => OrderStatus::Failed,
+        }
+    }
+
+    /// 参与验签的全部参数
+    pub fn params(&self) -> &BTreeMap<String, String> {
+        &self.params
+    }
+}
+
+/// Apple 内购客户端回传的收据
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AppleCallback {
+    pub order_id: String,
+    #[serde(rename = "receipt-data")]
+    pub receipt_data: String,
+}
+
+impl AppleCallback {
+    pub fn parse(value: &Value) -> Result<Self, PaymentError> {
+        parse("Apple", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_wechat_callback() {
+        let callback = PaymentType::WxH5.parse_callback(&json!({
+            "return_code": "SUCCESS",
+            "result_code": "SUCCESS",
+            "out_trade_no": "order_1",
+            "transaction_id": "4200000123456789",
+            "total_fee": 10000
+        }))
+        .unwrap();
+
+        let TypedCallback::Wechat(wechat) = &callback else {
+            panic!("expected wechat callback, got {:?}", callback);
+        };
+        assert_eq!(callback.order_id(), "order_1");
+        assert_eq!(wechat.transaction_id.as_deref(), Some("4200000123456789"));
+        assert_eq!(wechat.total_fee, Some(10000));
+        assert_eq!(wechat.status(), OrderStatus::Success);
+
+        // 缺少订单号
+        let result = PaymentType::WxH5.parse_callback(&json!({ "result_code": "SUCCESS" }));
+        assert!(matches!(result, Err(PaymentError::InvalidCallback(_))));
+
+        // 金额类型错误
+        let result = PaymentType::WxH5.parse_callback(&json!({
+            "result_code": "SUCCESS",
+            "out_trade_no": "order_1",
+            "total_fee": "100.00"
+        }));
+        assert!(matches!(result, Err(PaymentError::InvalidCallback(_))));
+    }
+
+    #[test]
+    fn test_parse_unionpay_callback() {
+        let callback = PaymentType::UnionPayH5
+            .parse_callback(&json!({ "orderId": "20240101", "reqReserved": "order_1", "respCode": "00", "txnAmt": "100" }))
+            .unwrap();
+        assert_eq!(callback.order_id(), "order_1");
+
+        // 退款通知透传订单号和退款单号
+        let callback = UnionPayCallback::parse(&json!({
+            "orderId": "R1",
+            "reqReserved": UnionPayCallback::refund_reserved("order_1", "R1"),
+            "txnType": "04",
+            "queryId": "771610246453102098801",
+            "respCode": "00"
+        }))
+        .unwrap();
+        assert!(callback.is_refund());
+        assert_eq!(callback.order_id(), "order_1");
+        assert_eq!(callback.refund_no(), Some("R1"));
+        assert_eq!(callback.query_id(), Some("771610246453102098801"));
+
+        let result = PaymentType::UnionPayH5.parse_callback(&json!({ "respCode": "00" }));
+        assert!(matches!(result, Err(PaymentError::InvalidCallback(_))));
+    }
+}
@@ -46,6 +46,18 @@ impl PaymentFactory {
             Arc::new(RateLimitedStrategy::new(apple_iap, 200))
         );
 
+        for (payment_type, mode) in [
+            (PaymentType::UnionPayH5, unionpay::UnionPayMode::H5),
+            (PaymentType::UnionPaySdk, unionpay::UnionPayMode::App),
+            (PaymentType::ScanPayUnion, unionpay::UnionPayMode::Qr),
+        ] {
+            let union_pay = Arc::new(unionpay::UnionPayStrategy::new(mode));
+            strategies.insert(
+                payment_type,
+                Arc::new(RateLimitedStrategy::new(union_pay, 50))
+            );
+        }
+
         // ... 其他支付方式
 
         Self { strategies, config_cache }
@@ -58,6 +70,16 @@ impl PaymentFactory {
             .ok_or_else(|| PaymentError::UnsupportedPaymentType(payment_type.to_string()))
     }
 
+    /// 注册或替换支付策略
+    pub fn register(&mut self, payment_type: PaymentType, strategy: Arc<dyn PaymentStrategy>) {
+        self.strategies.insert(payment_type, strategy);
+    }
+
+    /// 所有已注册的支付策略
+    pub fn strategies(&self) -> impl Iterator<Item = (PaymentType, Arc<dyn PaymentStrategy>)> + '_ {
+        self.strategies.iter().map(|(payment_type, strategy)| (*payment_type, strategy.clone()))
+    }
+
     pub fn config_cache(&self) -> Arc<ConfigCache> {
         self.config_cache.clone()
     }
@@ -89,6 +111,8 @@ mod tests {
         let zfb_h5_strategy = factory.get_strategy(&PaymentType::ZfbH5);
         assert!(zfb_h5_strategy.is_ok());
 
+        assert!(factory.get_strategy(&PaymentType::UnionPaySdk).is_ok());
+
         // 测试获取未注册的策略
         let unknown_strategy = factory.get_strategy(&PaymentType::PaypalH5);
         assert!(unknown_strategy.is_err());
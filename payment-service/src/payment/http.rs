@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use crate::error::PaymentError;
+
+/// 发往支付渠道的 HTTP 请求
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// `application/x-www-form-urlencoded` 表单提交
+    pub fn form<T: Serialize + ?Sized>(url: &str, params: &T) -> Result<Self, PaymentError> {
+        let body = serde_urlencoded::to_string(params)
+            .map_err(|e| PaymentError::Internal(format!("请求参数编码失败: {}", e)))?;
+        Ok(Self {
+            method: "POST".to_string(),
+            url: url.to_string(),
+            content_type: Some("application/x-www-form-urlencoded".to_string()),
+            body,
+        })
+    }
+}
+
+/// 支付渠道返回的 HTTP 响应
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub body: String,
+}
+
+/// 渠道适配器访问支付网关使用的 HTTP 客户端
+///
+/// 测试中可以替换为 [`ReplayHttpClient`](crate::payment::replay::ReplayHttpClient)，
+/// 用录制的请求/响应回放渠道交互
+#[async_trait]
+pub trait PaymentHttpClient: Send + Sync {
+    async fn send(&self, request: &HttpRequest) -> Result<HttpResponse, PaymentError>;
+}
+
+/// 基于 reqwest 的默认实现
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestHttpClient {
+    client: reqwest::Client,
+}
+
+impl ReqwestHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PaymentHttpClient for ReqwestHttpClient {
+    async fn send(&self, request: &HttpRequest) -> Result<HttpResponse, PaymentError> {
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .map_err(|e| PaymentError::Internal(format!("无效的请求方法 {}: {}", request.method, e)))?;
+        let mut builder = self.client.request(method, &request.url).body(request.body.clone());
+        if let Some(content_type) = &request.content_type {
+            builder = builder.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| {
+                // 连接失败时请求没有发出，其他错误（如超时）无法确定渠道是否已处理
+                if e.is_connect() || e.is_builder() {
+                    PaymentError::Internal(format!("渠道请求失败: {}", e))
+                } else {
+                    PaymentError::ChannelOutcomeUnknown(format!("渠道请求失败: {}", e))
+                }
+            })?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| PaymentError::ChannelOutcomeUnknown(format!("渠道响应读取失败: {}", e)))?;
+        Ok(HttpResponse { status, body })
+    }
+}
@@ -1,3 +1,6 @@
+pub mod callback;
 pub mod factory;
+pub mod http;
 pub mod strategy;
-pub mod providers;
\ No newline at end of file
+pub mod providers;
+pub mod replay;
\ No newline at end of file
@@ -2,8 +2,18 @@ use async_trait::async_trait;
 use crate::error::PaymentError;
 use crate::models::payment::*;
 use crate::models::enums::OrderStatus;
-use crate::payment::strategy::PaymentStrategy;
+use crate::payment::callback::AlipayCallback;
+use crate::payment::strategy::{ChannelNotification, PaymentStrategy};
 use crate::domain::payment::PaymentOrder;
+use chrono::{DateTime, Utc};
+
+/// 订单剩余有效期，格式为支付宝 `timeout_express` 的分钟数（如 `29m`）
+///
+/// 向下取整，保证支付宝侧不晚于订单过期；支付宝要求至少 1 分钟
+fn timeout_express(order: &PaymentOrder, now: DateTime<Utc>) -> String {
+    let minutes = (order.expire_at() - now).num_minutes().max(1);
+    format!("{}m", minutes)
+}
 
 pub struct AlipayH5Strategy;
 
@@ -35,6 +45,7 @@ impl PaymentStrategy for AlipayH5Strategy {
             "total_amount": (order.amount.amount as f64 / 100.0).to_string(), // 转换为元
             "subject": request.product_name,
             "product_code": "QUICK_WAP_WAY",
+            "timeout_express": timeout_express(order, Utc::now()),
             "body": request.product_desc.clone().unwrap_or_default()
         });
 
@@ -70,8 +81,7 @@ impl PaymentStrategy for AlipayH5Strategy {
 
         Ok(CreatePaymentResponse {
             order_id: order.order_id.clone(),
-            payment_url: Some(payment_url),
-            payment_params: None,
+            instruction: PaymentInstruction::Redirect(payment_url),
         })
     }
 
@@ -79,7 +89,7 @@ impl PaymentStrategy for AlipayH5Strategy {
         &self,
         order: &PaymentOrder,
         config: &PaymentConfig,
-    ) -> Result<OrderStatus, PaymentError> {
+    ) -> Result<(OrderStatus, Option<String>), PaymentError> {
         // 在实际实现中，需要调用支付宝查询订单API
         // 这里简化处理，模拟返回成功状态
 
@@ -96,42 +106,37 @@ impl PaymentStrategy for AlipayH5Strategy {
         });
 
         // 解析响应
-        let trade_status = api_response["alipay_trade_query_response"]["trade_status"].as_str().unwrap_or("WAIT_BUYER_PAY");
+        let response = &api_response["alipay_trade_query_response"];
+        let trade_status = response["trade_status"].as_str().unwrap_or("WAIT_BUYER_PAY");
+        let trade_no = response["trade_no"].as_str().map(str::to_string);
 
-        match trade_status {
-            "TRADE_SUCCESS" | "TRADE_FINISHED" => Ok(OrderStatus::Success),
-            "TRADE_CLOSED" => Ok(OrderStatus::Refunded),
-            "WAIT_BUYER_PAY" => Ok(OrderStatus::Processing),
-            _ => Ok(OrderStatus::Processing),
-        }
+        let status = match trade_status {
+            "TRADE_SUCCESS" | "TRADE_FINISHED" => OrderStatus::Success,
+            "TRADE_CLOSED" => OrderStatus::Refunded,
+            "WAIT_BUYER_PAY" => OrderStatus::Processing,
+            _ => OrderStatus::Processing,
+        };
+
+        Ok((status, trade_no))
     }
 
     async fn handle_callback(
         &self,
         config: &PaymentConfig,
         callback_data: &serde_json::Value,
-    ) -> Result<(String, OrderStatus), PaymentError> {
+    ) -> Result<ChannelNotification, PaymentError> {
         // 1. 验证签名
         // 实际实现中需要验证支付宝回调的签名
 
         // 2. 解析订单号和支付状态
-        let order_id = callback_data["out_trade_no"]
-            .as_str()
-            .ok_or_else(|| PaymentError::Internal("Missing out_trade_no in callback data".to_string()))?
-            .to_string();
-
-        let trade_status = callback_data["trade_status"]
-            .as_str()
-            .unwrap_or("WAIT_BUYER_PAY");
-
-        let status = match trade_status {
-            "TRADE_SUCCESS" | "TRADE_FINISHED" => OrderStatus::Success,
-            "TRADE_CLOSED" => OrderStatus::Refunded,
-            "WAIT_BUYER_PAY" => OrderStatus::Processing,
-            _ => OrderStatus::Processing,
-        };
+        let callback = AlipayCallback::parse(callback_data)?;
+        let status = callback.status();
 
-        Ok((order_id, status))
+        Ok(ChannelNotification::Payment {
+            order_id: callback.out_trade_no,
+            status,
+            transaction_id: callback.trade_no,
+        })
     }
 
     async fn refund(
@@ -142,10 +147,10 @@ impl PaymentStrategy for AlipayH5Strategy {
     ) -> Result<String, PaymentError> {
         // 实现支付宝退款逻辑
         // 1. 构建退款请求参数
-        let refund_id = uuid::Uuid::new_v4().to_string();
+        let refund_id = refund_request.refund_no();
         let biz_content = serde_json::json!({
             "out_trade_no": order.order_id,
-            "refund_amount": (refund_request.refund_amount as f64 / 100.0).to_string(),
+            "refund_amount": (refund_request.refund_amount.to_minor(order.amount.currency)? as f64 / 100.0).to_string(),
             "out_request_no": refund_id,
             "refund_reason": refund_request.refund_reason.clone().unwrap_or_else(|| "客户退款".to_string())
         });
@@ -192,6 +197,7 @@ impl PaymentStrategy for AlipaySdkStrategy {
             "total_amount": (order.amount.amount as f64 / 100.0).to_string(), // 转换为元
             "subject": request.product_name,
             "product_code": "QUICK_MSECURITY_PAY",
+            "timeout_express": timeout_express(order, Utc::now()),
             "body": request.product_desc.clone().unwrap_or_default()
         });
 
@@ -225,8 +231,7 @@ impl PaymentStrategy for AlipaySdkStrategy {
 
         Ok(CreatePaymentResponse {
             order_id: order.order_id.clone(),
-            payment_url: None,
-            payment_params: Some(serde_json::json!({
+            instruction: PaymentInstruction::AppParams(serde_json::json!({
                 "orderString": order_string
             })),
         })
@@ -236,7 +241,7 @@ impl PaymentStrategy for AlipaySdkStrategy {
         &self,
         order: &PaymentOrder,
         config: &PaymentConfig,
-    ) -> Result<OrderStatus, PaymentError> {
+    ) -> Result<(OrderStatus, Option<String>), PaymentError> {
         // 查询逻辑与H5支付相同
         let alipay_h5 = AlipayH5Strategy::new();
         alipay_h5.query_order(order, config).await
@@ -246,7 +251,7 @@ impl PaymentStrategy for AlipaySdkStrategy {
         &self,
         config: &PaymentConfig,
         callback_data: &serde_json::Value,
-    ) -> Result<(String, OrderStatus), PaymentError> {
+    ) -> Result<ChannelNotification, PaymentError> {
         // 回调处理逻辑与H5支付相同
         let alipay_h5 = AlipayH5Strategy::new();
         alipay_h5.handle_callback(config, callback_data).await
@@ -269,6 +274,17 @@ mod tests {
     use super::*;
     use crate::domain::money::Money;
 
+    #[test]
+    fn test_timeout_express_within_order_window() {
+        let order = PaymentOrder::new(1, 100, crate::models::enums::PaymentType::ZfbH5, Money::cny(100), None, None, None, Utc::now());
+        let created_at = order.created_at;
+
+        assert_eq!(timeout_express(&order, created_at), "30m");
+        // 不足一分钟的部分舍去，支付宝侧先于订单过期
+        assert_eq!(timeout_express(&order, created_at + chrono::Duration::seconds(630)), "19m");
+        assert_eq!(timeout_express(&order, order.expire_at() - chrono::Duration::seconds(20)), "1m");
+    }
+
     #[tokio::test]
     async fn test_alipay_h5_create_order() {
         let strategy = AlipayH5Strategy::new();
@@ -282,6 +298,7 @@ mod tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            Utc::now(),
         );
 
         // 创建测试配置
@@ -300,6 +317,8 @@ mod tests {
             notify_url: "https://www.example.com/notify".to_string(),
             return_url: Some("https://www.example.com/return".to_string()),
             extra_config: None,
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -308,10 +327,14 @@ mod tests {
         // 创建支付请求
         let request = CreatePaymentRequest {
             tenant_id: 1,
+            merchant_order_id: None,
             user_id: 100,
             payment_type: crate::models::enums::PaymentType::ZfbH5,
             amount: 10000,
             currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: TransactionType::Sale,
             product_name: "测试商品".to_string(),
             product_desc: Some("商品描述".to_string()),
             callback_url: Some("http://example.com/callback".to_string()),
@@ -325,8 +348,10 @@ mod tests {
 
         let response = result.unwrap();
         assert_eq!(response.order_id, order.order_id);
-        assert!(response.payment_url.is_some());
-        assert!(response.payment_url.unwrap().contains("openapi.alipay.com"));
+        let PaymentInstruction::Redirect(url) = response.instruction else {
+            panic!("H5 支付应返回跳转地址");
+        };
+        assert!(url.contains("openapi.alipay.com"));
     }
 
     #[tokio::test]
@@ -342,6 +367,7 @@ mod tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            Utc::now(),
         );
 
         // 创建测试配置
@@ -360,6 +386,8 @@ mod tests {
             notify_url: "https://www.example.com/notify".to_string(),
             return_url: None,
             extra_config: None,
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -368,10 +396,14 @@ mod tests {
         // 创建支付请求
         let request = CreatePaymentRequest {
             tenant_id: 1,
+            merchant_order_id: None,
             user_id: 100,
             payment_type: crate::models::enums::PaymentType::ZfbSdk,
             amount: 10000,
             currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: TransactionType::Sale,
             product_name: "测试商品".to_string(),
             product_desc: Some("商品描述".to_string()),
             callback_url: Some("http://example.com/callback".to_string()),
@@ -385,10 +417,9 @@ mod tests {
 
         let response = result.unwrap();
         assert_eq!(response.order_id, order.order_id);
-        assert!(response.payment_url.is_none());
-        assert!(response.payment_params.is_some());
-
-        let params = response.payment_params.unwrap();
+        let PaymentInstruction::AppParams(params) = response.instruction else {
+            panic!("SDK 支付应返回客户端参数");
+        };
         assert!(params.get("orderString").is_some());
     }
 }
\ No newline at end of file
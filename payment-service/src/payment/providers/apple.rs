@@ -2,7 +2,8 @@ use async_trait::async_trait;
 use crate::error::PaymentError;
 use crate::models::payment::*;
 use crate::models::enums::OrderStatus;
-use crate::payment::strategy::PaymentStrategy;
+use crate::payment::callback::AppleCallback;
+use crate::payment::strategy::{ChannelNotification, PaymentStrategy};
 use crate::domain::payment::PaymentOrder;
 
 pub struct AppleIapStrategy;
@@ -49,11 +50,12 @@ impl PaymentStrategy for AppleIapStrategy {
         _config: &PaymentConfig,
         _request: &CreatePaymentRequest,
     ) -> Result<CreatePaymentResponse, PaymentError> {
-        // Apple IAP 通常在客户端完成，这里只需要记录订单  
+        // Apple IAP 在客户端完成，这里只需要记录订单，客户端购买时带上订单号用于回传凭证时关联
         Ok(CreatePaymentResponse {
             order_id: order.order_id.clone(),
-            payment_url: None,
-            payment_params: None,
+            instruction: PaymentInstruction::AppParams(serde_json::json!({
+                "order_id": order.order_id
+            })),
         })
     }
 
@@ -61,34 +63,26 @@ impl PaymentStrategy for AppleIapStrategy {
         &self,
         order: &PaymentOrder,
         _config: &PaymentConfig,
-    ) -> Result<OrderStatus, PaymentError> {
+    ) -> Result<(OrderStatus, Option<String>), PaymentError> {
         // 对于 Apple IAP，通常不会主动查询订单状态  
         // 而是依赖客户端回调或收据验证  
 
         // 如果订单状态不是 Pending，则直接返回当前状态  
         if order.status != OrderStatus::Pending {
-            return Ok(order.status);
+            return Ok((order.status, order.third_party_order_id.clone()));
         }
 
         // 否则返回处理中状态  
-        Ok(OrderStatus::Processing)
+        Ok((OrderStatus::Processing, None))
     }
 
     async fn handle_callback(
         &self,
         config: &PaymentConfig,
         callback_data: &serde_json::Value,
-    ) -> Result<(String, OrderStatus), PaymentError> {
-        // 从回调数据中获取订单ID  
-        let order_id = callback_data["order_id"]
-            .as_str()
-            .ok_or_else(|| PaymentError::Internal("Missing order_id in callback data".to_string()))?
-            .to_string();
-
-        // 从回调数据中获取收据数据  
-        let receipt_data = callback_data["receipt-data"]
-            .as_str()
-            .ok_or_else(|| PaymentError::Internal("Missing receipt-data in callback data".to_string()))?;
+    ) -> Result<ChannelNotification, PaymentError> {
+        // 解析订单ID和收据数据
+        let AppleCallback { order_id, receipt_data } = AppleCallback::parse(callback_data)?;
 
         // 检查是否是沙箱环境  
         let is_sandbox = config.extra_config
@@ -98,14 +92,21 @@ impl PaymentStrategy for AppleIapStrategy {
             .unwrap_or(false);
 
         // 验证收据  
-        let verification_response = self.verify_receipt(receipt_data, is_sandbox).await?;
+        let verification_response = self.verify_receipt(&receipt_data, is_sandbox).await?;
 
         // 解析验证结果  
         let status = verification_response["status"].as_i64().unwrap_or(1);
 
         if status == 0 {
-            // 验证成功  
-            Ok((order_id, OrderStatus::Success))
+            // 验证成功，收据中的交易号作为渠道交易号
+            let transaction_id = verification_response["receipt"]["in_app"][0]["transaction_id"]
+                .as_str()
+                .map(str::to_string);
+            Ok(ChannelNotification::Payment {
+                order_id,
+                status: OrderStatus::Success,
+                transaction_id,
+            })
         } else {
             // 验证失败  
             let error_message = format!("Apple receipt verification failed with status: {}", status);
@@ -149,6 +150,7 @@ mod tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            chrono::Utc::now(),
         );
 
         // 创建测试配置
@@ -169,6 +171,8 @@ mod tests {
             extra_config: Some(serde_json::json!({
                 "is_sandbox": true
             })),
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -177,10 +181,14 @@ mod tests {
         // 创建支付请求
         let request = CreatePaymentRequest {
             tenant_id: 1,
+            merchant_order_id: None,
             user_id: 100,
             payment_type: crate::models::enums::PaymentType::AppleIap,
             amount: 10000,
             currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: TransactionType::Sale,
             product_name: "测试商品".to_string(),
             product_desc: Some("商品描述".to_string()),
             callback_url: Some("http://example.com/callback".to_string()),
@@ -194,8 +202,10 @@ mod tests {
 
         let response = result.unwrap();
         assert_eq!(response.order_id, order.order_id);
-        assert!(response.payment_url.is_none());
-        assert!(response.payment_params.is_none());
+        let PaymentInstruction::AppParams(params) = response.instruction else {
+            panic!("Apple IAP 应返回客户端参数");
+        };
+        assert_eq!(params["order_id"], order.order_id.as_str());
     }
 
     #[tokio::test]
@@ -220,6 +230,8 @@ mod tests {
             extra_config: Some(serde_json::json!({
                 "is_sandbox": true
             })),
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -237,9 +249,14 @@ mod tests {
         let result = strategy.handle_callback(&config, &callback_data).await;
         assert!(result.is_ok());
 
-        let (order_id, status) = result.unwrap();
-        assert_eq!(order_id, "test_order_123");
-        assert_eq!(status, OrderStatus::Success);
+        assert_eq!(
+            result.unwrap(),
+            ChannelNotification::Payment {
+                order_id: "test_order_123".to_string(),
+                status: OrderStatus::Success,
+                transaction_id: Some("1000000123456789".to_string()),
+            }
+        );
     }
 
     #[tokio::test]
@@ -255,6 +272,7 @@ mod tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            chrono::Utc::now(),
         );
 
         // 创建测试配置
@@ -273,6 +291,8 @@ mod tests {
             notify_url: "https://www.example.com/notify".to_string(),
             return_url: None,
             extra_config: None,
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -281,8 +301,10 @@ mod tests {
         // 创建退款请求
         let refund_request = RefundRequest {
             order_id: order.order_id.clone(),
-            refund_amount: 10000,
+            refund_amount: 10000.into(),
             refund_reason: Some("测试退款".to_string()),
+            destination: RefundDestination::Original,
+            merchant_refund_no: None,
         };
 
         // 测试退款
@@ -1,3 +1,12 @@
+use chrono::{DateTime, FixedOffset, Utc};
+
 pub mod wechat;
 pub mod alipay;
-pub mod apple;
\ No newline at end of file
+pub mod apple;
+pub mod unionpay;
+
+/// 北京时间 `yyyyMMddHHmmss`，微信、银联接口的时间字段使用该格式
+pub(crate) fn beijing_time(time: DateTime<Utc>) -> String {
+    let beijing = FixedOffset::east_opt(8 * 3600).unwrap();
+    time.with_timezone(&beijing).format("%Y%m%d%H%M%S").to_string()
+}
@@ -0,0 +1,694 @@
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::error::PaymentError;
+use crate::models::payment::*;
+use crate::models::enums::OrderStatus;
+use crate::payment::callback::UnionPayCallback;
+use crate::payment::http::{HttpRequest, PaymentHttpClient, ReqwestHttpClient};
+use crate::domain::refund::ChannelRefundStatus;
+use crate::payment::strategy::{ChannelNotification, PaymentStrategy};
+use crate::domain::payment::PaymentOrder;
+use super::beijing_time;
+
+/// 银联全渠道接口版本
+const VERSION: &str = "5.1.0";
+/// 签名方式：RSA-SHA256
+const SIGN_METHOD: &str = "01";
+
+const FRONT_TRANS_PATH: &str = "/gateway/api/frontTransReq.do";
+const APP_TRANS_PATH: &str = "/gateway/api/appTransReq.do";
+const BACK_TRANS_PATH: &str = "/gateway/api/backTransReq.do";
+const QUERY_TRANS_PATH: &str = "/gateway/api/queryTrans.do";
+
+/// 银联支付场景
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnionPayMode {
+    /// 手机网页支付，返回需要自动提交的表单
+    H5,
+    /// 控件支付，返回 App 调起支付使用的 tn
+    App,
+    /// 商户主扫二维码，返回二维码内容
+    Qr,
+}
+
+/// 银联全渠道支付
+///
+/// 商户配置：`merchant_id` 为银联商户号，`private_key` 为签名私钥（PEM），
+/// `public_key` 为银联验签公钥（PEM），`extra_config.cert_id` 为签名证书序列号
+pub struct UnionPayStrategy {
+    mode: UnionPayMode,
+    http: Arc<dyn PaymentHttpClient>,
+}
+
+impl UnionPayStrategy {
+    pub fn new(mode: UnionPayMode) -> Self {
+        Self {
+            mode,
+            http: Arc::new(ReqwestHttpClient::new()),
+        }
+    }
+
+    /// 替换访问银联网关的 HTTP 客户端，测试中用于回放录制的交互
+    pub fn with_http_client(mut self, http: Arc<dyn PaymentHttpClient>) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// 签名原文：除 `signature` 外的非空字段按键名排序后以 `k=v&k=v` 拼接
+    fn sign_content(params: &BTreeMap<String, String>) -> String {
+        params
+            .iter()
+            .filter(|(key, value)| key.as_str() != "signature" && !value.is_empty())
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// 对签名原文的 SHA-256 十六进制摘要做 RSA-SHA256 签名，写入 `signature`
+    fn sign(params: &mut BTreeMap<String, String>, config: &PaymentConfig) -> Result<(), PaymentError> {
+        let private_key = config.private_key.as_deref()
+            .ok_or_else(|| PaymentError::Configuration("银联商户缺少签名私钥".to_string()))?;
+        let private_key = RsaPrivateKey::from_pkcs8_pem(private_key)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(private_key))
+            .map_err(|e| PaymentError::Configuration(format!("银联签名私钥无效: {}", e)))?;
+
+        let digest = format!("{:x}", Sha256::digest(Self::sign_content(params).as_bytes()));
+        let signature = SigningKey::<Sha256>::new(private_key).sign(digest.as_bytes());
+        params.insert("signature".to_string(), BASE64.encode(signature.to_bytes()));
+        Ok(())
+    }
+
+    /// 使用银联公钥验证响应或通知的签名
+    fn verify(params: &BTreeMap<String, String>, config: &PaymentConfig) -> Result<(), PaymentError> {
+        let public_key = config.public_key.as_deref()
+            .ok_or_else(|| PaymentError::Configuration("银联商户缺少验签公钥".to_string()))?;
+        let public_key = RsaPublicKey::from_public_key_pem(public_key)
+            .or_else(|_| RsaPublicKey::from_pkcs1_pem(public_key))
+            .map_err(|e| PaymentError::Configuration(format!("银联验签公钥无效: {}", e)))?;
+
+        let invalid = || PaymentError::ExternalApi {
+            code: "INVALID_SIGNATURE".to_string(),
+            message: "银联签名验证失败".to_string(),
+        };
+        let signature = params.get("signature").ok_or_else(invalid)?;
+        let signature = BASE64.decode(signature).map_err(|_| invalid())?;
+        let signature = Signature::try_from(signature.as_slice()).map_err(|_| invalid())?;
+
+        let digest = format!("{:x}", Sha256::digest(Self::sign_content(params).as_bytes()));
+        VerifyingKey::<Sha256>::new(public_key)
+            .verify(digest.as_bytes(), &signature)
+            .map_err(|_| invalid())
+    }
+
+    fn cert_id(config: &PaymentConfig) -> Result<String, PaymentError> {
+        config.extra_config
+            .as_ref()
+            .and_then(|extra| extra.get("cert_id"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .ok_or_else(|| PaymentError::Configuration("银联商户缺少签名证书序列号 cert_id".to_string()))
+    }
+
+    /// 银联订单号只允许字母和数字，对账时按同样的规则匹配本地订单
+    pub fn union_order_id(order_id: &str) -> String {
+        order_id.chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+    }
+
+    /// 交易时间使用北京时间 `yyyyMMddHHmmss`，查询时需要与下单时一致
+    fn txn_time(time: DateTime<Utc>) -> String {
+        beijing_time(time)
+    }
+
+    /// 所有交易共用的字段
+    fn base_params(config: &PaymentConfig, txn_type: &str, txn_sub_type: &str) -> Result<BTreeMap<String, String>, PaymentError> {
+        let mut params = BTreeMap::new();
+        params.insert("version".to_string(), VERSION.to_string());
+        params.insert("encoding".to_string(), "UTF-8".to_string());
+        params.insert("signMethod".to_string(), SIGN_METHOD.to_string());
+        params.insert("certId".to_string(), Self::cert_id(config)?);
+        params.insert("txnType".to_string(), txn_type.to_string());
+        params.insert("txnSubType".to_string(), txn_sub_type.to_string());
+        params.insert("accessType".to_string(), "0".to_string());
+        params.insert("merId".to_string(), config.merchant_id.clone());
+        Ok(params)
+    }
+
+    fn url(config: &PaymentConfig, path: &str) -> String {
+        format!("{}{}", config.gateway_url.trim_end_matches('/'), path)
+    }
+
+    /// 后台同步请求，验证响应签名后返回响应字段
+    async fn post(&self, url: &str, params: &BTreeMap<String, String>, config: &PaymentConfig) -> Result<BTreeMap<String, String>, PaymentError> {
+        let response = self.http.send(&HttpRequest::form(url, params)?).await?;
+        // 网关错误时银联可能已经受理
+        if response.status >= 500 {
+            return Err(PaymentError::ChannelOutcomeUnknown(format!("银联返回 HTTP {}", response.status)));
+        }
+        let body = response.body;
+
+        let response: BTreeMap<String, String> = serde_urlencoded::from_str(&body)
+            .map_err(|e| PaymentError::Internal(format!("银联响应解析失败: {}", e)))?;
+        Self::verify(&response, config)?;
+        Ok(response)
+    }
+
+    fn check_resp_code(response: &BTreeMap<String, String>) -> Result<(), PaymentError> {
+        match response.get("respCode").map(String::as_str) {
+            Some("00") => Ok(()),
+            code => Err(PaymentError::ExternalApi {
+                code: code.unwrap_or("UNKNOWN").to_string(),
+                message: response.get("respMsg").cloned().unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentStrategy for UnionPayStrategy {
+    async fn create_order(
+        &self,
+        order: &PaymentOrder,
+        config: &PaymentConfig,
+        request: &CreatePaymentRequest,
+    ) -> Result<CreatePaymentResponse, PaymentError> {
+        let (txn_sub_type, biz_type, channel_type) = match self.mode {
+            UnionPayMode::H5 => ("01", "000201", "08"),
+            UnionPayMode::App => ("01", "000201", "08"),
+            UnionPayMode::Qr => ("07", "000000", "08"),
+        };
+
+        let mut params = Self::base_params(config, "01", txn_sub_type)?;
+        params.insert("bizType".to_string(), biz_type.to_string());
+        params.insert("channelType".to_string(), channel_type.to_string());
+        params.insert("orderId".to_string(), Self::union_order_id(&order.order_id));
+        params.insert("txnTime".to_string(), Self::txn_time(order.created_at));
+        // 支付超时时间与订单过期时间一致
+        params.insert("payTimeout".to_string(), beijing_time(order.expire_at()));
+        params.insert("txnAmt".to_string(), order.amount.amount.to_string());
+        // 交易币种使用 ISO 4217 数字代码
+        params.insert("currencyCode".to_string(), order.amount.currency.numeric_code());
+        params.insert("backUrl".to_string(), config.notify_url.clone());
+        params.insert("orderDesc".to_string(), request.product_name.clone());
+        // 原样回传本系统订单号，通知中据此定位订单
+        params.insert("reqReserved".to_string(), order.order_id.clone());
+        if let Some(return_url) = &config.return_url {
+            params.insert("frontUrl".to_string(), return_url.clone());
+        }
+        Self::sign(&mut params, config)?;
+
+        match self.mode {
+            // 网页支付由浏览器自动提交表单到银联前台
+            UnionPayMode::H5 => Ok(CreatePaymentResponse {
+                order_id: order.order_id.clone(),
+                instruction: PaymentInstruction::html_form(&Self::url(config, FRONT_TRANS_PATH), &params),
+            }),
+            UnionPayMode::App => {
+                let response = self.post(&Self::url(config, APP_TRANS_PATH), &params, config).await?;
+                Self::check_resp_code(&response)?;
+                let tn = response.get("tn")
+                    .ok_or_else(|| PaymentError::Internal("银联响应缺少 tn".to_string()))?;
+
+                Ok(CreatePaymentResponse {
+                    order_id: order.order_id.clone(),
+                    instruction: PaymentInstruction::AppParams(serde_json::json!({ "tn": tn })),
+                })
+            }
+            UnionPayMode::Qr => {
+                let response = self.post(&Self::url(config, BACK_TRANS_PATH), &params, config).await?;
+                Self::check_resp_code(&response)?;
+                let qr_code = response.get("qrCode")
+                    .ok_or_else(|| PaymentError::Internal("银联响应缺少 qrCode".to_string()))?;
+
+                Ok(CreatePaymentResponse {
+                    order_id: order.order_id.clone(),
+                    instruction: PaymentInstruction::QrCode(qr_code.clone()),
+                })
+            }
+        }
+    }
+
+    async fn query_order(
+        &self,
+        order: &PaymentOrder,
+        config: &PaymentConfig,
+    ) -> Result<(OrderStatus, Option<String>), PaymentError> {
+        let mut params = Self::base_params(config, "00", "00")?;
+        params.insert("bizType".to_string(), "000000".to_string());
+        params.insert("orderId".to_string(), Self::union_order_id(&order.order_id));
+        params.insert("txnTime".to_string(), Self::txn_time(order.created_at));
+        Self::sign(&mut params, config)?;
+
+        let response = self.post(&Self::url(config, QUERY_TRANS_PATH), &params, config).await?;
+        match response.get("respCode").map(String::as_str) {
+            // 查询成功，以原交易应答码为准，queryId 为原交易流水号
+            Some("00") => {
+                let status = match response.get("origRespCode").map(String::as_str) {
+                    Some("00") | Some("A6") => OrderStatus::Success,
+                    Some("03") | Some("04") | Some("05") => OrderStatus::Processing,
+                    _ => OrderStatus::Failed,
+                };
+                Ok((status, response.get("queryId").cloned()))
+            }
+            // 交易不存在，用户尚未完成支付
+            Some("34") => Ok((OrderStatus::Processing, None)),
+            _ => Err(Self::check_resp_code(&response).unwrap_err()),
+        }
+    }
+
+    async fn handle_callback(
+        &self,
+        config: &PaymentConfig,
+        callback_data: &serde_json::Value,
+    ) -> Result<ChannelNotification, PaymentError> {
+        // 1. 解析通知字段
+        let callback = UnionPayCallback::parse(callback_data)?;
+
+        // 2. 验证签名
+        Self::verify(callback.params(), config)?;
+
+        // 3. 退款结果与支付结果推送到同一个 backUrl，按交易类型区分
+        let order_id = callback.order_id().to_string();
+        if callback.is_refund() {
+            let refund_no = callback.refund_no()
+                .ok_or_else(|| PaymentError::InvalidCallback(format!("银联退款通知缺少退款单号: {}", order_id)))?
+                .to_string();
+            let status = match callback.status() {
+                OrderStatus::Success => ChannelRefundStatus::Succeeded(
+                    callback.query_id().unwrap_or(&refund_no).to_string(),
+                ),
+                _ => ChannelRefundStatus::Failed,
+            };
+            return Ok(ChannelNotification::Refund { order_id, refund_no, status });
+        }
+
+        // 4. 解析订单号、支付状态和交易流水号，退款时需要流水号
+        Ok(ChannelNotification::Payment {
+            order_id,
+            status: callback.status(),
+            transaction_id: callback.query_id().map(str::to_string),
+        })
+    }
+
+    async fn refund(
+        &self,
+        order: &PaymentOrder,
+        config: &PaymentConfig,
+        refund_request: &RefundRequest,
+    ) -> Result<String, PaymentError> {
+        // 退款需要原交易的银联流水号 queryId
+        let orig_query_id = order.third_party_order_id.as_deref()
+            .ok_or_else(|| PaymentError::UnsupportedOperation("银联订单缺少交易流水号，无法退款".to_string()))?;
+
+        let refund_no = refund_request.refund_no();
+        let refund_id = Self::union_order_id(&refund_no);
+        let mut params = Self::base_params(config, "04", "00")?;
+        params.insert("bizType".to_string(), "000201".to_string());
+        params.insert("channelType".to_string(), "07".to_string());
+        params.insert("orderId".to_string(), refund_id.clone());
+        params.insert("origQryId".to_string(), orig_query_id.to_string());
+        params.insert("txnTime".to_string(), Self::txn_time(Utc::now()));
+        params.insert("txnAmt".to_string(), refund_request.refund_amount.to_minor(order.amount.currency)?.to_string());
+        params.insert("backUrl".to_string(), config.notify_url.clone());
+        // 退款结果通知与支付通知共用 backUrl，透传订单号和退款单号用于定位退款
+        params.insert("reqReserved".to_string(), UnionPayCallback::refund_reserved(&order.order_id, &refund_no));
+        Self::sign(&mut params, config)?;
+
+        let response = self.post(&Self::url(config, BACK_TRANS_PATH), &params, config).await?;
+        Self::check_resp_code(&response)?;
+
+        Ok(response.get("queryId").cloned().unwrap_or(refund_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::money::Money;
+    use crate::models::enums::PaymentType;
+    use crate::payment::replay::{RecordingHttpClient, ReplayHttpClient};
+    use httpmock::prelude::*;
+    use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+    use std::sync::OnceLock;
+
+    /// 测试用密钥对，同一对密钥同时模拟商户签名和银联签名
+    fn test_keys() -> &'static (String, String) {
+        static KEYS: OnceLock<(String, String)> = OnceLock::new();
+        KEYS.get_or_init(|| {
+            let private_key = RsaPrivateKey::new(&mut rsa::rand_core::OsRng, 1024).unwrap();
+            let public_key = RsaPublicKey::from(&private_key);
+            (
+                private_key.to_pkcs8_pem(LineEnding::LF).unwrap().to_string(),
+                public_key.to_public_key_pem(LineEnding::LF).unwrap(),
+            )
+        })
+    }
+
+    fn test_config(gateway_url: &str) -> PaymentConfig {
+        let (private_key, public_key) = test_keys();
+        PaymentConfig {
+            id: 1,
+            tenant_id: 1,
+            payment_type: 19,
+            payment_sub_type: 19,
+            merchant_id: "777290058110048".to_string(),
+            app_id: None,
+            private_key: Some(private_key.clone()),
+            public_key: Some(public_key.clone()),
+            api_key: None,
+            api_secret: None,
+            gateway_url: gateway_url.to_string(),
+            notify_url: "https://www.example.com/notify".to_string(),
+            return_url: Some("https://www.example.com/return".to_string()),
+            extra_config: Some(serde_json::json!({ "cert_id": "69629715588" })),
+            rate_limit_qps: None,
+            rate_limit_burst: None,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn test_request(payment_type: PaymentType) -> CreatePaymentRequest {
+        CreatePaymentRequest {
+            tenant_id: 1,
+            merchant_order_id: None,
+            user_id: 100,
+            payment_type,
+            amount: 10000,
+            currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: TransactionType::Sale,
+            product_name: "测试商品".to_string(),
+            product_desc: None,
+            callback_url: None,
+            notify_url: None,
+            extra_data: None,
+        }
+    }
+
+    /// 模拟银联返回的已签名响应
+    fn signed_response(config: &PaymentConfig, fields: &[(&str, &str)]) -> String {
+        let mut params: BTreeMap<String, String> = fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        UnionPayStrategy::sign(&mut params, config).unwrap();
+        serde_urlencoded::to_string(&params).unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let config = test_config("https://gateway.95516.com");
+        let mut params = UnionPayStrategy::base_params(&config, "01", "01").unwrap();
+        params.insert("txnAmt".to_string(), "100".to_string());
+        params.insert("reqReserved".to_string(), String::new());
+        UnionPayStrategy::sign(&mut params, &config).unwrap();
+
+        assert_eq!(params["certId"], "69629715588");
+        assert!(UnionPayStrategy::verify(&params, &config).is_ok());
+
+        // 篡改金额后验签失败
+        params.insert("txnAmt".to_string(), "1".to_string());
+        assert!(UnionPayStrategy::verify(&params, &config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unionpay_create_order() {
+        let server = MockServer::start();
+        let config = test_config(&server.base_url());
+        let order = PaymentOrder::new(1, 100, PaymentType::UnionPaySdk, Money::cny(10000), None, None, None, Utc::now());
+
+        // 网页支付不请求银联，直接返回表单
+        let response = UnionPayStrategy::new(UnionPayMode::H5)
+            .create_order(&order, &config, &test_request(PaymentType::UnionPayH5))
+            .await
+            .unwrap();
+        let PaymentInstruction::HtmlForm(form) = response.instruction else {
+            panic!("网页支付应返回表单");
+        };
+        assert!(form.contains(&format!(r#"action="{}{}""#, server.base_url(), FRONT_TRANS_PATH)));
+        assert!(form.contains(r#"name="txnAmt" value="10000""#));
+        assert!(form.contains(&format!(r#"name="reqReserved" value="{}""#, order.order_id)));
+        let pay_timeout = UnionPayStrategy::txn_time(order.expire_at());
+        assert!(form.contains(&format!(r#"name="payTimeout" value="{}""#, pay_timeout)));
+        assert!(pay_timeout > UnionPayStrategy::txn_time(order.created_at));
+        assert!(form.contains(r#"name="currencyCode" value="156""#));
+        assert!(form.contains(r#"name="signature" value=""#));
+
+        // 外币订单按订单币种上送
+        let usd_order = PaymentOrder::new(1, 100, PaymentType::UnionPaySdk, Money::usd(10000), None, None, None, Utc::now());
+        let response = UnionPayStrategy::new(UnionPayMode::H5)
+            .create_order(&usd_order, &config, &test_request(PaymentType::UnionPayH5))
+            .await
+            .unwrap();
+        let PaymentInstruction::HtmlForm(form) = response.instruction else {
+            panic!("网页支付应返回表单");
+        };
+        assert!(form.contains(r#"name="currencyCode" value="840""#));
+
+        // 控件支付返回 tn
+        let app_mock = server.mock(|when, then| {
+            when.method(POST).path(APP_TRANS_PATH).body_contains("txnAmt=10000");
+            then.status(200).body(signed_response(&config, &[("respCode", "00"), ("tn", "877610246453102098800")]));
+        });
+        let response = UnionPayStrategy::new(UnionPayMode::App)
+            .create_order(&order, &config, &test_request(PaymentType::UnionPaySdk))
+            .await
+            .unwrap();
+        app_mock.assert();
+        assert_eq!(response.instruction, PaymentInstruction::AppParams(serde_json::json!({ "tn": "877610246453102098800" })));
+
+        // 主扫返回二维码
+        let qr_mock = server.mock(|when, then| {
+            when.method(POST).path(BACK_TRANS_PATH).body_contains("txnSubType=07");
+            then.status(200).body(signed_response(&config, &[("respCode", "00"), ("qrCode", "https://qr.95516.com/00010000/123")]));
+        });
+        let response = UnionPayStrategy::new(UnionPayMode::Qr)
+            .create_order(&order, &config, &test_request(PaymentType::ScanPayUnion))
+            .await
+            .unwrap();
+        qr_mock.assert();
+        assert_eq!(response.instruction, PaymentInstruction::QrCode("https://qr.95516.com/00010000/123".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_unionpay_create_order_replay() {
+        let server = MockServer::start();
+        let config = test_config(&server.base_url());
+        let order = PaymentOrder::new(1, 100, PaymentType::UnionPaySdk, Money::cny(10000), None, None, None, Utc::now());
+        let request = test_request(PaymentType::UnionPaySdk);
+        let fixture = std::env::temp_dir().join(format!("unionpay_create_order_{}.json", order.order_id));
+
+        // 录制模拟网关的下单交互
+        let mock = server.mock(|when, then| {
+            when.method(POST).path(APP_TRANS_PATH);
+            then.status(200).body(signed_response(&config, &[("respCode", "00"), ("tn", "877610246453102098800")]));
+        });
+        let recorder = Arc::new(RecordingHttpClient::new(Arc::new(ReqwestHttpClient::new()), &fixture));
+        let recorded = UnionPayStrategy::new(UnionPayMode::App)
+            .with_http_client(recorder.clone())
+            .create_order(&order, &config, &request)
+            .await
+            .unwrap();
+        mock.assert();
+        assert_eq!(recorder.exchanges().len(), 1);
+
+        // 网关下线后从夹具回放，解析结果不变
+        drop(server);
+        let replayed = UnionPayStrategy::new(UnionPayMode::App)
+            .with_http_client(Arc::new(ReplayHttpClient::from_file(&fixture).unwrap()))
+            .create_order(&order, &config, &request)
+            .await
+            .unwrap();
+        assert_eq!(replayed.order_id, recorded.order_id);
+        assert_eq!(replayed.instruction, recorded.instruction);
+        assert_eq!(replayed.instruction, PaymentInstruction::AppParams(serde_json::json!({ "tn": "877610246453102098800" })));
+
+        // 请求内容变化时没有匹配的录制记录
+        let other = PaymentOrder::new(1, 100, PaymentType::UnionPaySdk, Money::cny(20000), None, None, None, Utc::now());
+        let result = UnionPayStrategy::new(UnionPayMode::App)
+            .with_http_client(Arc::new(ReplayHttpClient::from_file(&fixture).unwrap()))
+            .create_order(&other, &config, &request)
+            .await;
+        assert!(result.is_err());
+
+        std::fs::remove_file(&fixture).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unionpay_query_order() {
+        let server = MockServer::start();
+        let config = test_config(&server.base_url());
+        let order = PaymentOrder::new(1, 100, PaymentType::UnionPaySdk, Money::cny(10000), None, None, None, Utc::now());
+        let strategy = UnionPayStrategy::new(UnionPayMode::App);
+
+        let mut mock = server.mock(|when, then| {
+            when.method(POST).path(QUERY_TRANS_PATH);
+            then.status(200).body(signed_response(&config, &[
+                ("respCode", "00"),
+                ("origRespCode", "00"),
+                ("queryId", "771610246453102098800"),
+            ]));
+        });
+        assert_eq!(
+            strategy.query_order(&order, &config).await.unwrap(),
+            (OrderStatus::Success, Some("771610246453102098800".to_string()))
+        );
+        mock.delete();
+
+        let mut mock = server.mock(|when, then| {
+            when.method(POST).path(QUERY_TRANS_PATH);
+            then.status(200).body(signed_response(&config, &[("respCode", "34")]));
+        });
+        assert_eq!(strategy.query_order(&order, &config).await.unwrap(), (OrderStatus::Processing, None));
+        mock.delete();
+
+        // 响应签名无效
+        server.mock(|when, then| {
+            when.method(POST).path(QUERY_TRANS_PATH);
+            then.status(200).body("respCode=00&origRespCode=00&signature=invalid");
+        });
+        assert!(strategy.query_order(&order, &config).await.is_err());
+    }
+
+    /// 模拟银联推送的已签名通知
+    fn signed_callback(config: &PaymentConfig, fields: &[(&str, &str)]) -> serde_json::Value {
+        let mut params: BTreeMap<String, String> = fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        UnionPayStrategy::sign(&mut params, config).unwrap();
+        serde_json::to_value(&params).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unionpay_handle_callback() {
+        let config = test_config("https://gateway.95516.com");
+        let strategy = UnionPayStrategy::new(UnionPayMode::App);
+
+        let callback = signed_callback(&config, &[
+            ("orderId", "abc123"),
+            ("reqReserved", "abc-123"),
+            ("txnType", "01"),
+            ("respCode", "00"),
+            ("queryId", "771610246453102098800"),
+        ]);
+        assert_eq!(
+            strategy.handle_callback(&config, &callback).await.unwrap(),
+            ChannelNotification::Payment {
+                order_id: "abc-123".to_string(),
+                status: OrderStatus::Success,
+                transaction_id: Some("771610246453102098800".to_string()),
+            }
+        );
+
+        // 伪造的通知被拒绝
+        let mut forged = callback.clone();
+        forged["respCode"] = "01".into();
+        assert!(strategy.handle_callback(&config, &forged).await.is_err());
+
+        // 退款通知推送到同一个 backUrl，按退款单号返回退款结果
+        let reserved = UnionPayCallback::refund_reserved("abc-123", "R-1");
+        let callback = signed_callback(&config, &[
+            ("orderId", "R1"),
+            ("reqReserved", reserved.as_str()),
+            ("txnType", "04"),
+            ("respCode", "00"),
+            ("queryId", "881610246453102098800"),
+        ]);
+        assert_eq!(
+            strategy.handle_callback(&config, &callback).await.unwrap(),
+            ChannelNotification::Refund {
+                order_id: "abc-123".to_string(),
+                refund_no: "R-1".to_string(),
+                status: ChannelRefundStatus::Succeeded("881610246453102098800".to_string()),
+            }
+        );
+
+        let callback = signed_callback(&config, &[
+            ("orderId", "R1"),
+            ("reqReserved", reserved.as_str()),
+            ("txnType", "04"),
+            ("respCode", "01"),
+        ]);
+        assert_eq!(
+            strategy.handle_callback(&config, &callback).await.unwrap(),
+            ChannelNotification::Refund {
+                order_id: "abc-123".to_string(),
+                refund_no: "R-1".to_string(),
+                status: ChannelRefundStatus::Failed,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unionpay_refund() {
+        let server = MockServer::start();
+        let config = test_config(&server.base_url());
+        let strategy = UnionPayStrategy::new(UnionPayMode::App);
+
+        let mut order = PaymentOrder::new(1, 100, PaymentType::UnionPaySdk, Money::cny(10000), None, None, None, Utc::now());
+        let refund_request = RefundRequest {
+            order_id: order.order_id.clone(),
+            refund_amount: 5000.into(),
+            refund_reason: None,
+            destination: RefundDestination::Original,
+            merchant_refund_no: Some("R-20240101-1".to_string()),
+        };
+
+        // 没有银联流水号无法退款
+        assert!(strategy.refund(&order, &config, &refund_request).await.is_err());
+
+        // 支付通知中的 queryId 作为原交易流水号
+        let union_order_id = UnionPayStrategy::union_order_id(&order.order_id);
+        let callback = signed_callback(&config, &[
+            ("orderId", union_order_id.as_str()),
+            ("reqReserved", order.order_id.as_str()),
+            ("txnType", "01"),
+            ("respCode", "00"),
+            ("queryId", "771610246453102098800"),
+        ]);
+        let ChannelNotification::Payment { transaction_id: Some(transaction_id), .. } =
+            strategy.handle_callback(&config, &callback).await.unwrap()
+        else {
+            panic!("支付通知应返回交易流水号");
+        };
+        order.complete_payment(transaction_id, Utc::now()).unwrap();
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path(BACK_TRANS_PATH)
+                .body_contains("txnType=04")
+                .body_contains("orderId=R202401011")
+                .body_contains("origQryId=771610246453102098800")
+                .body_contains("reqReserved=");
+            then.status(200).body(signed_response(&config, &[("respCode", "00"), ("queryId", "881610246453102098800")]));
+        });
+        let refund_id = strategy.refund(&order, &config, &refund_request).await.unwrap();
+        mock.assert();
+        assert_eq!(refund_id, "881610246453102098800");
+
+        // 网关错误时结果未知
+        let server = MockServer::start();
+        let config = test_config(&server.base_url());
+        server.mock(|when, then| {
+            when.method(POST).path(BACK_TRANS_PATH);
+            then.status(504);
+        });
+        assert!(matches!(
+            strategy.refund(&order, &config, &refund_request).await,
+            Err(PaymentError::ChannelOutcomeUnknown(_))
+        ));
+    }
+}
@@ -2,8 +2,10 @@ use async_trait::async_trait;
 use crate::error::PaymentError;
 use crate::models::payment::*;
 use crate::models::enums::OrderStatus;
-use crate::payment::strategy::PaymentStrategy;
+use crate::payment::callback::WechatCallback;
+use crate::payment::strategy::{ChannelNotification, PaymentStrategy};
 use crate::domain::payment::PaymentOrder;
+use super::beijing_time;
 
 pub struct WechatH5Strategy;
 
@@ -40,6 +42,8 @@ impl PaymentStrategy for WechatH5Strategy {
             "spbill_create_ip": "127.0.0.1", // 实际实现中应该从请求中获取
             "notify_url": config.notify_url,
             "trade_type": "MWEB", // H5支付
+            // 交易结束时间与订单过期时间一致，超时后微信侧不能再支付
+            "time_expire": beijing_time(order.expire_at()),
             "scene_info": serde_json::json!({
                 "h5_info": {
                     "type": "Wap",
@@ -57,8 +61,7 @@ impl PaymentStrategy for WechatH5Strategy {
 
         Ok(CreatePaymentResponse {
             order_id: order.order_id.clone(),
-            payment_url: Some(payment_url),
-            payment_params: None,
+            instruction: PaymentInstruction::Redirect(payment_url),
         })
     }
 
@@ -66,7 +69,7 @@ impl PaymentStrategy for WechatH5Strategy {
         &self,
         order: &PaymentOrder,
         config: &PaymentConfig,
-    ) -> Result<OrderStatus, PaymentError> {
+    ) -> Result<(OrderStatus, Option<String>), PaymentError> {
         // 在实际实现中，需要调用微信查询订单API
         // 这里简化处理，模拟返回成功状态
 
@@ -82,41 +85,36 @@ impl PaymentStrategy for WechatH5Strategy {
 
         // 解析响应
         let trade_state = api_response["trade_state"].as_str().unwrap_or("UNKNOWN");
+        let transaction_id = api_response["transaction_id"].as_str().map(str::to_string);
+
+        let status = match trade_state {
+            "SUCCESS" => OrderStatus::Success,
+            "REFUND" => OrderStatus::Refunded,
+            "NOTPAY" | "USERPAYING" => OrderStatus::Processing,
+            "CLOSED" | "REVOKED" | "PAYERROR" => OrderStatus::Failed,
+            _ => OrderStatus::Processing,
+        };
 
-        match trade_state {
-            "SUCCESS" => Ok(OrderStatus::Success),
-            "REFUND" => Ok(OrderStatus::Refunded),
-            "NOTPAY" | "USERPAYING" => Ok(OrderStatus::Processing),
-            "CLOSED" | "REVOKED" | "PAYERROR" => Ok(OrderStatus::Failed),
-            _ => Ok(OrderStatus::Processing),
-        }
+        Ok((status, transaction_id))
     }
 
     async fn handle_callback(
         &self,
         config: &PaymentConfig,
         callback_data: &serde_json::Value,
-    ) -> Result<(String, OrderStatus), PaymentError> {
+    ) -> Result<ChannelNotification, PaymentError> {
         // 1. 验证签名
         // 实际实现中需要验证微信回调的签名
 
         // 2. 解析订单号和支付状态
-        let order_id = callback_data["out_trade_no"]
-            .as_str()
-            .ok_or_else(|| PaymentError::Internal("Missing out_trade_no in callback data".to_string()))?
-            .to_string();
-
-        let result_code = callback_data["result_code"]
-            .as_str()
-            .unwrap_or("FAIL");
-
-        let status = if result_code == "SUCCESS" {
-            OrderStatus::Success
-        } else {
-            OrderStatus::Failed
-        };
+        let callback = WechatCallback::parse(callback_data)?;
+        let status = callback.status();
 
-        Ok((order_id, status))
+        Ok(ChannelNotification::Payment {
+            order_id: callback.out_trade_no,
+            status,
+            transaction_id: callback.transaction_id,
+        })
     }
 
     async fn refund(
@@ -127,7 +125,7 @@ impl PaymentStrategy for WechatH5Strategy {
     ) -> Result<String, PaymentError> {
         // 实现微信退款逻辑
         // 1. 构建退款请求参数
-        let refund_id = uuid::Uuid::new_v4().to_string();
+        let refund_id = refund_request.refund_no();
         let params = serde_json::json!({
             "appid": config.app_id,
             "mch_id": config.merchant_id,
@@ -135,7 +133,7 @@ impl PaymentStrategy for WechatH5Strategy {
             "out_trade_no": order.order_id,
             "out_refund_no": &refund_id,
             "total_fee": order.amount.amount,
-            "refund_fee": refund_request.refund_amount,
+            "refund_fee": refund_request.refund_amount.to_minor(order.amount.currency)?,
             "refund_desc": refund_request.refund_reason.clone().unwrap_or_else(|| "客户退款".to_string())
         });
 
@@ -180,8 +178,7 @@ impl PaymentStrategy for WechatSdkStrategy {
 
         Ok(CreatePaymentResponse {
             order_id: order.order_id.clone(),
-            payment_url: None,
-            payment_params: Some(payment_params),
+            instruction: PaymentInstruction::AppParams(payment_params),
         })
     }
 
@@ -189,7 +186,7 @@ impl PaymentStrategy for WechatSdkStrategy {
         &self,
         order: &PaymentOrder,
         config: &PaymentConfig,
-    ) -> Result<OrderStatus, PaymentError> {
+    ) -> Result<(OrderStatus, Option<String>), PaymentError> {
         // 查询逻辑与H5支付相同
         let wx_h5 = WechatH5Strategy::new();
         wx_h5.query_order(order, config).await
@@ -199,7 +196,7 @@ impl PaymentStrategy for WechatSdkStrategy {
         &self,
         config: &PaymentConfig,
         callback_data: &serde_json::Value,
-    ) -> Result<(String, OrderStatus), PaymentError> {
+    ) -> Result<ChannelNotification, PaymentError> {
         // 回调处理逻辑与H5支付相同
         let wx_h5 = WechatH5Strategy::new();
         wx_h5.handle_callback(config, callback_data).await
@@ -235,6 +232,7 @@ mod tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            chrono::Utc::now(),
         );
 
         // 创建测试配置
@@ -253,6 +251,8 @@ mod tests {
             notify_url: "https://www.example.com/notify".to_string(),
             return_url: None,
             extra_config: None,
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -261,10 +261,14 @@ mod tests {
         // 创建支付请求
         let request = CreatePaymentRequest {
             tenant_id: 1,
+            merchant_order_id: None,
             user_id: 100,
             payment_type: crate::models::enums::PaymentType::WxH5,
             amount: 10000,
             currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: TransactionType::Sale,
             product_name: "测试商品".to_string(),
             product_desc: Some("商品描述".to_string()),
             callback_url: Some("http://example.com/callback".to_string()),
@@ -278,8 +282,10 @@ mod tests {
 
         let response = result.unwrap();
         assert_eq!(response.order_id, order.order_id);
-        assert!(response.payment_url.is_some());
-        assert!(response.payment_url.unwrap().contains("wx.tenpay.com"));
+        let PaymentInstruction::Redirect(url) = response.instruction else {
+            panic!("H5 支付应返回跳转地址");
+        };
+        assert!(url.contains("wx.tenpay.com"));
     }
 
     #[tokio::test]
@@ -295,6 +301,7 @@ mod tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            chrono::Utc::now(),
         );
 
         // 创建测试配置
@@ -313,6 +320,8 @@ mod tests {
             notify_url: "https://www.example.com/notify".to_string(),
             return_url: None,
             extra_config: None,
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -321,10 +330,14 @@ mod tests {
         // 创建支付请求
         let request = CreatePaymentRequest {
             tenant_id: 1,
+            merchant_order_id: None,
             user_id: 100,
             payment_type: crate::models::enums::PaymentType::WxSdk,
             amount: 10000,
             currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: TransactionType::Sale,
             product_name: "测试商品".to_string(),
             product_desc: Some("商品描述".to_string()),
             callback_url: Some("http://example.com/callback".to_string()),
@@ -338,10 +351,9 @@ mod tests {
 
         let response = result.unwrap();
         assert_eq!(response.order_id, order.order_id);
-        assert!(response.payment_url.is_none());
-        assert!(response.payment_params.is_some());
-
-        let params = response.payment_params.unwrap();
+        let PaymentInstruction::AppParams(params) = response.instruction else {
+            panic!("SDK 支付应返回客户端参数");
+        };
         assert!(params.get("appid").is_some());
         assert!(params.get("prepayid").is_some());
         assert!(params.get("sign").is_some());
@@ -0,0 +1,164 @@
+//! 渠道交互的录制与回放
+//!
+//! 录制模式下 [`RecordingHttpClient`] 把真实的请求/响应写入 JSON 夹具文件，
+//! 测试时 [`ReplayHttpClient`] 按 方法 + URL + 请求体哈希 匹配并返回录制的响应，
+//! 不再访问渠道。请求体包含时间、订单号、签名等字段，回放时订单和商户配置需要与录制时一致
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::error::PaymentError;
+use crate::payment::http::{HttpRequest, HttpResponse, PaymentHttpClient};
+
+/// 设置该环境变量时 [`fixture_client`] 录制真实交互，否则回放夹具
+pub const RECORD_FIXTURES_ENV: &str = "PAYMENT_RECORD_FIXTURES";
+
+/// 一次录制的请求/响应
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub request: HttpRequest,
+    pub response: HttpResponse,
+}
+
+/// 回放时匹配请求使用的键
+fn exchange_key(request: &HttpRequest) -> String {
+    format!(
+        "{} {} {:x}",
+        request.method.to_uppercase(),
+        request.url,
+        Sha256::digest(request.body.as_bytes())
+    )
+}
+
+/// 转发请求并把每次交互追加到夹具文件
+pub struct RecordingHttpClient {
+    inner: Arc<dyn PaymentHttpClient>,
+    path: PathBuf,
+    exchanges: Mutex<Vec<RecordedExchange>>,
+}
+
+impl RecordingHttpClient {
+    pub fn new(inner: Arc<dyn PaymentHttpClient>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            exchanges: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 已录制的交互
+    pub fn exchanges(&self) -> Vec<RecordedExchange> {
+        self.exchanges.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl PaymentHttpClient for RecordingHttpClient {
+    async fn send(&self, request: &HttpRequest) -> Result<HttpResponse, PaymentError> {
+        let response = self.inner.send(request).await?;
+
+        let content = {
+            let mut exchanges = self.exchanges.lock().unwrap();
+            exchanges.push(RecordedExchange { request: request.clone(), response: response.clone() });
+            serde_json::to_string_pretty(&*exchanges)
+                .map_err(|e| PaymentError::Internal(format!("夹具序列化失败: {}", e)))?
+        };
+        std::fs::write(&self.path, content)
+            .map_err(|e| PaymentError::Internal(format!("写入夹具文件 {} 失败: {}", self.path.display(), e)))?;
+
+        Ok(response)
+    }
+}
+
+/// 按录制的夹具返回响应，同一请求录制了多次时按录制顺序返回，用完后重复最后一次
+pub struct ReplayHttpClient {
+    responses: Mutex<HashMap<String, VecDeque<HttpResponse>>>,
+}
+
+impl ReplayHttpClient {
+    pub fn new(exchanges: Vec<RecordedExchange>) -> Self {
+        let mut responses: HashMap<String, VecDeque<HttpResponse>> = HashMap::new();
+        for exchange in exchanges {
+            responses
+                .entry(exchange_key(&exchange.request))
+                .or_default()
+                .push_back(exchange.response);
+        }
+        Self { responses: Mutex::new(responses) }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, PaymentError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| PaymentError::Internal(format!("读取夹具文件 {} 失败: {}", path.display(), e)))?;
+        let exchanges = serde_json::from_str(&content)
+            .map_err(|e| PaymentError::Internal(format!("夹具文件 {} 格式错误: {}", path.display(), e)))?;
+        Ok(Self::new(exchanges))
+    }
+}
+
+#[async_trait]
+impl PaymentHttpClient for ReplayHttpClient {
+    async fn send(&self, request: &HttpRequest) -> Result<HttpResponse, PaymentError> {
+        let mut responses = self.responses.lock().unwrap();
+        let queue = responses
+            .get_mut(&exchange_key(request))
+            .ok_or_else(|| PaymentError::Internal(format!("没有与请求匹配的录制记录: {} {}", request.method, request.url)))?;
+
+        match queue.len() {
+            1 => Ok(queue[0].clone()),
+            _ => Ok(queue.pop_front().expect("录制记录不为空")),
+        }
+    }
+}
+
+/// 设置了 [`RECORD_FIXTURES_ENV`] 时通过 `live` 访问渠道并录制到 `path`，否则从 `path` 回放
+pub fn fixture_client(
+    path: impl AsRef<Path>,
+    live: Arc<dyn PaymentHttpClient>,
+) -> Result<Arc<dyn PaymentHttpClient>, PaymentError> {
+    if std::env::var_os(RECORD_FIXTURES_ENV).is_some() {
+        Ok(Arc::new(RecordingHttpClient::new(live, path.as_ref())))
+    } else {
+        Ok(Arc::new(ReplayHttpClient::from_file(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(body: &str) -> HttpRequest {
+        HttpRequest {
+            method: "POST".to_string(),
+            url: "https://gateway.example.com/pay".to_string(),
+            content_type: Some("application/x-www-form-urlencoded".to_string()),
+            body: body.to_string(),
+        }
+    }
+
+    fn response(body: &str) -> HttpResponse {
+        HttpResponse { status: 200, body: body.to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_replay_matches_request() {
+        let client = ReplayHttpClient::new(vec![
+            RecordedExchange { request: form("a=1"), response: response("first") },
+            RecordedExchange { request: form("a=1"), response: response("second") },
+            RecordedExchange { request: form("a=2"), response: response("other") },
+        ]);
+
+        // 相同请求按录制顺序返回，用完后重复最后一次
+        assert_eq!(client.send(&form("a=1")).await.unwrap().body, "first");
+        assert_eq!(client.send(&form("a=1")).await.unwrap().body, "second");
+        assert_eq!(client.send(&form("a=1")).await.unwrap().body, "second");
+        assert_eq!(client.send(&form("a=2")).await.unwrap().body, "other");
+
+        // 请求体不同时不匹配
+        assert!(client.send(&form("a=3")).await.is_err());
+    }
+}
@@ -2,9 +2,30 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use crate::error::PaymentError;
 use crate::models::payment::*;
+use crate::domain::dispute::DisputeNotification;
+use crate::domain::refund::{ChannelRefundStatus, Refund};
 use crate::domain::payment::PaymentOrder;
 use crate::models::enums::OrderStatus;
 
+/// 渠道异步通知的解析结果
+///
+/// 部分渠道（如银联）的退款结果与支付结果推送到同一个通知地址，由策略按报文区分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelNotification {
+    /// 支付结果，`transaction_id` 为渠道交易号（如银联 `queryId`），退款等后续交易需要使用
+    Payment {
+        order_id: String,
+        status: OrderStatus,
+        transaction_id: Option<String>,
+    },
+    /// 退款结果，`refund_no` 为发起退款时上送的退款单号（[`Refund::merchant_refund_no`]）
+    Refund {
+        order_id: String,
+        refund_no: String,
+        status: ChannelRefundStatus,
+    },
+}
+
 #[async_trait]
 pub trait PaymentStrategy: Send + Sync {
     /// 创建支付订单
@@ -15,19 +36,19 @@ pub trait PaymentStrategy: Send + Sync {
         request: &CreatePaymentRequest,
     ) -> Result<CreatePaymentResponse, PaymentError>;
 
-    /// 查询订单状态
+    /// 查询订单状态，同时返回渠道交易号（渠道未返回时为空）
     async fn query_order(
         &self,
         order: &PaymentOrder,
         config: &PaymentConfig,
-    ) -> Result<OrderStatus, PaymentError>;
+    ) -> Result<(OrderStatus, Option<String>), PaymentError>;
 
-    /// 处理支付回调
+    /// 验证并解析渠道的异步通知
     async fn handle_callback(
         &self,
         config: &PaymentConfig,
         callback_data: &serde_json::Value,
-    ) -> Result<(String, OrderStatus), PaymentError>;
+    ) -> Result<ChannelNotification, PaymentError>;
 
     /// 发起退款
     async fn refund(
@@ -36,6 +57,68 @@ pub trait PaymentStrategy: Send + Sync {
         config: &PaymentConfig,
         refund_request: &RefundRequest,
     ) -> Result<String, PaymentError>;
+
+    /// 按退款单号（[`Refund::merchant_refund_no`]）查询退款结果
+    ///
+    /// 退款请求超时或网关错误时结果未知，服务保留处理中的退款并通过此方法确认；
+    /// 默认不支持，这类退款保持处理中，需要人工核对
+    async fn query_refund(
+        &self,
+        _order: &PaymentOrder,
+        _config: &PaymentConfig,
+        _refund: &Refund,
+    ) -> Result<ChannelRefundStatus, PaymentError> {
+        Err(PaymentError::UnsupportedOperation("该渠道不支持退款查询".to_string()))
+    }
+
+    /// 渠道是否支持预授权（[`TransactionType::Authorization`]），默认不支持
+    fn supports_authorization(&self) -> bool {
+        false
+    }
+
+    /// 捕获预授权订单，按 `amount` 扣款，返回渠道的捕获单号
+    ///
+    /// 上次捕获失败或结果未知时会用相同金额再次调用，渠道实现应以订单号作为幂等键，重复请求不会重复扣款
+    async fn capture(
+        &self,
+        _order: &PaymentOrder,
+        _config: &PaymentConfig,
+        _amount: i64,
+    ) -> Result<String, PaymentError> {
+        Err(PaymentError::UnsupportedOperation("该渠道不支持预授权捕获".to_string()))
+    }
+
+    /// 撤销预授权订单，释放冻结的额度
+    async fn void_authorization(
+        &self,
+        _order: &PaymentOrder,
+        _config: &PaymentConfig,
+    ) -> Result<(), PaymentError> {
+        Err(PaymentError::UnsupportedOperation("该渠道不支持撤销预授权".to_string()))
+    }
+
+    /// 处理争议（拒付）通知，验证通知并解析出争议信息
+    ///
+    /// 国际卡渠道（PayPal、Stripe 等）的拒付通知与支付通知分开推送，默认不支持
+    async fn handle_dispute_notification(
+        &self,
+        _config: &PaymentConfig,
+        _notification: &serde_json::Value,
+    ) -> Result<DisputeNotification, PaymentError> {
+        Err(PaymentError::UnsupportedOperation("该渠道不支持争议通知".to_string()))
+    }
+
+    /// 渠道是否支持退款到非原支付账户（[`RefundDestination::Alternate`]），默认不支持
+    fn supports_alternate_refund(&self) -> bool {
+        false
+    }
+
+    /// 渠道健康检查，用于就绪探针
+    ///
+    /// 实现应当尽量轻量（如 ping 网关或获取 token），默认视为健康
+    async fn health_check(&self) -> Result<(), PaymentError> {
+        Ok(())
+    }
 }
 
 // 添加限流装饰器
@@ -71,7 +154,7 @@ impl<T: PaymentStrategy> PaymentStrategy for RateLimitedStrategy<T> {
         &self,
         order: &PaymentOrder,
         config: &PaymentConfig,
-    ) -> Result<OrderStatus, PaymentError> {
+    ) -> Result<(OrderStatus, Option<String>), PaymentError> {
         let _permit = self.limiter.try_acquire()
             .map_err(|_| PaymentError::RateLimited)?;
 
@@ -82,7 +165,7 @@ impl<T: PaymentStrategy> PaymentStrategy for RateLimitedStrategy<T> {
         &self,
         config: &PaymentConfig,
         callback_data: &serde_json::Value,
-    ) -> Result<(String, OrderStatus), PaymentError> {
+    ) -> Result<ChannelNotification, PaymentError> {
         // 回调处理不限流
         self.inner.handle_callback(config, callback_data).await
     }
@@ -98,6 +181,63 @@ impl<T: PaymentStrategy> PaymentStrategy for RateLimitedStrategy<T> {
 
         self.inner.refund(order, config, refund_request).await
     }
+
+    async fn query_refund(
+        &self,
+        order: &PaymentOrder,
+        config: &PaymentConfig,
+        refund: &Refund,
+    ) -> Result<ChannelRefundStatus, PaymentError> {
+        let _permit = self.limiter.try_acquire()
+            .map_err(|_| PaymentError::RateLimited)?;
+
+        self.inner.query_refund(order, config, refund).await
+    }
+
+    fn supports_authorization(&self) -> bool {
+        self.inner.supports_authorization()
+    }
+
+    async fn capture(
+        &self,
+        order: &PaymentOrder,
+        config: &PaymentConfig,
+        amount: i64,
+    ) -> Result<String, PaymentError> {
+        let _permit = self.limiter.try_acquire()
+            .map_err(|_| PaymentError::RateLimited)?;
+
+        self.inner.capture(order, config, amount).await
+    }
+
+    async fn void_authorization(
+        &self,
+        order: &PaymentOrder,
+        config: &PaymentConfig,
+    ) -> Result<(), PaymentError> {
+        let _permit = self.limiter.try_acquire()
+            .map_err(|_| PaymentError::RateLimited)?;
+
+        self.inner.void_authorization(order, config).await
+    }
+
+    async fn handle_dispute_notification(
+        &self,
+        config: &PaymentConfig,
+        notification: &serde_json::Value,
+    ) -> Result<DisputeNotification, PaymentError> {
+        // 通知处理不限流
+        self.inner.handle_dispute_notification(config, notification).await
+    }
+
+    fn supports_alternate_refund(&self) -> bool {
+        self.inner.supports_alternate_refund()
+    }
+
+    async fn health_check(&self) -> Result<(), PaymentError> {
+        // 健康检查不限流
+        self.inner.health_check().await
+    }
 }
 
 #[cfg(test)]
@@ -123,13 +263,13 @@ mod tests {
                 &self,
                 order: &PaymentOrder,
                 config: &PaymentConfig,
-            ) -> Result<OrderStatus, PaymentError>;
+            ) -> Result<(OrderStatus, Option<String>), PaymentError>;
             
             async fn handle_callback(
                 &self,
                 config: &PaymentConfig,
                 callback_data: &serde_json::Value,
-            ) -> Result<(String, OrderStatus), PaymentError>;
+            ) -> Result<ChannelNotification, PaymentError>;
             
             async fn refund(
                 &self,
@@ -151,8 +291,7 @@ mod tests {
             .returning(|_, _, _| {
                 Ok(CreatePaymentResponse {
                     order_id: "test123".to_string(),
-                    payment_url: Some("http://example.com".to_string()),
-                    payment_params: None,
+                    instruction: PaymentInstruction::Redirect("http://example.com".to_string()),
                 })
             });
 
@@ -163,7 +302,7 @@ mod tests {
         let order = PaymentOrder::new(
             1, 1, crate::models::enums::PaymentType::WxH5,
             crate::domain::money::Money::cny(100),
-            None, None, None
+            None, None, None, chrono::Utc::now()
         );
 
         let config = PaymentConfig {
@@ -181,6 +320,8 @@ mod tests {
             notify_url: "http://example.com".to_string(),
             return_url: None,
             extra_config: None,
+            rate_limit_qps: None,
+            rate_limit_burst: None,
             enabled: true,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -188,10 +329,14 @@ mod tests {
 
         let request = CreatePaymentRequest {
             tenant_id: 1,
+            merchant_order_id: None,
             user_id: 1,
             payment_type: crate::models::enums::PaymentType::WxH5,
             amount: 100,
             currency: "CNY".to_string(),
+            settlement_currency: None,
+            exchange_rate: None,
+            transaction_type: TransactionType::Sale,
             product_name: "Test".to_string(),
             product_desc: None,
             callback_url: None,
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use sqlx::MySqlPool;
+
+use crate::error::PaymentError;
+
+/// 商户调用支付接口使用的 API 凭证
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct MerchantCredential {
+    pub api_key: String,
+    /// 凭证所属的商户（租户）
+    pub tenant_id: i64,
+    /// 请求签名密钥，只保存在服务端和商户侧
+    pub secret: String,
+}
+
+#[async_trait]
+pub trait CredentialRepository: Send + Sync {
+    /// 按 API Key 查询启用中的凭证
+    async fn find_by_api_key(&self, api_key: &str) -> Result<Option<MerchantCredential>, PaymentError>;
+}
+
+pub struct MySqlCredentialRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlCredentialRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CredentialRepository for MySqlCredentialRepository {
+    async fn find_by_api_key(&self, api_key: &str) -> Result<Option<MerchantCredential>, PaymentError> {
+        sqlx::query_as(
+            "SELECT api_key, tenant_id, secret FROM merchant_api_keys WHERE api_key = ? AND enabled = TRUE",
+        )
+            .bind(api_key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(PaymentError::Database)
+    }
+}
@@ -1 +1,2 @@
-pub mod payment_repository;
\ No newline at end of file
+pub mod credential_repository;
+pub mod payment_repository;
@@ -0,0 +1,78 @@
+//! 商户回调签名
+//!
+//! 支付结果通知商户时，用商户的回调密钥（`extra_config.callback_secret`）对 `"{timestamp}.{body}"` 签名，
+//! 发送时间（Unix 秒）放在 `X-Timestamp` 请求头中，签名放在 `X-Signature` 请求头中。
+//!
+//! 商户验签方式：
+//! 1. 读取原始请求体字节，不要先反序列化再序列化；
+//! 2. 拼接 `X-Timestamp` 的值、`.` 和请求体字节，以回调密钥为 key 计算 HMAC-SHA256，结果转为小写十六进制；
+//! 3. 与 `X-Signature` 做常量时间比较，一致则为本网关发出的通知；
+//! 4. `X-Timestamp` 与本地时间相差超过 [`CALLBACK_FRESHNESS_SECS`]（5 分钟）的通知应拒绝，
+//!    防止截获的通知被重放。网关重试时会重新签名，重试的通知不会因为超出时间窗口被拒绝。
+//!
+//! 未配置回调密钥的商户不签名，通知中不带 `X-Signature`。
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::Write;
+
+/// 签名请求头
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// 签名时间戳请求头，Unix 秒
+pub const TIMESTAMP_HEADER: &str = "X-Timestamp";
+
+/// 商户验签时允许的时间戳偏差，超出的通知视为重放
+pub const CALLBACK_FRESHNESS_SECS: i64 = 300;
+
+/// 对 `"{timestamp}.{body}"` 签名，时间戳随通知放在 [`TIMESTAMP_HEADER`] 中
+pub fn sign_timestamped(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    sign_callback(secret, &message)
+}
+
+/// 计算消息的 HMAC-SHA256 签名，返回小写十六进制
+pub fn sign_callback(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_known_vector() {
+        let signature = sign_callback("key", b"The quick brown fox jumps over the lazy dog");
+        assert_eq!(signature, "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8");
+    }
+
+    #[test]
+    fn test_sign_depends_on_secret_and_body() {
+        let body = br#"{"order_id":"o1","status":"Success"}"#;
+        let signature = sign_callback("merchant_secret", body);
+        assert_eq!(signature.len(), 64);
+        assert_eq!(signature, sign_callback("merchant_secret", body));
+        assert_ne!(signature, sign_callback("other_secret", body));
+        assert_ne!(signature, sign_callback("merchant_secret", br#"{"order_id":"o2","status":"Success"}"#));
+    }
+
+    #[test]
+    fn test_sign_timestamped() {
+        let body = br#"{"order_id":"o1","status":"Success"}"#;
+        let signature = sign_timestamped("merchant_secret", 1_700_000_000, body);
+        assert_eq!(signature, sign_callback("merchant_secret", br#"1700000000.{"order_id":"o1","status":"Success"}"#));
+        // 时间戳参与签名，改动时间戳后签名失效
+        assert_ne!(signature, sign_timestamped("merchant_secret", 1_700_000_001, body));
+    }
+}
@@ -0,0 +1,144 @@
+//! 商户回调模板
+//!
+//! 商户需要自定义通知格式时，在 `extra_config.callback_template` 中配置 JSON 模板，通知发送前
+//! 将标准通知按模板转换，签名针对转换后的请求体计算。未配置模板时发送标准格式。
+//!
+//! 模板中的字符串可以引用标准通知的字段，`{{a.b}}` 按路径取嵌套字段：
+//!
+//! ```json
+//! { "orderNo": "{{order_id}}", "result": { "state": "{{status}}" }, "desc": "订单 {{order_id}} 已{{status}}", "version": 1 }
+//! ```
+//!
+//! - 整个字符串只有一个占位符时保留字段原来的类型（数字、对象等），字段不存在时为 `null`
+//! - 占位符与其他文字混合时拼接为字符串，字段不存在时替换为空字符串
+//! - 其他值原样输出
+
+use serde_json::{Map, Value};
+
+/// 校验通过的回调模板
+#[derive(Debug, Clone)]
+pub struct CallbackTemplate(Value);
+
+impl CallbackTemplate {
+    /// 校验模板，模板必须是 JSON 对象且占位符格式正确
+    pub fn parse(template: &Value) -> Result<Self, String> {
+        if !template.is_object() {
+            return Err("模板必须是 JSON 对象".to_string());
+        }
+        check(template)?;
+        Ok(Self(template.clone()))
+    }
+
+    /// 按模板转换标准通知
+    pub fn render(&self, notification: &Value) -> Value {
+        render(&self.0, notification)
+    }
+}
+
+fn check(template: &Value) -> Result<(), String> {
+    match template {
+        Value::String(text) => segments(text).map(drop),
+        Value::Array(items) => items.iter().try_for_each(check),
+        Value::Object(fields) => fields.values().try_for_each(check),
+        _ => Ok(()),
+    }
+}
+
+fn render(template: &Value, notification: &Value) -> Value {
+    match template {
+        Value::String(text) => {
+            // 校验时已确认格式正确
+            let segments = segments(text).unwrap_or_default();
+            match segments.as_slice() {
+                [Segment::Field(path)] => lookup(notification, path).cloned().unwrap_or(Value::Null),
+                _ => Value::String(
+                    segments
+                        .iter()
+                        .map(|segment| match segment {
+                            Segment::Text(text) => text.to_string(),
+                            Segment::Field(path) => match lookup(notification, path) {
+                                Some(Value::String(value)) => value.clone(),
+                                Some(Value::Null) | None => String::new(),
+                                Some(value) => value.to_string(),
+                            },
+                        })
+                        .collect(),
+                ),
+            }
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| render(item, notification)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), render(value, notification)))
+                .collect::<Map<_, _>>(),
+        ),
+        other => other.clone(),
+    }
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Field(&'a str),
+}
+
+/// 拆分字符串中的文字和占位符
+fn segments(mut text: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    while let Some(start) = text.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Text(&text[..start]));
+        }
+        let rest = &text[start + 2..];
+        let end = rest.find("}}").ok_or_else(|| format!("占位符未闭合: {}", text))?;
+        let path = rest[..end].trim();
+        if path.is_empty() || path.split('.').any(str::is_empty) {
+            return Err(format!("占位符字段无效: {{{{{}}}}}", &rest[..end]));
+        }
+        segments.push(Segment::Field(path));
+        text = &rest[end + 2..];
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+fn lookup<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_template() {
+        let template = CallbackTemplate::parse(&json!({
+            "orderNo": "{{order_id}}",
+            "result": { "state": "{{status}}", "paid": "{{amount}}" },
+            "desc": "订单 {{order_id}} 金额 {{amount}}",
+            "missing": "{{extra.none}}",
+            "version": 1
+        }))
+        .unwrap();
+
+        let rendered = template.render(&json!({ "order_id": "o1", "status": "SUCCESS", "amount": 100 }));
+        assert_eq!(rendered, json!({
+            "orderNo": "o1",
+            "result": { "state": "SUCCESS", "paid": 100 },
+            "desc": "订单 o1 金额 100",
+            "missing": null,
+            "version": 1
+        }));
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        assert!(CallbackTemplate::parse(&json!("{{order_id}}")).is_err());
+        assert!(CallbackTemplate::parse(&json!({ "orderNo": "{{order_id" })).is_err());
+        assert!(CallbackTemplate::parse(&json!({ "orderNo": ["{{}}"] })).is_err());
+        assert!(CallbackTemplate::parse(&json!({ "orderNo": "{{order..id}}" })).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use crate::models::enums::PaymentType;
+
+/// 单个渠道的订单计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ChannelCounters {
+    /// 向渠道发起下单的次数
+    pub created: u64,
+    /// 支付成功的订单数
+    pub succeeded: u64,
+    /// 渠道下单失败或支付失败的订单数
+    pub failed: u64,
+}
+
+impl ChannelCounters {
+    /// 支付成功率：成功数 / 发起下单数，尚未下单时为空
+    pub fn success_rate(&self) -> Option<f64> {
+        (self.created > 0).then(|| self.succeeded as f64 / self.created as f64)
+    }
+}
+
+/// 订单在渠道上的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentOutcome {
+    Created,
+    Succeeded,
+    Failed,
+}
+
+/// 按渠道统计的进程内支付计数，服务重启后清零，由 Prometheus 按计数器语义处理
+#[derive(Default)]
+pub struct ChannelMetrics {
+    counters: Mutex<HashMap<PaymentType, ChannelCounters>>,
+}
+
+impl ChannelMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, payment_type: PaymentType, outcome: PaymentOutcome) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let counter = counters.entry(payment_type).or_default();
+        match outcome {
+            PaymentOutcome::Created => counter.created += 1,
+            PaymentOutcome::Succeeded => counter.succeeded += 1,
+            PaymentOutcome::Failed => counter.failed += 1,
+        }
+    }
+
+    /// 当前各渠道的计数，未产生过订单的渠道不出现
+    pub fn snapshot(&self) -> HashMap<PaymentType, ChannelCounters> {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// 按 Prometheus 文本格式输出计数，渠道按名称排序保证输出稳定
+pub fn render_prometheus(snapshot: &HashMap<PaymentType, ChannelCounters>) -> String {
+    let mut channels: Vec<(String, &ChannelCounters)> = snapshot
+        .iter()
+        .map(|(payment_type, counters)| (payment_type.to_string(), counters))
+        .collect();
+    channels.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut output = String::new();
+    output.push_str("# HELP payment_orders_total Payment orders per channel and outcome.\n");
+    output.push_str("# TYPE payment_orders_total counter\n");
+    for (channel, counters) in &channels {
+        for (outcome, value) in [
+            ("created", counters.created),
+            ("succeeded", counters.succeeded),
+            ("failed", counters.failed),
+        ] {
+            let _ = writeln!(output, "payment_orders_total{{channel=\"{}\",outcome=\"{}\"}} {}", channel, outcome, value);
+        }
+    }
+
+    output.push_str("# HELP payment_success_rate Succeeded orders divided by created orders per channel.\n");
+    output.push_str("# TYPE payment_success_rate gauge\n");
+    for (channel, counters) in &channels {
+        if let Some(rate) = counters.success_rate() {
+            let _ = writeln!(output, "payment_success_rate{{channel=\"{}\"}} {}", channel, rate);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus() {
+        let metrics = ChannelMetrics::new();
+        metrics.record(PaymentType::WxH5, PaymentOutcome::Created);
+        metrics.record(PaymentType::WxH5, PaymentOutcome::Created);
+        metrics.record(PaymentType::WxH5, PaymentOutcome::Succeeded);
+
+        let output = render_prometheus(&metrics.snapshot());
+        assert!(output.contains("payment_orders_total{channel=\"WX_H5\",outcome=\"created\"} 2\n"));
+        assert!(output.contains("payment_orders_total{channel=\"WX_H5\",outcome=\"failed\"} 0\n"));
+        assert!(output.contains("payment_success_rate{channel=\"WX_H5\"} 0.5\n"));
+    }
+}
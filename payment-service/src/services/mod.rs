@@ -1 +1,8 @@
-pub mod payment_service;
\ No newline at end of file
+pub mod callback_signer;
+pub mod callback_template;
+pub mod ledger;
+pub mod metrics;
+pub mod outbox;
+pub mod payment_service;
+pub mod rate_limiter;
+pub mod reconciliation;
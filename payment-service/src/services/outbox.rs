@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use lapin::options::{BasicPublishOptions, ConfirmSelectOptions, ExchangeDeclareOptions};
+use lapin::types::{FieldTable, ShortString};
+use lapin::{BasicProperties, Channel, Connection, ConnectionProperties, ExchangeKind};
+
+use crate::domain::events::OutboxEvent;
+use crate::error::PaymentError;
+
+/// outbox 事件发布器
+///
+/// 只有返回 `Ok` 后事件才会标记为已投递，发布失败的事件会在下一轮重新发布，
+/// 因此下游可能收到重复消息，需要按消息ID（outbox 记录ID）去重
+#[async_trait]
+pub trait OutboxPublisher: Send + Sync {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), PaymentError>;
+}
+
+/// 通过 RabbitMQ 主题交换机发布事件，路由键为事件类型
+pub struct RabbitMqPublisher {
+    // 连接关闭后通道随之失效，需要与通道一起持有
+    _connection: Connection,
+    channel: Channel,
+    exchange: String,
+}
+
+impl RabbitMqPublisher {
+    /// 连接 RabbitMQ 并声明持久化的主题交换机，开启发布确认
+    pub async fn connect(url: &str, exchange: &str) -> Result<Self, PaymentError> {
+        let connection = Connection::connect(url, ConnectionProperties::default())
+            .await
+            .map_err(rabbitmq_error)?;
+        let channel = connection.create_channel().await.map_err(rabbitmq_error)?;
+
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await
+            .map_err(rabbitmq_error)?;
+        channel
+            .exchange_declare(
+                exchange,
+                ExchangeKind::Topic,
+                ExchangeDeclareOptions { durable: true, ..Default::default() },
+                FieldTable::default(),
+            )
+            .await
+            .map_err(rabbitmq_error)?;
+
+        Ok(Self {
+            _connection: connection,
+            channel,
+            exchange: exchange.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl OutboxPublisher for RabbitMqPublisher {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), PaymentError> {
+        let body = serde_json::to_vec(event).map_err(|e| PaymentError::Internal(e.to_string()))?;
+        let properties = BasicProperties::default()
+            .with_message_id(ShortString::from(event.id.to_string()))
+            .with_content_type(ShortString::from("application/json".to_string()))
+            // 持久化消息
+            .with_delivery_mode(2);
+
+        let confirm = self.channel
+            .basic_publish(
+                self.exchange.as_str(),
+                event.event_type.as_str(),
+                BasicPublishOptions::default(),
+                &body,
+                properties,
+            )
+            .await
+            .map_err(rabbitmq_error)?
+            .await
+            .map_err(rabbitmq_error)?;
+
+        if confirm.is_nack() {
+            return Err(PaymentError::ExternalApi {
+                code: "RABBITMQ".to_string(),
+                message: format!("broker 拒绝事件 {}", event.id),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn rabbitmq_error(e: lapin::Error) -> PaymentError {
+    PaymentError::ExternalApi {
+        code: "RABBITMQ".to_string(),
+        message: e.to_string(),
+    }
+}
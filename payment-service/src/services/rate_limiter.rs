@@ -0,0 +1,158 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+use crate::error::PaymentError;
+
+/// 商户限流器（令牌桶）
+///
+/// 与风控无关，只限制单个商户的请求速率：每秒补充 `qps` 个令牌，桶容量为 `burst`
+#[async_trait]
+pub trait MerchantRateLimiter: Send + Sync {
+    /// 尝试获取一个令牌，失败时返回需要等待的时间
+    async fn try_acquire(&self, key: &str, qps: u32, burst: u32) -> Result<Result<(), Duration>, PaymentError>;
+}
+
+/// 令牌桶状态
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn take(&mut self, qps: u32, burst: u32, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * qps as f64).min(burst as f64);
+        self.updated_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / qps as f64))
+        }
+    }
+}
+
+/// 进程内令牌桶，适用于单实例部署和测试
+#[derive(Default)]
+pub struct InMemoryRateLimiter {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl InMemoryRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl MerchantRateLimiter for InMemoryRateLimiter {
+    async fn try_acquire(&self, key: &str, qps: u32, burst: u32) -> Result<Result<(), Duration>, PaymentError> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().await;
+        let bucket = buckets.entry(key.to_string()).or_insert(TokenBucket {
+            tokens: burst as f64,
+            updated_at: now,
+        });
+
+        Ok(bucket.take(qps, burst, now))
+    }
+}
+
+/// 基于 Redis 的分布式令牌桶，多实例共享同一商户的配额
+pub struct RedisRateLimiter {
+    conn: redis::aio::ConnectionManager,
+}
+
+impl RedisRateLimiter {
+    pub async fn new(redis_url: &str) -> Result<Self, PaymentError> {
+        let client = redis::Client::open(redis_url)
+            .map_err(|e| PaymentError::Configuration(format!("Redis地址无效: {}", e)))?;
+        let conn = client.get_connection_manager()
+            .await
+            .map_err(|e| PaymentError::Internal(format!("Redis连接失败: {}", e)))?;
+
+        Ok(Self { conn })
+    }
+}
+
+/// 令牌桶 Lua 脚本，返回 {是否获取成功, 需要等待的毫秒数}
+const TOKEN_BUCKET_SCRIPT: &str = r"
+    local qps = tonumber(ARGV[1])
+    local burst = tonumber(ARGV[2])
+    local now = tonumber(ARGV[3])
+
+    local bucket = redis.call('hmget', KEYS[1], 'tokens', 'ts')
+    local tokens = tonumber(bucket[1]) or burst
+    local ts = tonumber(bucket[2]) or now
+    tokens = math.min(burst, tokens + math.max(0, now - ts) * qps / 1000)
+
+    local allowed = 0
+    local wait = 0
+    if tokens >= 1 then
+        tokens = tokens - 1
+        allowed = 1
+    else
+        wait = math.ceil((1 - tokens) * 1000 / qps)
+    end
+
+    redis.call('hset', KEYS[1], 'tokens', tokens, 'ts', now)
+    redis.call('pexpire', KEYS[1], math.ceil(burst * 1000 / qps) + 1000)
+    return {allowed, wait}
+";
+
+#[async_trait]
+impl MerchantRateLimiter for RedisRateLimiter {
+    async fn try_acquire(&self, key: &str, qps: u32, burst: u32) -> Result<Result<(), Duration>, PaymentError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut conn = self.conn.clone();
+        let (allowed, wait_ms): (i64, i64) = redis::Script::new(TOKEN_BUCKET_SCRIPT)
+            .key(format!("payment:rate_limit:{}", key))
+            .arg(qps)
+            .arg(burst)
+            .arg(now)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| PaymentError::Internal(format!("Redis限流失败: {}", e)))?;
+
+        if allowed == 1 {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(Duration::from_millis(wait_ms.max(1) as u64)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket { tokens: 2.0, updated_at: start };
+
+        assert!(bucket.take(10, 2, start).is_ok());
+        assert!(bucket.take(10, 2, start).is_ok());
+
+        // 桶已空，10 QPS 需要等待 100ms
+        let wait = bucket.take(10, 2, start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(100));
+
+        // 100ms 后补充一个令牌
+        assert!(bucket.take(10, 2, start + Duration::from_millis(100)).is_ok());
+
+        // 长时间空闲也不会超过桶容量
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.take(10, 2, later).is_ok());
+        assert!(bucket.take(10, 2, later).is_ok());
+        assert!(bucket.take(10, 2, later).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! 渠道对账
+//!
+//! 按账单日比对本地已支付的订单与渠道的交易记录（下载的对账单或查询结果），列出两边不一致的交易：
+//! - 本地有、渠道没有：渠道侧未扣款却被标记为已支付，需要排查回调
+//! - 渠道有、本地没有：通常是回调丢失，订单仍停留在待支付
+//! - 两边都有但金额不同
+//!
+//! 交易按发给渠道的商户订单号匹配，通常就是本地订单号；银联订单号只允许字母和数字，
+//! 按下单时相同的规则转换后匹配。本地订单按支付成功时间归入账单日，与渠道账单的切分口径一致。
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::domain::money::{Currency, Money};
+use crate::domain::payment::PaymentOrder;
+use crate::error::PaymentError;
+use crate::models::enums::PaymentType;
+use crate::payment::providers::unionpay::UnionPayStrategy;
+use crate::repository::payment_repository::PaymentRepository;
+
+/// 渠道侧的一笔成功交易
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelTransaction {
+    /// 发给渠道的商户订单号，见 [`channel_order_id`]
+    pub order_id: String,
+    pub third_party_order_id: Option<String>,
+    pub amount: Money,
+}
+
+/// 渠道交易记录来源
+#[async_trait]
+pub trait SettlementSource: Send + Sync {
+    /// 渠道在账单日 `date` 内成功的交易
+    async fn fetch_transactions(&self, payment_type: PaymentType, date: NaiveDate) -> Result<Vec<ChannelTransaction>, PaymentError>;
+}
+
+/// 读取已下载到本地的对账单，路径为 `<dir>/<支付方式>/<YYYY-MM-DD>.csv`
+///
+/// 文件首行为表头，之后每行为 `order_id,third_party_order_id,amount,currency`，
+/// 金额为最小货币单位，渠道订单号可以为空
+pub struct SettlementFileSource {
+    dir: PathBuf,
+}
+
+impl SettlementFileSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self, payment_type: PaymentType, date: NaiveDate) -> PathBuf {
+        self.dir
+            .join(payment_type.to_string())
+            .join(format!("{}.csv", date.format("%Y-%m-%d")))
+    }
+}
+
+#[async_trait]
+impl SettlementSource for SettlementFileSource {
+    async fn fetch_transactions(&self, payment_type: PaymentType, date: NaiveDate) -> Result<Vec<ChannelTransaction>, PaymentError> {
+        let path = self.path(payment_type, date);
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| PaymentError::Internal(format!("读取对账单失败: {} ({})", path.display(), e)))?;
+        parse_settlement(&content)
+            .map_err(|e| PaymentError::Internal(format!("对账单格式错误: {} ({})", path.display(), e)))
+    }
+}
+
+fn parse_settlement(content: &str) -> Result<Vec<ChannelTransaction>, String> {
+    content
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [order_id, third_party_order_id, amount, currency] = fields[..] else {
+                return Err(format!("第 {} 行应有 4 列", index + 1));
+            };
+            let amount = amount
+                .parse::<i64>()
+                .map_err(|_| format!("第 {} 行金额无效: {}", index + 1, amount))?;
+            let currency = Currency::from_code(currency)
+                .ok_or_else(|| format!("第 {} 行货币无效: {}", index + 1, currency))?;
+
+            Ok(ChannelTransaction {
+                order_id: order_id.to_string(),
+                third_party_order_id: (!third_party_order_id.is_empty()).then(|| third_party_order_id.to_string()),
+                amount: Money::new(amount, currency),
+            })
+        })
+        .collect()
+}
+
+/// 对账结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReconStatus {
+    /// 两边一致
+    Matched,
+    /// 只有本地有
+    LocalOnly,
+    /// 只有渠道有
+    ChannelOnly,
+    /// 两边都有但金额不同
+    AmountMismatch,
+}
+
+/// 对账报告中的一行
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReconEntry {
+    pub order_id: String,
+    pub third_party_order_id: Option<String>,
+    pub local_amount: Option<Money>,
+    pub channel_amount: Option<Money>,
+    pub status: ReconStatus,
+}
+
+/// 单个支付方式在一个账单日的对账报告
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReconReport {
+    pub payment_type: PaymentType,
+    pub date: NaiveDate,
+    /// 先按本地订单支付顺序，之后是只有渠道有的交易
+    pub entries: Vec<ReconEntry>,
+}
+
+impl ReconReport {
+    pub fn count(&self, status: ReconStatus) -> usize {
+        self.entries.iter().filter(|entry| entry.status == status).count()
+    }
+
+    /// 需要人工核对的行
+    pub fn discrepancies(&self) -> impl Iterator<Item = &ReconEntry> {
+        self.entries.iter().filter(|entry| entry.status != ReconStatus::Matched)
+    }
+
+    /// 两边完全一致
+    pub fn is_balanced(&self) -> bool {
+        self.discrepancies().next().is_none()
+    }
+}
+
+/// 本地订单在渠道侧的商户订单号，渠道账单按此记录交易
+pub fn channel_order_id(payment_type: PaymentType, order_id: &str) -> String {
+    match payment_type {
+        PaymentType::UnionPayH5 | PaymentType::UnionPaySdk | PaymentType::ScanPayUnion => {
+            UnionPayStrategy::union_order_id(order_id)
+        }
+        _ => order_id.to_string(),
+    }
+}
+
+/// 比对本地订单与渠道交易
+pub fn reconcile(
+    payment_type: PaymentType,
+    date: NaiveDate,
+    local: &[PaymentOrder],
+    channel: Vec<ChannelTransaction>,
+) -> ReconReport {
+    let mut channel: BTreeMap<String, ChannelTransaction> = channel
+        .into_iter()
+        .map(|transaction| (transaction.order_id.clone(), transaction))
+        .collect();
+
+    let mut entries = Vec::with_capacity(local.len() + channel.len());
+    for order in local {
+        let transaction = channel.remove(&channel_order_id(payment_type, &order.order_id));
+        let status = match &transaction {
+            None => ReconStatus::LocalOnly,
+            Some(transaction) if transaction.amount != order.paid_amount() => ReconStatus::AmountMismatch,
+            Some(_) => ReconStatus::Matched,
+        };
+        let third_party_order_id = order
+            .third_party_order_id
+            .clone()
+            .or_else(|| transaction.as_ref().and_then(|t| t.third_party_order_id.clone()));
+
+        entries.push(ReconEntry {
+            order_id: order.order_id.clone(),
+            third_party_order_id,
+            local_amount: Some(order.paid_amount()),
+            channel_amount: transaction.map(|t| t.amount),
+            status,
+        });
+    }
+
+    entries.extend(channel.into_values().map(|transaction| ReconEntry {
+        order_id: transaction.order_id,
+        third_party_order_id: transaction.third_party_order_id,
+        local_amount: None,
+        channel_amount: Some(transaction.amount),
+        status: ReconStatus::ChannelOnly,
+    }));
+
+    ReconReport { payment_type, date, entries }
+}
+
+/// 按账单日生成对账报告
+pub struct ReconciliationService {
+    repository: Arc<dyn PaymentRepository>,
+    source: Arc<dyn SettlementSource>,
+    /// 账单日所在时区，默认为北京时间
+    offset: FixedOffset,
+}
+
+impl ReconciliationService {
+    pub fn new(repository: Arc<dyn PaymentRepository>, source: Arc<dyn SettlementSource>) -> Self {
+        Self {
+            repository,
+            source,
+            offset: FixedOffset::east_opt(8 * 3600).expect("valid offset"),
+        }
+    }
+
+    /// 设置账单日所在时区，与渠道切分账单的时区保持一致
+    pub fn with_offset(mut self, offset: FixedOffset) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// 生成 `payment_type` 在账单日 `date` 的对账报告
+    pub async fn generate_report(&self, payment_type: PaymentType, date: NaiveDate) -> Result<ReconReport, PaymentError> {
+        let (start, end) = self.day_bounds(date);
+        let local = self.repository.find_paid_between(payment_type, start, end).await?;
+        let channel = self.source.fetch_transactions(payment_type, date).await?;
+
+        let report = reconcile(payment_type, date, &local, channel);
+        if !report.is_balanced() {
+            tracing::warn!(
+                "{} {} 对账不平: 本地多 {} 笔, 渠道多 {} 笔, 金额不符 {} 笔",
+                payment_type,
+                date,
+                report.count(ReconStatus::LocalOnly),
+                report.count(ReconStatus::ChannelOnly),
+                report.count(ReconStatus::AmountMismatch),
+            );
+        }
+        Ok(report)
+    }
+
+    /// 账单日在 UTC 下的起止时间 `[start, end)`
+    fn day_bounds(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = date
+            .and_time(NaiveTime::MIN)
+            .and_local_timezone(self.offset)
+            .unwrap()
+            .with_timezone(&Utc);
+        (start, start + Duration::days(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(amount: i64, third_party_order_id: Option<&str>) -> PaymentOrder {
+        let mut order = PaymentOrder::new(1, 100, PaymentType::WxH5, Money::cny(amount), None, None, None, Utc::now());
+        order.third_party_order_id = third_party_order_id.map(str::to_string);
+        order
+    }
+
+    fn transaction(order_id: &str, amount: i64) -> ChannelTransaction {
+        ChannelTransaction {
+            order_id: order_id.to_string(),
+            third_party_order_id: Some(format!("wx_{}", order_id)),
+            amount: Money::cny(amount),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_discrepancies() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let matched = order(10000, Some("wx_1"));
+        let mismatched = order(20000, None);
+        let local_only = order(30000, None);
+        let local = vec![matched.clone(), mismatched.clone(), local_only.clone()];
+        let channel = vec![
+            transaction("CHANNEL_ONLY", 5000),
+            transaction(&mismatched.order_id, 19900),
+            transaction(&matched.order_id, 10000),
+        ];
+
+        let report = reconcile(PaymentType::WxH5, date, &local, channel);
+
+        let statuses: Vec<(&str, ReconStatus)> = report
+            .entries
+            .iter()
+            .map(|entry| (entry.order_id.as_str(), entry.status))
+            .collect();
+        assert_eq!(statuses, vec![
+            (matched.order_id.as_str(), ReconStatus::Matched),
+            (mismatched.order_id.as_str(), ReconStatus::AmountMismatch),
+            (local_only.order_id.as_str(), ReconStatus::LocalOnly),
+            ("CHANNEL_ONLY", ReconStatus::ChannelOnly),
+        ]);
+
+        // 本地没有渠道订单号时取渠道账单中的
+        assert_eq!(report.entries[1].third_party_order_id, Some(format!("wx_{}", mismatched.order_id)));
+        assert_eq!(report.entries[1].channel_amount, Some(Money::cny(19900)));
+        assert_eq!(report.discrepancies().count(), 3);
+        assert!(!report.is_balanced());
+    }
+
+    #[test]
+    fn test_reconcile_unionpay_order_id() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let mut paid = order(10000, Some("union_1"));
+        paid.payment_type = PaymentType::UnionPayH5;
+        assert!(paid.order_id.contains('-'));
+
+        // 银联账单中的订单号去掉了本地订单号中的非字母数字字符
+        let union_order_id: String = paid.order_id.chars().filter(char::is_ascii_alphanumeric).collect();
+        let report = reconcile(PaymentType::UnionPayH5, date, &[paid.clone()], vec![transaction(&union_order_id, 10000)]);
+        assert!(report.is_balanced());
+        assert_eq!(report.entries[0].order_id, paid.order_id);
+
+        // 其他渠道仍按本地订单号匹配
+        let report = reconcile(PaymentType::WxH5, date, &[order(10000, None)], vec![transaction(&union_order_id, 10000)]);
+        assert_eq!(report.count(ReconStatus::LocalOnly), 1);
+        assert_eq!(report.count(ReconStatus::ChannelOnly), 1);
+    }
+
+    #[test]
+    fn test_parse_settlement() {
+        let content = "order_id,third_party_order_id,amount,currency\nP001,wx_001,10000,CNY\n\nP002,,500,USD\n";
+        let transactions = parse_settlement(content).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[1].third_party_order_id, None);
+        assert_eq!(transactions[1].amount, Money::new(500, Currency::USD));
+
+        assert!(parse_settlement("header\nP001,wx_001,abc,CNY").is_err());
+        assert!(parse_settlement("header\nP001,10000").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+//! 金额相关的工具函数
+//!
+//! 实现位于 [`crate::domain::money`]，这里重新导出供不依赖领域模型的调用方使用。
+
+pub use crate::domain::money::format_for_display;
@@ -1,8 +1,28 @@
-use payment_service::models::payment::{CreatePaymentRequest, RefundRequest};
+use payment_service::models::payment::{CreatePaymentRequest, RefundDestination, RefundRequest, TransactionType};
 use payment_service::models::enums::{PaymentType, OrderStatus};
+use payment_service::handlers::auth::{sign_request, API_KEY_HEADER, TIMESTAMP_HEADER};
+use payment_service::services::callback_signer::SIGNATURE_HEADER;
 use serde_json::json;
 use httpmock::prelude::*;
-use reqwest::Client;
+use reqwest::{Client, Method, RequestBuilder};
+
+/// 测试商户的 API 凭证，需预先写入 merchant_api_keys
+const TEST_API_KEY: &str = "test_key_1";
+const TEST_API_SECRET: &str = "test_secret_1";
+
+/// 按商户鉴权规则签名请求
+fn signed(client: &Client, method: Method, path: &str, body: Option<&impl serde::Serialize>) -> RequestBuilder {
+    let body = body.map(|body| serde_json::to_vec(body).unwrap()).unwrap_or_default();
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = sign_request(TEST_API_SECRET, timestamp, method.as_str(), path, &body);
+
+    client.request(method, format!("http://localhost:3001{}", path))
+        .header(API_KEY_HEADER, TEST_API_KEY)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, signature)
+        .header("Content-Type", "application/json")
+        .body(body)
+}
 
 #[tokio::test]
 #[ignore] // 需要数据库，所以默认忽略
@@ -78,10 +98,14 @@ async fn test_payment_flow() -> anyhow::Result<()> {
     // 1. 创建支付订单
     let create_request = CreatePaymentRequest {
         tenant_id: 1,
+        merchant_order_id: None,
         user_id: 100,
         payment_type: PaymentType::WxH5,
         amount: 10000,
         currency: "CNY".to_string(),
+        settlement_currency: None,
+        exchange_rate: None,
+        transaction_type: TransactionType::Sale,
         product_name: "测试商品".to_string(),
         product_desc: Some("商品描述".to_string()),
         callback_url: Some("http://example.com/callback".to_string()),
@@ -89,8 +113,7 @@ async fn test_payment_flow() -> anyhow::Result<()> {
         extra_data: None,
     };
 
-    let response = client.post("http://localhost:3001/api/v1/payment/create")
-        .json(&create_request)
+    let response = signed(&client, Method::POST, "/api/v1/payment/create", Some(&create_request))
         .send()
         .await?;
 
@@ -105,7 +128,7 @@ async fn test_payment_flow() -> anyhow::Result<()> {
     wechat_unifiedorder_mock.assert();
 
     // 2. 查询支付订单
-    let response = client.get(&format!("http://localhost:3001/api/v1/payment/query/{}", order_id))
+    let response = signed(&client, Method::GET, &format!("/api/v1/payment/query/{}", order_id), None::<&()>)
         .send()
         .await?;
 
@@ -135,7 +158,7 @@ async fn test_payment_flow() -> anyhow::Result<()> {
     assert_eq!(response.status(), 200);
 
     // 4. 再次查询确认状态已更新
-    let response = client.get(&format!("http://localhost:3001/api/v1/payment/query/{}", order_id))
+    let response = signed(&client, Method::GET, &format!("/api/v1/payment/query/{}", order_id), None::<&()>)
         .send()
         .await?;
 
@@ -148,12 +171,13 @@ async fn test_payment_flow() -> anyhow::Result<()> {
     // 5. 申请退款
     let refund_request = RefundRequest {
         order_id: order_id.clone(),
-        refund_amount: 10000,
+        refund_amount: 10000.into(),
         refund_reason: Some("测试退款".to_string()),
+        destination: RefundDestination::Original,
+        merchant_refund_no: None,
     };
 
-    let response = client.post("http://localhost:3001/api/v1/payment/refund")
-        .json(&refund_request)
+    let response = signed(&client, Method::POST, "/api/v1/payment/refund", Some(&refund_request))
         .send()
         .await?;
 
@@ -167,7 +191,7 @@ async fn test_payment_flow() -> anyhow::Result<()> {
     wechat_refund_mock.assert();
 
     // 6. 再次查询确认状态为已退款
-    let response = client.get(&format!("http://localhost:3001/api/v1/payment/query/{}", order_id))
+    let response = signed(&client, Method::GET, &format!("/api/v1/payment/query/{}", order_id), None::<&()>)
         .send()
         .await?;
 
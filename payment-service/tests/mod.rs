@@ -17,22 +17,23 @@ mod payment_tests {
             Some("http://example.com/callback".to_string()),
             Some("http://example.com/notify".to_string()),
             None,
+            Utc::now(),
         );
 
         // 测试初始状态
         assert_eq!(order.status, OrderStatus::Pending);
 
         // 测试发起支付
-        order.initiate_payment(Some("http://pay.example.com".to_string())).unwrap();
+        order.initiate_payment(Some("http://pay.example.com".to_string()), Utc::now()).unwrap();
         assert_eq!(order.status, OrderStatus::Processing);
 
         // 测试支付成功
-        order.complete_payment("third_party_order_123".to_string()).unwrap();
+        order.complete_payment("third_party_order_123".to_string(), Utc::now()).unwrap();
         assert_eq!(order.status, OrderStatus::Success);
         assert_eq!(order.third_party_order_id, Some("third_party_order_123".to_string()));
 
         // 测试退款
-        order.request_refund("refund_123".to_string(), 10000).unwrap();
+        order.request_refund("refund_123".to_string(), 10000, Utc::now()).unwrap();
         assert_eq!(order.status, OrderStatus::Refunded);
 
         // 验证事件数量
@@ -49,15 +50,16 @@ mod payment_tests {
             None,
             None,
             None,
+            Utc::now(),
         );
 
         // 尝试直接从Pending到Success，应该失败
-        let result = order.complete_payment("test".to_string());
+        let result = order.complete_payment("test".to_string(), Utc::now());
         assert!(result.is_err());
 
         // 正确流程：Pending -> Processing -> Success
-        order.initiate_payment(None).unwrap();
-        let result = order.complete_payment("test".to_string());
+        order.initiate_payment(None, Utc::now()).unwrap();
+        let result = order.complete_payment("test".to_string(), Utc::now());
         assert!(result.is_ok());
     }
 }
\ No newline at end of file
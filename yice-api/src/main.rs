@@ -24,7 +24,10 @@ async fn main() {
 
     println!("server started on port 3000");
     info!("listening on port 3000");
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(common::shutdown::signal())
+        .await
+        .unwrap();
 
 }
 